@@ -0,0 +1,98 @@
+//! `#[derive(FlowAction)]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashMap;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FlowAction can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut seen: HashMap<String, &syn::Ident> = HashMap::new();
+    let mut variants = Vec::new();
+    let mut names = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "FlowAction variants must not have fields",
+            ));
+        }
+        let name = variant_name(variant)?;
+        if let Some(previous) = seen.insert(name.clone(), &variant.ident) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!("action name `{name}` is already used by `{previous}`"),
+            ));
+        }
+        variants.push(&variant.ident);
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::agentflow::core::typed_flow::FlowAction for #ident #ty_generics #where_clause {
+            fn name(&self) -> &'static str {
+                match self {
+                    #( Self::#variants => #names, )*
+                }
+            }
+
+            fn from_name(name: &str) -> ::core::option::Option<Self> {
+                match name {
+                    #( #names => ::core::option::Option::Some(Self::#variants), )*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn all() -> ::std::vec::Vec<Self> {
+                ::std::vec![ #( Self::#variants ),* ]
+            }
+        }
+    })
+}
+
+/// `#[action(rename = "...")]`, or the variant identifier in `snake_case`.
+fn variant_name(variant: &syn::Variant) -> syn::Result<String> {
+    let mut rename = None;
+    for attr in variant.attrs.iter().filter(|a| a.path().is_ident("action")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let lit: LitStr = meta.value()?.parse()?;
+                rename = Some(lit.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(rename.unwrap_or_else(|| snake_case(&variant.ident.to_string())))
+}
+
+fn snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len() + 4);
+    let chars: Vec<char> = ident.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev_lower = i > 0 && !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let prev_upper = i > 0 && chars[i - 1].is_uppercase();
+            if prev_lower || (prev_upper && next_lower) {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(*c);
+        }
+    }
+    out
+}
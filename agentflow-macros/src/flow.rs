@@ -0,0 +1,196 @@
+//! `typed_flow!`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, Expr, Ident, Token, Type};
+
+pub(crate) struct FlowDef {
+    state: Type,
+    action: Type,
+    max_steps: Option<Expr>,
+    nodes: Vec<NodeDef>,
+    edges: Vec<EdgeDef>,
+    error_edges: Vec<ErrorEdgeDef>,
+}
+
+struct NodeDef {
+    name: Ident,
+    node: Expr,
+}
+
+struct EdgeDef {
+    from: Ident,
+    to: Ident,
+    action: Expr,
+}
+
+struct ErrorEdgeDef {
+    from: Ident,
+    to: Ident,
+}
+
+impl Parse for NodeDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let node = if input.parse::<Option<Token![:]>>()?.is_some() {
+            input.parse()?
+        } else {
+            // Shorthand: `draft` means `draft()`, as generated by #[typed_node].
+            syn::parse_quote!(#name())
+        };
+        Ok(Self { name, node })
+    }
+}
+
+impl Parse for EdgeDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let from = input.parse()?;
+        input.parse::<Token![->]>()?;
+        let to = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let action = input.parse()?;
+        Ok(Self { from, to, action })
+    }
+}
+
+impl Parse for ErrorEdgeDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let from = input.parse()?;
+        input.parse::<Token![->]>()?;
+        let to = input.parse()?;
+        Ok(Self { from, to })
+    }
+}
+
+fn braced_list<T: Parse>(input: ParseStream) -> syn::Result<Vec<T>> {
+    let content;
+    braced!(content in input);
+    Ok(Punctuated::<T, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+impl Parse for FlowDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut state = None;
+        let mut action = None;
+        let mut max_steps = None;
+        let mut nodes = None;
+        let mut edges = None;
+        let mut error_edges = None;
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![:]>()?;
+            let duplicate = match key.to_string().as_str() {
+                "state" => state.replace(input.parse::<Type>()?).is_some(),
+                "action" => action.replace(input.parse::<Type>()?).is_some(),
+                "max_steps" => max_steps.replace(input.parse::<Expr>()?).is_some(),
+                "nodes" => nodes.replace(braced_list(input)?).is_some(),
+                "edges" => edges.replace(braced_list(input)?).is_some(),
+                "error_edges" => error_edges.replace(braced_list(input)?).is_some(),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &key,
+                        "expected one of `state`, `action`, `max_steps`, `nodes`, `edges`, `error_edges`",
+                    ))
+                }
+            };
+            if duplicate {
+                return Err(syn::Error::new_spanned(
+                    &key,
+                    format!("`{key}` is given more than once"),
+                ));
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        let missing = |what: &str| syn::Error::new(input.span(), format!("missing `{what}:`"));
+        Ok(Self {
+            state: state.ok_or_else(|| missing("state"))?,
+            action: action.ok_or_else(|| missing("action"))?,
+            max_steps,
+            nodes: nodes.ok_or_else(|| missing("nodes"))?,
+            edges: edges.unwrap_or_default(),
+            error_edges: error_edges.unwrap_or_default(),
+        })
+    }
+}
+
+pub(crate) fn expand(def: FlowDef) -> syn::Result<TokenStream> {
+    if def.nodes.is_empty() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "typed_flow! needs at least one node",
+        ));
+    }
+
+    let mut declared = HashSet::new();
+    for node in &def.nodes {
+        if !declared.insert(node.name.to_string()) {
+            return Err(syn::Error::new_spanned(
+                &node.name,
+                format!("node `{}` is declared more than once", node.name),
+            ));
+        }
+    }
+    let check = |ident: &Ident| {
+        if declared.contains(&ident.to_string()) {
+            Ok(())
+        } else {
+            Err(syn::Error::new_spanned(
+                ident,
+                format!("unknown node `{ident}`; declare it under `nodes:`"),
+            ))
+        }
+    };
+    for edge in &def.edges {
+        check(&edge.from)?;
+        check(&edge.to)?;
+    }
+    for edge in &def.error_edges {
+        check(&edge.from)?;
+        check(&edge.to)?;
+    }
+
+    let FlowDef {
+        state,
+        action,
+        max_steps,
+        nodes,
+        edges,
+        error_edges,
+    } = def;
+
+    let max_steps = max_steps.map(|steps| quote!(let __flow = __flow.with_max_steps(#steps);));
+    let add_nodes = nodes.iter().map(|NodeDef { name, node }| {
+        let name = name.to_string();
+        quote! {
+            ::agentflow::core::typed_flow::IntoTypedFlowNode::<#state, #action>::add_to(#node, &mut __flow, #name);
+        }
+    });
+    let add_edges = edges.iter().map(|EdgeDef { from, to, action }| {
+        let (from, to) = (from.to_string(), to.to_string());
+        quote!(__flow.add_edge(#from, #action, #to);)
+    });
+    let add_error_edges = error_edges.iter().map(|ErrorEdgeDef { from, to }| {
+        let (from, to) = (from.to_string(), to.to_string());
+        quote!(__flow.add_error_edge(#from, #to);)
+    });
+
+    Ok(quote! {{
+        let __flow = ::agentflow::core::typed_flow::TypedFlow::<#state, #action>::new();
+        #max_steps
+        let mut __flow = __flow;
+        #(#add_nodes)*
+        #(#add_edges)*
+        #(#add_error_edges)*
+        __flow
+    }})
+}
@@ -0,0 +1,160 @@
+//! # agentflow-macros
+//!
+//! Procedural macros that remove the boilerplate from
+//! [`TypedFlow`](https://docs.rs/agentflow/latest/agentflow/core/typed_flow/struct.TypedFlow.html)
+//! definitions. They are re-exported from the `agentflow` crate, so depend on
+//! `agentflow` and import them from there:
+//!
+//! - [`macro@FlowAction`] — derive stable names for an action enum.
+//! - [`macro@typed_node`] — turn an `async fn` into a node constructor.
+//! - [`typed_flow!`] — declare nodes and edges; names are checked at compile time.
+//!
+//! ```rust
+//! use agentflow::core::typed_store::TypedStore;
+//! use agentflow::{typed_flow, typed_node, FlowAction};
+//!
+//! #[derive(Debug, Clone, Default)]
+//! struct Counter { n: u32 }
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
+//! enum Action { Again }
+//!
+//! #[typed_node]
+//! async fn tick(mut store: TypedStore<Counter>) -> (TypedStore<Counter>, Option<Action>) {
+//!     store.inner.n += 1;
+//!     let next = (store.inner.n < 3).then_some(Action::Again);
+//!     (store, next)
+//! }
+//!
+//! # #[tokio::main] async fn main() {
+//! let flow = typed_flow! {
+//!     state: Counter,
+//!     action: Action,
+//!     max_steps: 10,
+//!     nodes: { tick },
+//!     edges: { tick -> tick: Action::Again },
+//! };
+//! let result = flow.run(TypedStore::new(Counter::default())).await;
+//! assert_eq!(result.inner.n, 3);
+//! # }
+//! ```
+#![warn(missing_docs)]
+
+mod action;
+mod flow;
+mod node;
+
+use proc_macro::TokenStream;
+
+/// Derive `agentflow::core::typed_flow::FlowAction` for an enum of unit
+/// variants.
+///
+/// Each variant is named after its identifier in `snake_case`; use
+/// `#[action(rename = "...")]` on a variant to choose another name. Variants
+/// with fields and duplicate names are rejected at compile time.
+///
+/// ```rust
+/// use agentflow::FlowAction;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
+/// enum Route { NeedsReview, #[action(rename = "ok")] Approved }
+///
+/// assert_eq!(Route::NeedsReview.name(), "needs_review");
+/// assert_eq!(Route::from_name("ok"), Some(Route::Approved));
+/// ```
+#[proc_macro_derive(FlowAction, attributes(action))]
+pub fn derive_flow_action(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    action::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Turn an `async fn` into a function that builds a typed node.
+///
+/// The function must take a single `TypedStore<T>` argument and return
+/// either `(TypedStore<T>, Option<E>)` or
+/// `Result<(TypedStore<T>, Option<E>), AgentFlowError>`. The generated
+/// function has the same name and visibility, takes no arguments, and returns
+/// a `SimpleTypedNode<T, E>` or `TypedResultNode<T, E>` respectively.
+///
+/// ```rust
+/// use agentflow::core::typed_store::TypedStore;
+/// use agentflow::core::typed_flow::SimpleTypedNode;
+/// use agentflow::typed_node;
+///
+/// #[derive(Clone, PartialEq, Eq, Hash)]
+/// enum Action { Next }
+///
+/// #[typed_node]
+/// async fn greet(mut store: TypedStore<String>) -> (TypedStore<String>, Option<Action>) {
+///     store.inner.push_str(", world");
+///     (store, None)
+/// }
+///
+/// let _node: SimpleTypedNode<String, Action> = greet();
+/// ```
+#[proc_macro_attribute]
+pub fn typed_node(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "#[typed_node] takes no arguments",
+        )
+        .into_compile_error()
+        .into();
+    }
+    let input = syn::parse_macro_input!(input as syn::ItemFn);
+    node::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declare a `TypedFlow` graph.
+///
+/// ```text
+/// typed_flow! {
+///     state: MyState,          // T
+///     action: Action,          // E
+///     max_steps: 20,           // optional
+///     nodes: {
+///         draft,               // shorthand for `draft: draft()`
+///         review: review_node(),
+///     },
+///     edges: {
+///         draft -> review: Action::Review,
+///         review -> draft: Action::Revise,
+///     },
+///     error_edges: {           // optional, for result nodes
+///         review -> draft,
+///     },
+/// }
+/// ```
+///
+/// The first node is the start node. Duplicate node names and edges that
+/// refer to undeclared nodes are compile errors:
+///
+/// ```rust,compile_fail
+/// use agentflow::core::typed_store::TypedStore;
+/// use agentflow::{typed_flow, typed_node, FlowAction};
+///
+/// #[derive(Clone, PartialEq, Eq, Hash, FlowAction)]
+/// enum Action { Next }
+///
+/// #[typed_node]
+/// async fn a(s: TypedStore<u32>) -> (TypedStore<u32>, Option<Action>) { (s, None) }
+///
+/// let _flow = typed_flow! {
+///     state: u32,
+///     action: Action,
+///     nodes: { a },
+///     edges: { a -> b: Action::Next }, // error: unknown node `b`
+/// };
+/// ```
+#[proc_macro]
+pub fn typed_flow(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as flow::FlowDef);
+    flow::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
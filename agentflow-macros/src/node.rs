@@ -0,0 +1,141 @@
+//! `#[typed_node]`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{FnArg, GenericArgument, ItemFn, PathArguments, ReturnType, Type, TypePath, TypeTuple};
+
+pub(crate) fn expand(input: ItemFn) -> syn::Result<TokenStream> {
+    let sig = &input.sig;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            sig.fn_token,
+            "#[typed_node] requires an `async fn`",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "#[typed_node] functions cannot be generic",
+        ));
+    }
+    let mut inputs = sig.inputs.iter();
+    let (state, extra) = (inputs.next(), inputs.next());
+    let state_ty = match (state, extra) {
+        (Some(FnArg::Typed(arg)), None) => typed_store_arg(&arg.ty)?,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &sig.inputs,
+                "#[typed_node] functions take exactly one `TypedStore<T>` argument",
+            ))
+        }
+    };
+
+    let ReturnType::Type(_, ret) = &sig.output else {
+        return Err(syn::Error::new_spanned(
+            sig,
+            "#[typed_node] functions must return `(TypedStore<T>, Option<E>)` \
+             or `Result<(TypedStore<T>, Option<E>), AgentFlowError>`",
+        ));
+    };
+    let (action_ty, fallible) = match last_segment(ret, "Result") {
+        Some(args) => match args.first() {
+            Some(GenericArgument::Type(Type::Tuple(tuple))) => (action_of(tuple)?, true),
+            _ => return Err(return_error(ret)),
+        },
+        None => match &**ret {
+            Type::Tuple(tuple) => (action_of(tuple)?, false),
+            _ => return Err(return_error(ret)),
+        },
+    };
+
+    let vis = &input.vis;
+    let name = &sig.ident;
+    // Docs describe the generated constructor; other attributes (lints,
+    // instrumentation) stay on the async body.
+    let docs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .collect();
+    let inner = format_ident!("__{}_typed_node", name);
+    let mut inner_fn = input.clone();
+    inner_fn.sig.ident = inner.clone();
+    inner_fn.vis = syn::Visibility::Inherited;
+    inner_fn.attrs.retain(|a| !a.path().is_ident("doc"));
+
+    let (node_ty, ctor) = if fallible {
+        (
+            quote!(::agentflow::core::typed_flow::TypedResultNode<#state_ty, #action_ty>),
+            quote!(::agentflow::core::typed_flow::create_typed_result_node),
+        )
+    } else {
+        (
+            quote!(::agentflow::core::typed_flow::SimpleTypedNode<#state_ty, #action_ty>),
+            quote!(::agentflow::core::typed_flow::create_typed_node),
+        )
+    };
+
+    Ok(quote! {
+        #(#docs)*
+        #vis fn #name() -> #node_ty {
+            #inner_fn
+            #ctor(#inner)
+        }
+    })
+}
+
+fn typed_store_arg(ty: &Type) -> syn::Result<&Type> {
+    match last_segment(ty, "TypedStore") {
+        Some(args) => match args.first() {
+            Some(GenericArgument::Type(t)) if args.len() == 1 => Ok(t),
+            _ => Err(syn::Error::new_spanned(ty, "expected `TypedStore<T>`")),
+        },
+        None => Err(syn::Error::new_spanned(
+            ty,
+            "the argument of a #[typed_node] function must be `TypedStore<T>`",
+        )),
+    }
+}
+
+fn action_of(tuple: &TypeTuple) -> syn::Result<&Type> {
+    let mut elems = tuple.elems.iter();
+    match (elems.next(), elems.next(), elems.next()) {
+        (Some(_), Some(action), None) => match last_segment(action, "Option") {
+            Some(args) => match args.first() {
+                Some(GenericArgument::Type(t)) if args.len() == 1 => Ok(t),
+                _ => Err(syn::Error::new_spanned(action, "expected `Option<E>`")),
+            },
+            None => Err(syn::Error::new_spanned(action, "expected `Option<E>`")),
+        },
+        _ => Err(syn::Error::new_spanned(
+            tuple,
+            "expected `(TypedStore<T>, Option<E>)`",
+        )),
+    }
+}
+
+/// Generic arguments of `ty` if its last path segment is `name`.
+fn last_segment<'a>(
+    ty: &'a Type,
+    name: &str,
+) -> Option<&'a syn::punctuated::Punctuated<GenericArgument, syn::token::Comma>> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => Some(&args.args),
+        _ => None,
+    }
+}
+
+fn return_error(ret: &Type) -> syn::Error {
+    syn::Error::new_spanned(
+        ret,
+        "expected `(TypedStore<T>, Option<E>)` or \
+         `Result<(TypedStore<T>, Option<E>), AgentFlowError>`",
+    )
+}
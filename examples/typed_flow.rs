@@ -7,13 +7,16 @@ count. The flow loops through Draft → Critique → Revise until the LLM critic
 approves or the revision limit is reached.
 
 This showcases TypedFlow's key advantage over the HashMap-based Flow: the state
-is a plain Rust struct — no string key lookups, full type safety.
+is a plain Rust struct — no string key lookups, full type safety. The graph is
+declared with `#[derive(FlowAction)]`, `#[typed_node]` and `typed_flow!`, so a
+misspelled node name is a compile error rather than a silent dead end.
 
 Requires: OPENAI_API_KEY
 Run with: cargo run --example typed-flow
 */
 
-use agentflow::core::{create_typed_node, TypedFlow, TypedStore};
+use agentflow::core::TypedStore;
+use agentflow::{typed_flow, typed_node, FlowAction};
 use dotenvy::dotenv;
 use rig::prelude::*;
 use rig::{completion::Prompt, providers};
@@ -29,7 +32,7 @@ struct ContentState {
     revisions: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
 enum Action {
     Review,
     Revise,
@@ -44,6 +47,63 @@ async fn llm(system: &str, user: &str) -> String {
     }
 }
 
+// ── Draft node ───────────────────────────────────────────────────────────────
+#[typed_node]
+async fn draft(mut store: TypedStore<ContentState>) -> (TypedStore<ContentState>, Option<Action>) {
+    let state = &store.inner;
+    let prompt = if state.critique.is_empty() {
+        format!(
+            "Write a clear, engaging 3-sentence paragraph about: {}",
+            state.topic
+        )
+    } else {
+        format!(
+            "Revise this paragraph about '{}' based on the feedback.\nFeedback: {}\nWrite only the revised paragraph.",
+            state.topic, state.critique
+        )
+    };
+
+    println!("\n[Draft] Revision {}…", state.revisions + 1);
+    let draft = llm(
+        "You are a skilled technical writer. Output only the paragraph — no preamble.",
+        &prompt,
+    )
+    .await;
+    println!("[Draft]\n{}\n", draft.trim());
+
+    store.inner.draft = draft;
+    store.inner.revisions += 1;
+    (store, Some(Action::Review))
+}
+
+// ── Critique node ────────────────────────────────────────────────────────────
+#[typed_node]
+async fn critique(
+    mut store: TypedStore<ContentState>,
+) -> (TypedStore<ContentState>, Option<Action>) {
+    println!("[Critique] Reviewing draft…");
+    let verdict = llm(
+        "You are a strict editor. Review the paragraph. \
+         Respond with APPROVED or REVISE: <one-sentence reason>. No other text.",
+        &store.inner.draft,
+    )
+    .await;
+    println!("[Critique] {}\n", verdict.trim());
+
+    store.inner.approved = verdict.trim().starts_with("APPROVED");
+    if store.inner.approved {
+        store.inner.critique = String::new();
+        return (store, None);
+    }
+    store.inner.critique = verdict
+        .trim()
+        .strip_prefix("REVISE:")
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    (store, Some(Action::Revise))
+}
+
 #[tokio::main]
 async fn main() {
     dotenv().ok();
@@ -51,81 +111,17 @@ async fn main() {
         .with_env_filter(EnvFilter::new("agentflow=debug,typed_flow=debug"))
         .init();
 
-    let mut flow = TypedFlow::<ContentState, Action>::new().with_max_steps(12);
-
-    // ── Draft node ────────────���───────────────────────────────────────────────
-    let draft_node = create_typed_node(|mut store: TypedStore<ContentState>| async move {
-        let (topic, critique, revisions) = (
-            store.inner.topic.clone(),
-            store.inner.critique.clone(),
-            store.inner.revisions,
-        );
-
-        let prompt = if critique.is_empty() {
-            format!(
-                "Write a clear, engaging 3-sentence paragraph about: {}",
-                topic
-            )
-        } else {
-            format!(
-                "Revise this paragraph about '{}' based on the feedback.\nFeedback: {}\nWrite only the revised paragraph.",
-                topic, critique
-            )
-        };
-
-        println!("\n[Draft] Revision {}…", revisions + 1);
-        let draft = llm(
-            "You are a skilled technical writer. Output only the paragraph — no preamble.",
-            &prompt,
-        )
-        .await;
-        println!("[Draft]\n{}\n", draft.trim());
-
-        store.inner.draft = draft;
-        store.inner.revisions += 1;
-        (store, Some(Action::Review))
-    });
-
-    // ── Critique node ─────────────────────────────────────────────────────────
-    let critique_node = create_typed_node(|mut store: TypedStore<ContentState>| async move {
-        let draft = store.inner.draft.clone();
-
-        println!("[Critique] Reviewing draft…");
-        let verdict = llm(
-            "You are a strict editor. Review the paragraph. \
-             Respond with APPROVED or REVISE: <one-sentence reason>. No other text.",
-            &draft,
-        )
-        .await;
-        println!("[Critique] {}\n", verdict.trim());
-
-        let mut approved = false;
-        if verdict.trim().starts_with("APPROVED") {
-            store.inner.approved = true;
-            store.inner.critique = String::new();
-            approved = true;
-        } else {
-            store.inner.approved = false;
-            store.inner.critique = verdict
-                .trim()
-                .strip_prefix("REVISE:")
-                .unwrap_or("")
-                .trim()
-                .to_string();
-        }
-
-        if approved {
-            (store, None)
-        } else {
-            (store, Some(Action::Revise))
-        }
-    });
-
-    flow.add_node("draft", draft_node);
-    flow.add_node("critique", critique_node);
-
-    flow.add_edge("draft", Action::Review, "critique");
-    flow.add_edge("critique", Action::Revise, "draft");
+    // Node names and edges are checked at compile time.
+    let flow = typed_flow! {
+        state: ContentState,
+        action: Action,
+        max_steps: 12,
+        nodes: { draft, critique },
+        edges: {
+            draft -> critique: Action::Review,
+            critique -> draft: Action::Revise,
+        },
+    };
 
     let topic = "How Rust's borrow checker prevents use-after-free bugs";
 
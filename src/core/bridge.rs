@@ -0,0 +1,345 @@
+//! Adapters between the string-keyed [`Flow`] and the typed [`TypedFlow`].
+//!
+//! Existing pipelines can move to typed state one sub-graph at a time:
+//!
+//! - [`TypedFlowNode`] runs a `TypedFlow<T, E>` as a [`SimpleNode`] inside a
+//!   `Flow`. The typed state lives in one store key as JSON; the flow's final
+//!   action is written to the store's `"action"` key by its
+//!   [`FlowAction::name`], so the enclosing `Flow` can route on it.
+//! - [`FlowTypedNode`] runs a `Flow` as a [`TypedResultNode`] inside a
+//!   `TypedFlow`. `T` is spread into store keys with serde (or kept under one
+//!   key), the flow runs, and `T` is read back from the resulting store.
+//!
+//! LLM usage crosses the bridge both ways, so a [`Budget`] on either side
+//! sees the calls made on the other: `TypedFlowNode` records the typed
+//! flow's calls under the store's [`"llm_usage"`](LLM_USAGE_KEY) key, and
+//! `FlowTypedNode` moves the entries the `Flow` leaves there into the typed
+//! [`FlowContext`]. Calls made before a failure are forwarded too.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::bridge::TypedFlowNode;
+//! use agentflow::core::flow::Flow;
+//! use agentflow::core::typed_flow::{create_typed_node, TypedFlow};
+//! use agentflow::core::typed_store::TypedStore;
+//! use agentflow::FlowAction;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Clone, Default, Serialize, Deserialize)]
+//! struct Ticket { priority: u8 }
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
+//! enum Triage { Urgent, Backlog }
+//!
+//! let mut triage = TypedFlow::<Ticket, Triage>::new();
+//! triage.add_node("score", create_typed_node(|s: TypedStore<Ticket>| async move {
+//!     let action = if s.inner.priority > 3 { Triage::Urgent } else { Triage::Backlog };
+//!     (s, Some(action))
+//! }));
+//!
+//! let mut flow = Flow::new();
+//! flow.add_node("triage", Box::new(TypedFlowNode::new(triage, "ticket")));
+//! // flow.add_edge("triage", "urgent", "page_oncall");
+//! // flow.add_edge("triage", "backlog", "file_issue");
+//! ```
+//!
+//! [`Flow`]: crate::core::flow::Flow
+//! [`TypedFlow`]: crate::core::typed_flow::TypedFlow
+//! [`SimpleNode`]: crate::core::node::SimpleNode
+//! [`TypedResultNode`]: crate::core::typed_flow::TypedResultNode
+//! [`FlowAction::name`]: crate::core::typed_flow::FlowAction::name
+//! [`Budget`]: crate::core::budget::Budget
+//! [`FlowContext`]: crate::core::telemetry::FlowContext
+
+use crate::core::error::AgentFlowError;
+use crate::core::flow::Flow;
+use crate::core::handoff::HANDOFF_CHAIN_KEY;
+use crate::core::node::{Node, SharedStore};
+use crate::core::sync::lock;
+use crate::core::telemetry::{record_llm_usage, take_llm_usage, LLM_USAGE_KEY};
+use crate::core::typed_flow::{FlowAction, TypedFlow, TypedNodeResult, TypedResultNodeFuture};
+use crate::core::typed_store::TypedStore;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Produces the state used when the store key is missing.
+pub type InitialStateFn<T> = Arc<dyn Fn() -> T + Send + Sync>;
+
+/// Maps the state left by a `Flow` to the action reported to the enclosing
+/// `TypedFlow`.
+pub type ActionMapFn<T, E> = Arc<dyn Fn(&T) -> Option<E> + Send + Sync>;
+
+/// Flow bookkeeping keys that are never part of the typed state.
+const RESERVED_KEYS: &[&str] = &[LLM_USAGE_KEY, HANDOFF_CHAIN_KEY];
+
+/// Runs a [`TypedFlow`] as a node of a string-keyed [`Flow`].
+///
+/// On each call it:
+///
+/// 1. Deserializes `T` from `state_key` (or uses the
+///    [initial state](Self::with_initial_state) if the key is absent).
+/// 2. Runs the typed flow with [`TypedFlow::run_safe_with_action`].
+/// 3. Serializes the final `T` back into `state_key` and writes the final
+///    action's [`name`](FlowAction::name) to `"action"` (or removes
+///    `"action"` if the typed flow ended without one).
+///
+/// Every LLM call recorded in the typed flow is appended to
+/// [`"llm_usage"`](LLM_USAGE_KEY), even if the typed flow fails.
+///
+/// Failures are written to the `"error"` key, matching [`Flow::run`].
+///
+/// [`Flow::run`]: crate::core::flow::Flow::run
+pub struct TypedFlowNode<T, E> {
+    flow: TypedFlow<T, E>,
+    state_key: String,
+    initial: Option<InitialStateFn<T>>,
+}
+
+impl<T, E> TypedFlowNode<T, E>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    E: FlowAction,
+{
+    /// Wrap `flow`, reading and writing its state as JSON under `state_key`.
+    pub fn new(flow: TypedFlow<T, E>, state_key: &str) -> Self {
+        Self {
+            flow,
+            state_key: state_key.to_string(),
+            initial: None,
+        }
+    }
+
+    /// State to start from when `state_key` is not in the store. Without it a
+    /// missing key is an error.
+    pub fn with_initial_state<F>(mut self, initial: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        self.initial = Some(Arc::new(initial));
+        self
+    }
+
+    async fn run(&self, store: &SharedStore) -> Result<(), AgentFlowError> {
+        let raw = store.read().await.get(&self.state_key).cloned();
+        let state = match (raw, &self.initial) {
+            (Some(value), _) => serde_json::from_value::<T>(value).map_err(|e| {
+                AgentFlowError::TypeMismatch(format!(
+                    "Store key '{}' does not hold the typed flow state: {}",
+                    self.state_key, e
+                ))
+            })?,
+            (None, Some(initial)) => initial(),
+            (None, None) => {
+                return Err(AgentFlowError::NotFound(format!(
+                    "Typed flow state key '{}' not found in store",
+                    self.state_key
+                )))
+            }
+        };
+
+        let mut start = TypedStore::new(state);
+        let log = start.context.start_call_log();
+        let outcome = self.flow.run_safe_with_action(start).await;
+        let calls = std::mem::take(&mut *lock(&log));
+        for usage in calls {
+            record_llm_usage(store, usage).await;
+        }
+        let (result, action) = outcome?;
+        let value = serde_json::to_value(&result.inner)?;
+
+        let mut guard = store.write().await;
+        guard.insert(self.state_key.clone(), value);
+        match action {
+            Some(action) => {
+                guard.insert("action".to_string(), Value::String(action.name().into()));
+            }
+            None => {
+                guard.remove("action");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, E> Node<SharedStore, SharedStore> for TypedFlowNode<T, E>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    E: FlowAction,
+{
+    fn call(&self, input: SharedStore) -> Pin<Box<dyn Future<Output = SharedStore> + Send + '_>> {
+        Box::pin(async move {
+            if let Err(e) = self.run(&input).await {
+                warn!(state_key = %self.state_key, error = %e.report(), "TypedFlowNode failed");
+                input
+                    .write()
+                    .await
+                    .insert("error".to_string(), Value::String(e.report()));
+            }
+            input
+        })
+    }
+}
+
+impl<T, E: Clone> Clone for TypedFlowNode<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            flow: self.flow.clone(),
+            state_key: self.state_key.clone(),
+            initial: self.initial.clone(),
+        }
+    }
+}
+
+/// Runs a [`Flow`] as a fallible node of a [`TypedFlow`].
+///
+/// By default `T` must serialize to a JSON object: each field becomes a store
+/// key, and after the flow runs `T` is deserialized from the whole store
+/// (unknown keys are ignored unless `T` uses `deny_unknown_fields`; the
+/// flow's `"llm_usage"` and `"handoff_chain"` keys are never read). Use
+/// [`with_state_key`](Self::with_state_key) to keep the state under a single
+/// key instead.
+///
+/// The LLM calls the flow reports under `"llm_usage"` are recorded in the
+/// typed [`FlowContext`](crate::core::telemetry::FlowContext), so the
+/// enclosing typed flow attributes them to this node and checks its budget.
+///
+/// A `Flow` consumes its `"action"` key, so the action reported to the
+/// enclosing typed flow is computed from the resulting state by
+/// [`with_action`](Self::with_action); without it the node returns no action.
+///
+/// Register it with [`TypedFlow::add_result_node`]:
+///
+/// ```rust,no_run
+/// # use agentflow::core::bridge::FlowTypedNode;
+/// # use agentflow::core::flow::Flow;
+/// # use agentflow::core::typed_flow::TypedFlow;
+/// # #[derive(Clone, serde::Serialize, serde::Deserialize)] struct Doc { text: String, ok: bool }
+/// # #[derive(Clone, PartialEq, Eq, Hash)] enum Action { Publish }
+/// # let legacy = Flow::new();
+/// let mut typed = TypedFlow::<Doc, Action>::new();
+/// typed.add_result_node(
+///     "legacy_review",
+///     Box::new(FlowTypedNode::new(legacy).with_action(|d: &Doc| d.ok.then_some(Action::Publish))),
+/// );
+/// ```
+pub struct FlowTypedNode<T, E> {
+    flow: Flow,
+    state_key: Option<String>,
+    action: Option<ActionMapFn<T, E>>,
+}
+
+impl<T, E> FlowTypedNode<T, E>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    E: Send + Sync + Clone + 'static,
+{
+    /// Wrap `flow`, spreading the fields of `T` over the store.
+    pub fn new(flow: Flow) -> Self {
+        Self {
+            flow,
+            state_key: None,
+            action: None,
+        }
+    }
+
+    /// Store the whole state as JSON under `key` instead of spreading its
+    /// fields.
+    pub fn with_state_key(mut self, key: &str) -> Self {
+        self.state_key = Some(key.to_string());
+        self
+    }
+
+    /// Compute the typed action from the state the flow left behind.
+    pub fn with_action<F>(mut self, action: F) -> Self
+    where
+        F: Fn(&T) -> Option<E> + Send + Sync + 'static,
+    {
+        self.action = Some(Arc::new(action));
+        self
+    }
+
+    fn write_state(&self, state: &T) -> Result<SharedStore, AgentFlowError> {
+        let value = serde_json::to_value(state)?;
+        let map = match (&self.state_key, value) {
+            (Some(key), value) => HashMap::from([(key.clone(), value)]),
+            (None, Value::Object(fields)) => fields.into_iter().collect(),
+            (None, other) => {
+                return Err(AgentFlowError::TypeMismatch(format!(
+                    "FlowTypedNode state must serialize to a JSON object, got {}; \
+                     use with_state_key",
+                    json_kind(&other)
+                )))
+            }
+        };
+        Ok(Arc::new(RwLock::new(map)))
+    }
+
+    async fn read_state(&self, store: &SharedStore) -> Result<T, AgentFlowError> {
+        let guard = store.read().await;
+        let value = match &self.state_key {
+            Some(key) => guard.get(key).cloned().ok_or_else(|| {
+                AgentFlowError::NotFound(format!("Flow removed state key '{}'", key))
+            })?,
+            None => Value::Object(
+                guard
+                    .iter()
+                    .filter(|(k, _)| !RESERVED_KEYS.contains(&k.as_str()))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ),
+        };
+        serde_json::from_value(value).map_err(|e| {
+            AgentFlowError::TypeMismatch(format!("Flow output does not match typed state: {}", e))
+        })
+    }
+}
+
+impl<T, E> TypedNodeResult<T, E> for FlowTypedNode<T, E>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    E: Send + Sync + Clone + 'static,
+{
+    fn call(&self, mut input: TypedStore<T>) -> TypedResultNodeFuture<'_, T, E> {
+        Box::pin(async move {
+            let shared = self.write_state(&input.inner)?;
+            let outcome = self.flow.run_safe(shared.clone()).await;
+            // A failed run leaves its calls in the store it was given.
+            for usage in take_llm_usage(outcome.as_ref().unwrap_or(&shared)).await {
+                input.context.record_llm_call(usage);
+            }
+            let shared = outcome?;
+            input.inner = self.read_state(&shared).await?;
+            let action = self.action.as_ref().and_then(|f| f(&input.inner));
+            Ok((input, action))
+        })
+    }
+}
+
+impl<T, E> Clone for FlowTypedNode<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            flow: self.flow.clone(),
+            state_key: self.state_key.clone(),
+            action: self.action.clone(),
+        }
+    }
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
@@ -0,0 +1,295 @@
+//! Model pricing and spend limits for flows.
+//!
+//! [`FlowContext`] counts tokens; this module turns the counts into money and
+//! stops runs that go over a limit:
+//!
+//! - [`PriceTable`] maps a model name to its per-token prices. Load it from a
+//!   JSON config with [`PriceTable::from_json_file`].
+//! - [`Budget`] sets hard limits on tokens, cost and number of LLM calls. Attach
+//!   it with [`Flow::with_budget`] or [`TypedFlow::with_budget`].
+//!
+//! After every node the flow attributes that node's LLM calls to it, prices
+//! them and checks the budget. When a limit is exceeded the run stops with
+//! [`AgentFlowError::ExecutionLimitExceeded`], whose message includes the usage
+//! broken down by node.
+//!
+//! Nodes report calls with [`FlowContext::record_llm_call`] in a `TypedFlow`,
+//! or [`record_llm_usage`] (the reserved `"llm_usage"` key) in a `Flow`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::budget::{Budget, PriceTable};
+//! use agentflow::core::flow::Flow;
+//!
+//! # fn main() -> Result<(), agentflow::core::error::AgentFlowError> {
+//! // {"gpt-4o-mini": {"input_per_million": 0.15, "output_per_million": 0.60}}
+//! let prices = PriceTable::from_json_file("prices.json")?;
+//! let flow = Flow::new().with_budget(
+//!     Budget::new()
+//!         .with_prices(prices)
+//!         .with_max_cost(0.50)
+//!         .with_max_calls(20),
+//! );
+//! # Ok(()) }
+//! ```
+//!
+//! [`FlowContext`]: crate::core::telemetry::FlowContext
+//! [`FlowContext::record_llm_call`]: crate::core::telemetry::FlowContext::record_llm_call
+//! [`record_llm_usage`]: crate::core::telemetry::record_llm_usage
+//! [`Flow::with_budget`]: crate::core::flow::Flow::with_budget
+//! [`TypedFlow::with_budget`]: crate::core::typed_flow::TypedFlow::with_budget
+
+use crate::core::error::AgentFlowError;
+use crate::core::telemetry::{FlowContext, LlmUsage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Per-token prices for one model, quoted per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Price of one million prompt tokens.
+    pub input_per_million: f64,
+    /// Price of one million completion tokens.
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    /// Prices per million input and output tokens.
+    pub fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+
+    /// Cost of one call with the given token counts.
+    pub fn cost(&self, input_tokens: usize, output_tokens: usize) -> f64 {
+        (input_tokens as f64 * self.input_per_million
+            + output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Model name → [`ModelPrice`].
+///
+/// Serialized as a plain JSON object:
+///
+/// ```json
+/// {
+///   "gpt-4o":      { "input_per_million": 2.50, "output_per_million": 10.00 },
+///   "gpt-4o-mini": { "input_per_million": 0.15, "output_per_million": 0.60 }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PriceTable {
+    models: HashMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    /// Create an empty price table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the price of `model`.
+    pub fn with_model(mut self, model: &str, price: ModelPrice) -> Self {
+        self.models.insert(model.to_string(), price);
+        self
+    }
+
+    /// Parse a price table from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AgentFlowError::Custom`] if `json` is not a valid table.
+    pub fn from_json_str(json: &str) -> Result<Self, AgentFlowError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a price table from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, AgentFlowError> {
+        let raw = std::fs::read_to_string(path)?;
+        Self::from_json_str(&raw)
+    }
+
+    /// Price of `model`, if known.
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.models.get(model)
+    }
+
+    /// Cost of `usage`, or `None` if its model has no price.
+    pub fn cost(&self, usage: &LlmUsage) -> Option<f64> {
+        self.price(&usage.model)
+            .map(|p| p.cost(usage.input_tokens, usage.output_tokens))
+    }
+}
+
+/// Hard limits on the LLM usage of one flow run.
+///
+/// All limits are optional; an empty budget only attributes usage to nodes.
+/// If [`max_cost`](Self::with_max_cost) is set, every call must use a model in
+/// the [price table](Self::with_prices), otherwise the run stops with
+/// [`AgentFlowError::NotFound`] rather than spend an unknown amount.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    /// Maximum [`token_usage`](FlowContext::token_usage): every LLM call's
+    /// input plus output tokens, and anything added with
+    /// [`add_tokens`](FlowContext::add_tokens).
+    pub max_tokens: Option<usize>,
+    /// Maximum total cost, in the price table's currency.
+    pub max_cost: Option<f64>,
+    /// Maximum number of LLM calls.
+    pub max_calls: Option<usize>,
+    prices: PriceTable,
+}
+
+impl Budget {
+    /// Create a budget with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the total number of tokens.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Limit the total cost.
+    pub fn with_max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Limit the number of LLM calls.
+    pub fn with_max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = Some(max_calls);
+        self
+    }
+
+    /// Price calls with `prices`.
+    pub fn with_prices(mut self, prices: PriceTable) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    /// The price table used for this budget.
+    pub fn prices(&self) -> &PriceTable {
+        &self.prices
+    }
+
+    /// Attribute the calls recorded by `node` since the last settlement,
+    /// price them, and check the limits.
+    ///
+    /// # Errors
+    ///
+    /// - [`AgentFlowError::NotFound`] if a cost limit is set and a call used a
+    ///   model with no price.
+    /// - [`AgentFlowError::ExecutionLimitExceeded`] if any limit is exceeded.
+    pub fn settle(&self, context: &mut FlowContext, node: &str) -> Result<(), AgentFlowError> {
+        for usage in context.take_pending_calls() {
+            let cost = match self.prices.cost(&usage) {
+                Some(cost) => cost,
+                None if self.max_cost.is_some() => {
+                    return Err(AgentFlowError::NotFound(format!(
+                        "No price for model '{}' called by node '{}'; cannot enforce cost limit",
+                        usage.model, node
+                    )))
+                }
+                None => 0.0,
+            };
+            context.attribute_call(node, &usage, cost);
+        }
+        self.check(context)
+    }
+
+    /// Check the limits against `context`: tokens against its
+    /// [`token_usage`](FlowContext::token_usage), cost and calls against
+    /// the usage already attributed to nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AgentFlowError::ExecutionLimitExceeded`] with a per-node
+    /// breakdown if any limit is exceeded.
+    pub fn check(&self, context: &FlowContext) -> Result<(), AgentFlowError> {
+        let tokens = context.token_usage;
+        let cost = context.total_cost();
+        let calls = context.total_calls();
+
+        let exceeded = if self.max_tokens.is_some_and(|max| tokens > max) {
+            format!(
+                "{} tokens > {} token limit",
+                tokens,
+                self.max_tokens.unwrap_or_default()
+            )
+        } else if self.max_cost.is_some_and(|max| cost > max) {
+            format!(
+                "cost {:.4} > {:.4} cost limit",
+                cost,
+                self.max_cost.unwrap_or_default()
+            )
+        } else if self.max_calls.is_some_and(|max| calls > max) {
+            format!(
+                "{} calls > {} call limit",
+                calls,
+                self.max_calls.unwrap_or_default()
+            )
+        } else {
+            return Ok(());
+        };
+
+        Err(AgentFlowError::ExecutionLimitExceeded(format!(
+            "Budget exceeded: {}. Usage by node: {}",
+            exceeded,
+            breakdown(context)
+        )))
+    }
+}
+
+/// Attribute pending calls to `node`, pricing them with `budget` if present.
+pub(crate) fn settle(
+    context: &mut FlowContext,
+    node: &str,
+    budget: Option<&Budget>,
+) -> Result<(), AgentFlowError> {
+    match budget {
+        Some(budget) => budget.settle(context, node),
+        None => {
+            for usage in context.take_pending_calls() {
+                context.attribute_call(node, &usage, 0.0);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// `draft: 2 calls, 1500 tokens, cost 0.0009; review: …`, most expensive first.
+fn breakdown(context: &FlowContext) -> String {
+    let mut nodes: Vec<_> = context.usage_by_node.iter().collect();
+    nodes.sort_by(|(a_name, a), (b_name, b)| {
+        b.cost
+            .total_cmp(&a.cost)
+            .then(b.total_tokens().cmp(&a.total_tokens()))
+            .then(a_name.cmp(b_name))
+    });
+    nodes
+        .iter()
+        .map(|(name, u)| {
+            format!(
+                "{}: {} calls, {} tokens, cost {:.4}",
+                name,
+                u.calls,
+                u.total_tokens(),
+                u.cost
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,521 @@
+//! Circuit breaker for fallible nodes.
+//!
+//! When an upstream LLM or tool is down, retries only make things worse. A
+//! [`CircuitBreaker`] watches the outcome of the calls it guards and, once the
+//! failure rate crosses a threshold, **opens**: further calls fail immediately
+//! with [`AgentFlowError::CircuitOpen`] without touching the upstream. After a
+//! cooldown it lets a few trial calls through (**half-open**); if they succeed
+//! it **closes** again, otherwise it reopens.
+//!
+//! ```text
+//!            failure rate ≥ threshold                cooldown elapsed
+//!  Closed ───────────────────────────────▶ Open ─────────────────────▶ HalfOpen
+//!    ▲                                       ▲                           │
+//!    │          trial call fails             └───────────────────────────┤
+//!    └───────────────────────────────────────────────────────────────────┘
+//!                         all trial calls succeed
+//! ```
+//!
+//! The breaker's state lives behind an `Arc`: every clone, and every node
+//! wrapped by any clone, shares it. Wrap all nodes that hit the same upstream
+//! with clones of one breaker, across as many flows as you like.
+//!
+//! # Fallbacks
+//!
+//! - [`CircuitBreakerNode::with_fallback`] runs another node when the circuit
+//!   is open or the guarded call fails.
+//! - In a [`TypedFlow`], route the `CircuitOpen` error with
+//!   [`add_error_edge`](crate::core::typed_flow::TypedFlow::add_error_edge).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::circuit_breaker::CircuitBreaker;
+//! use agentflow::prelude::*;
+//! use std::time::Duration;
+//!
+//! # let call_llm = create_result_node(|s: SharedStore| async move { Ok(s) });
+//! # let cached_answer = create_result_node(|s: SharedStore| async move { Ok(s) });
+//! let breaker = CircuitBreaker::new("openai")
+//!     .with_failure_rate(0.5)
+//!     .with_minimum_calls(4)
+//!     .with_cooldown(Duration::from_secs(30))
+//!     .on_state_change(|event| eprintln!("{event:?}"));
+//!
+//! let mut flow = Flow::new();
+//! flow.add_result_node(
+//!     "answer",
+//!     Box::new(breaker.wrap(call_llm).with_fallback(cached_answer)),
+//! );
+//! ```
+//!
+//! [`TypedFlow`]: crate::core::typed_flow::TypedFlow
+
+use crate::core::error::AgentFlowError;
+use crate::core::node::NodeResult;
+use crate::core::sync::lock;
+use crate::core::typed_flow::{TypedNodeResult, TypedResultNodeFuture};
+use crate::core::typed_store::TypedStore;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// State of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Calls pass through; outcomes are recorded.
+    Closed,
+    /// Calls are rejected until the cooldown elapses.
+    Open,
+    /// A limited number of trial calls are let through.
+    HalfOpen,
+}
+
+/// Emitted by a [`CircuitBreaker`] whenever its state changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitEvent {
+    /// Name of the breaker.
+    pub breaker: String,
+    /// State before the transition.
+    pub from: CircuitState,
+    /// State after the transition.
+    pub to: CircuitState,
+    /// Failure rate over the window when the transition happened.
+    pub failure_rate: f64,
+}
+
+/// Callback registered with [`CircuitBreaker::on_state_change`].
+pub type CircuitListener = Arc<dyn Fn(&CircuitEvent) + Send + Sync>;
+
+/// Decides whether an error counts as a failure of the guarded upstream.
+pub type FailurePredicate = Arc<dyn Fn(&AgentFlowError) -> bool + Send + Sync>;
+
+/// Opens after too many failures and fails fast until the upstream recovers.
+///
+/// See the [module docs](self). Configure with the `with_*` methods before
+/// cloning; clones share state and listeners but keep the configuration they
+/// were cloned with.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    name: Arc<str>,
+    /// Failure rate (0.0–1.0) over the window at which the circuit opens.
+    pub failure_rate: f64,
+    /// Outcomes required in the window before the rate is evaluated.
+    pub minimum_calls: usize,
+    /// Number of most recent outcomes considered.
+    pub window: usize,
+    /// How long the circuit stays open before allowing trial calls.
+    pub cooldown: Duration,
+    /// Trial calls allowed while half-open; all must succeed to close.
+    pub half_open_calls: usize,
+    is_failure: FailurePredicate,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    core: Mutex<Core>,
+    listeners: Mutex<Vec<CircuitListener>>,
+}
+
+struct Core {
+    state: CircuitState,
+    /// `true` = failure, most recent last.
+    outcomes: VecDeque<bool>,
+    opened_at: Option<Instant>,
+    trials_in_flight: usize,
+    trial_successes: usize,
+}
+
+impl CircuitBreaker {
+    /// A closed breaker named `name` (used in errors, events and logs).
+    ///
+    /// Defaults: opens at a 50% failure rate over the last 20 calls once at
+    /// least 5 have been made, stays open for 30 s, then allows one trial
+    /// call. Every error except [`AgentFlowError::Suspended`] counts as a
+    /// failure.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: Arc::from(name),
+            failure_rate: 0.5,
+            minimum_calls: 5,
+            window: 20,
+            cooldown: Duration::from_secs(30),
+            half_open_calls: 1,
+            is_failure: Arc::new(|e| !matches!(e, AgentFlowError::Suspended(_))),
+            shared: Arc::new(Shared {
+                core: Mutex::new(Core {
+                    state: CircuitState::Closed,
+                    outcomes: VecDeque::new(),
+                    opened_at: None,
+                    trials_in_flight: 0,
+                    trial_successes: 0,
+                }),
+                listeners: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Open when the failure rate reaches `rate` (clamped to 0.0–1.0).
+    pub fn with_failure_rate(mut self, rate: f64) -> Self {
+        self.failure_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Evaluate the failure rate only once `calls` outcomes are recorded.
+    pub fn with_minimum_calls(mut self, calls: usize) -> Self {
+        self.minimum_calls = calls.max(1);
+        self
+    }
+
+    /// Consider the last `calls` outcomes.
+    pub fn with_window(mut self, calls: usize) -> Self {
+        self.window = calls.max(1);
+        self
+    }
+
+    /// Stay open for `cooldown` before allowing trial calls.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Allow `calls` concurrent trial calls while half-open.
+    pub fn with_half_open_calls(mut self, calls: usize) -> Self {
+        self.half_open_calls = calls.max(1);
+        self
+    }
+
+    /// Count only errors matching `predicate` as failures; others pass
+    /// through without affecting the breaker.
+    pub fn with_failure_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&AgentFlowError) -> bool + Send + Sync + 'static,
+    {
+        self.is_failure = Arc::new(predicate);
+        self
+    }
+
+    /// Call `listener` on every state change. Listeners are shared by all
+    /// clones.
+    pub fn on_state_change<F>(self, listener: F) -> Self
+    where
+        F: Fn(&CircuitEvent) + Send + Sync + 'static,
+    {
+        lock(&self.shared.listeners).push(Arc::new(listener));
+        self
+    }
+
+    /// The breaker's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current state.
+    pub fn state(&self) -> CircuitState {
+        lock(&self.shared.core).state
+    }
+
+    /// Failure rate over the current window (0.0 if empty).
+    pub fn current_failure_rate(&self) -> f64 {
+        failure_rate(&lock(&self.shared.core).outcomes)
+    }
+
+    /// Force the breaker closed and forget recorded outcomes.
+    pub fn reset(&self) {
+        let event = {
+            let mut core = lock(&self.shared.core);
+            core.outcomes.clear();
+            self.transition(&mut core, CircuitState::Closed)
+        };
+        self.emit(event);
+    }
+
+    /// Guard `node` with this breaker.
+    pub fn wrap<N>(&self, node: N) -> CircuitBreakerNode<N> {
+        CircuitBreakerNode {
+            breaker: self.clone(),
+            node,
+            fallback: None,
+        }
+    }
+
+    /// Run `call` through the breaker.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::CircuitOpen`] if the circuit rejects the call;
+    /// otherwise whatever `call` returns.
+    pub async fn call<F, Fut, T>(&self, call: F) -> Result<T, AgentFlowError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AgentFlowError>>,
+    {
+        let permit = self.acquire()?;
+        let result = call().await;
+        match &result {
+            Ok(_) => permit.record(true),
+            Err(e) if (self.is_failure)(e) => permit.record(false),
+            // Neither a success nor a failure: only release a trial slot.
+            Err(_) => drop(permit),
+        }
+        result
+    }
+
+    fn acquire(&self) -> Result<Permit<'_>, AgentFlowError> {
+        let (result, event) = {
+            let mut core = lock(&self.shared.core);
+            let mut event = None;
+            if core.state == CircuitState::Open {
+                let opened_at = core.opened_at.unwrap_or_else(Instant::now);
+                let elapsed = opened_at.elapsed();
+                if elapsed < self.cooldown {
+                    let remaining = self.cooldown - elapsed;
+                    return Err(AgentFlowError::CircuitOpen(format!(
+                        "'{}' rejected the call; retry in {} ms",
+                        self.name,
+                        remaining.as_millis()
+                    )));
+                }
+                event = self.transition(&mut core, CircuitState::HalfOpen);
+            }
+            let result = match core.state {
+                CircuitState::HalfOpen if core.trials_in_flight >= self.half_open_calls => {
+                    Err(AgentFlowError::CircuitOpen(format!(
+                        "'{}' is half-open and its trial calls are in flight",
+                        self.name
+                    )))
+                }
+                CircuitState::HalfOpen => {
+                    core.trials_in_flight += 1;
+                    Ok(Permit {
+                        breaker: self,
+                        trial: true,
+                        done: false,
+                    })
+                }
+                _ => Ok(Permit {
+                    breaker: self,
+                    trial: false,
+                    done: false,
+                }),
+            };
+            (result, event)
+        };
+        self.emit(event);
+        result
+    }
+
+    fn record(&self, trial: bool, success: bool) {
+        let event = {
+            let mut core = lock(&self.shared.core);
+            if trial {
+                core.trials_in_flight = core.trials_in_flight.saturating_sub(1);
+            }
+            match core.state {
+                // A call admitted while closed that finished after the circuit
+                // moved on says nothing about the upstream's recovery.
+                CircuitState::HalfOpen if !trial => None,
+                CircuitState::HalfOpen if !success => {
+                    self.transition(&mut core, CircuitState::Open)
+                }
+                CircuitState::HalfOpen => {
+                    core.trial_successes += 1;
+                    if core.trial_successes >= self.half_open_calls {
+                        core.outcomes.clear();
+                        self.transition(&mut core, CircuitState::Closed)
+                    } else {
+                        None
+                    }
+                }
+                CircuitState::Closed => {
+                    core.outcomes.push_back(!success);
+                    while core.outcomes.len() > self.window {
+                        core.outcomes.pop_front();
+                    }
+                    if core.outcomes.len() >= self.minimum_calls
+                        && failure_rate(&core.outcomes) >= self.failure_rate
+                    {
+                        self.transition(&mut core, CircuitState::Open)
+                    } else {
+                        None
+                    }
+                }
+                // A call admitted before the circuit opened; already counted.
+                CircuitState::Open => None,
+            }
+        };
+        self.emit(event);
+    }
+
+    /// Release a trial slot whose call was dropped before completing.
+    fn abandon(&self, trial: bool) {
+        if trial {
+            let mut core = lock(&self.shared.core);
+            core.trials_in_flight = core.trials_in_flight.saturating_sub(1);
+        }
+    }
+
+    fn transition(&self, core: &mut Core, to: CircuitState) -> Option<CircuitEvent> {
+        let from = core.state;
+        if from == to {
+            return None;
+        }
+        core.state = to;
+        core.trial_successes = 0;
+        core.opened_at = (to == CircuitState::Open).then(Instant::now);
+        let event = CircuitEvent {
+            breaker: self.name.to_string(),
+            from,
+            to,
+            failure_rate: failure_rate(&core.outcomes),
+        };
+        if to == CircuitState::Open {
+            warn!(breaker = %self.name, ?from, failure_rate = event.failure_rate, "Circuit opened");
+        } else {
+            info!(breaker = %self.name, ?from, ?to, "Circuit state changed");
+        }
+        Some(event)
+    }
+
+    /// Whether a guarded node's error should run its fallback: a rejection by
+    /// the open circuit, or a failure as defined by
+    /// [`with_failure_predicate`](Self::with_failure_predicate). Suspensions
+    /// never do.
+    fn falls_back_on(&self, error: &AgentFlowError) -> bool {
+        match error.root_cause() {
+            AgentFlowError::Suspended(_) => false,
+            AgentFlowError::CircuitOpen(_) => true,
+            _ => (self.is_failure)(error),
+        }
+    }
+
+    /// Notify listeners outside the state lock so they may inspect the breaker.
+    fn emit(&self, event: Option<CircuitEvent>) {
+        if let Some(event) = event {
+            let listeners = lock(&self.shared.listeners).clone();
+            for listener in listeners {
+                listener(&event);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("name", &self.name)
+            .field("state", &self.state())
+            .field("failure_rate", &self.failure_rate)
+            .field("minimum_calls", &self.minimum_calls)
+            .field("window", &self.window)
+            .field("cooldown", &self.cooldown)
+            .field("half_open_calls", &self.half_open_calls)
+            .finish()
+    }
+}
+
+/// Admission to make one call; records its outcome exactly once.
+struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+    done: bool,
+}
+
+impl Permit<'_> {
+    fn record(mut self, success: bool) {
+        self.done = true;
+        self.breaker.record(self.trial, success);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.breaker.abandon(self.trial);
+        }
+    }
+}
+
+/// A node guarded by a [`CircuitBreaker`], created with
+/// [`CircuitBreaker::wrap`].
+///
+/// Implements [`NodeResult`] when `N` does (so it can be boxed as a
+/// [`ResultNode`](crate::core::node::ResultNode)) and [`TypedNodeResult`]
+/// when `N` does.
+#[derive(Clone)]
+pub struct CircuitBreakerNode<N> {
+    breaker: CircuitBreaker,
+    node: N,
+    fallback: Option<N>,
+}
+
+impl<N> CircuitBreakerNode<N> {
+    /// Run `fallback` with the same input when the circuit is open or the
+    /// guarded call fails as defined by the breaker's failure predicate.
+    /// Other errors, including [`AgentFlowError::Suspended`], are returned
+    /// unchanged. The fallback's result is returned as-is.
+    pub fn with_fallback(mut self, fallback: N) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// The breaker guarding this node.
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+}
+
+impl<N, I, O> NodeResult<I, O> for CircuitBreakerNode<N>
+where
+    N: NodeResult<I, O> + Clone,
+    I: Clone + Send + 'static,
+    O: Send + 'static,
+{
+    fn call(
+        &self,
+        input: I,
+    ) -> Pin<Box<dyn Future<Output = Result<O, AgentFlowError>> + Send + '_>> {
+        Box::pin(async move {
+            let backup = self.fallback.as_ref().map(|_| input.clone());
+            let result = self.breaker.call(|| self.node.call(input)).await;
+            match (result, &self.fallback, backup) {
+                (Err(e), Some(fallback), Some(input)) if self.breaker.falls_back_on(&e) => {
+                    warn!(breaker = %self.breaker.name, error = %e, "Circuit breaker using fallback");
+                    fallback.call(input).await
+                }
+                (result, _, _) => result,
+            }
+        })
+    }
+}
+
+impl<N, T, E> TypedNodeResult<T, E> for CircuitBreakerNode<N>
+where
+    N: TypedNodeResult<T, E> + Clone,
+    T: Clone + Send + Sync + 'static,
+    E: Send + Sync + 'static,
+{
+    fn call(&self, input: TypedStore<T>) -> TypedResultNodeFuture<'_, T, E> {
+        Box::pin(async move {
+            let backup = self.fallback.as_ref().map(|_| input.clone());
+            let result = self.breaker.call(|| self.node.call(input)).await;
+            match (result, &self.fallback, backup) {
+                (Err(e), Some(fallback), Some(input)) if self.breaker.falls_back_on(&e) => {
+                    warn!(breaker = %self.breaker.name, error = %e, "Circuit breaker using fallback");
+                    fallback.call(input).await
+                }
+                (result, _, _) => result,
+            }
+        })
+    }
+}
+
+fn failure_rate(outcomes: &VecDeque<bool>) -> f64 {
+    if outcomes.is_empty() {
+        return 0.0;
+    }
+    outcomes.iter().filter(|failed| **failed).count() as f64 / outcomes.len() as f64
+}
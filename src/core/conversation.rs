@@ -0,0 +1,354 @@
+//! Chat messages and conversation history.
+//!
+//! A [`Conversation`] is an ordered list of [`ChatMessage`]s stored as JSON
+//! under the reserved [`CONVERSATION_KEY`] (`"conversation"`) of a
+//! [`SharedStore`]. Nodes that agree on this key can hand a multi-turn
+//! exchange to each other without bespoke `"prompt"` / `"response"` keys.
+//!
+//! Messages borrow the field names of OpenAI-style chat APIs (`role`,
+//! `content`, `tool_calls`, `tool_call_id`) but are not wire-compatible:
+//! a [`ToolCall`] is a flat `{id, name, arguments}` object with `arguments`
+//! as JSON rather than a string, and an image part is
+//! `{"type": "image_url", "url": …}`. Providers convert at the request
+//! boundary (as `OpenAiClient` does). `content` is written as an array of parts
+//! and read from either a plain string or an array.
+//!
+//! # Example
+//!
+//! ```rust
+//! use agentflow::core::conversation::{ChatMessage, Conversation, Role};
+//!
+//! let mut convo = Conversation::new()
+//!     .with_message(ChatMessage::system("You are terse."))
+//!     .with_message(ChatMessage::user("Capital of France?"));
+//! convo.push(ChatMessage::assistant("Paris."));
+//!
+//! assert_eq!(convo.last_text(Role::Assistant).as_deref(), Some("Paris."));
+//! // Keep the system prompt plus the latest exchange.
+//! assert_eq!(convo.window(2).len(), 3);
+//! ```
+//!
+//! [`SharedStore`]: crate::core::node::SharedStore
+
+use crate::core::error::AgentFlowError;
+use crate::core::node::SharedStore;
+use crate::core::store::{load_json, save_json};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// Store key under which nodes share the [`Conversation`].
+pub const CONVERSATION_KEY: &str = "conversation";
+
+/// Author of a [`ChatMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Instructions that frame the conversation.
+    System,
+    /// The human (or calling system).
+    User,
+    /// The model.
+    Assistant,
+    /// The result of a tool call requested by the assistant.
+    Tool,
+}
+
+/// One piece of a message's content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    /// Plain text.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// An image, by URL or `data:` URI.
+    ImageUrl {
+        /// Image location.
+        url: String,
+    },
+    /// Inline binary data such as audio or a document.
+    Data {
+        /// MIME type, e.g. `"audio/wav"`.
+        mime_type: String,
+        /// Base64-encoded bytes.
+        data: String,
+    },
+}
+
+/// A tool invocation requested by the assistant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Provider-assigned ID, echoed by the matching [`Role::Tool`] message.
+    pub id: String,
+    /// Tool name.
+    pub name: String,
+    /// Arguments as JSON.
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+impl ToolCall {
+    /// A call of `name` with `arguments`.
+    pub fn new(id: &str, name: &str, arguments: Value) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments,
+        }
+    }
+}
+
+/// A single message of a [`Conversation`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Who wrote the message.
+    pub role: Role,
+    /// Text and multimodal parts, in order.
+    #[serde(default, deserialize_with = "content_parts")]
+    pub content: Vec<ContentPart>,
+    /// Optional participant name (e.g. which agent spoke).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tools the assistant asked to call.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For [`Role::Tool`] messages, the [`ToolCall::id`] being answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    /// A message from `role` with a single text part.
+    pub fn new(role: Role, text: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: vec![ContentPart::Text { text: text.into() }],
+            name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    /// A system message.
+    pub fn system(text: impl Into<String>) -> Self {
+        Self::new(Role::System, text)
+    }
+
+    /// A user message.
+    pub fn user(text: impl Into<String>) -> Self {
+        Self::new(Role::User, text)
+    }
+
+    /// An assistant message.
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self::new(Role::Assistant, text)
+    }
+
+    /// The result of the tool call `tool_call_id`.
+    pub fn tool(tool_call_id: &str, text: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.to_string()),
+            ..Self::new(Role::Tool, text)
+        }
+    }
+
+    /// Set the participant name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Append a content part.
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.content.push(part);
+        self
+    }
+
+    /// Append an image part.
+    pub fn with_image(self, url: &str) -> Self {
+        self.with_part(ContentPart::ImageUrl {
+            url: url.to_string(),
+        })
+    }
+
+    /// Attach a tool call (assistant messages).
+    pub fn with_tool_call(mut self, call: ToolCall) -> Self {
+        self.tool_calls.push(call);
+        self
+    }
+
+    /// All text parts joined with newlines.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// An ordered chat history. Serialized as a JSON array of [`ChatMessage`]s.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Conversation {
+    /// Messages, oldest first.
+    pub messages: Vec<ChatMessage>,
+}
+
+impl Conversation {
+    /// An empty conversation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder form of [`push`](Self::push).
+    pub fn with_message(mut self, message: ChatMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Append a message.
+    pub fn push(&mut self, message: ChatMessage) {
+        self.messages.push(message);
+    }
+
+    /// Append several messages.
+    pub fn extend(&mut self, messages: impl IntoIterator<Item = ChatMessage>) {
+        self.messages.extend(messages);
+    }
+
+    /// Number of messages.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Whether there are no messages.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// The most recent message.
+    pub fn last(&self) -> Option<&ChatMessage> {
+        self.messages.last()
+    }
+
+    /// Text of the most recent message from `role`.
+    pub fn last_text(&self, role: Role) -> Option<String> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.role == role)
+            .map(ChatMessage::text)
+    }
+
+    /// The leading system messages followed by the last `n` other messages.
+    ///
+    /// The window never starts with a [`Role::Tool`] message whose request
+    /// was cut off; such orphans are dropped.
+    pub fn window(&self, n: usize) -> Conversation {
+        let mut trimmed = self.clone();
+        trimmed.trim(n);
+        trimmed
+    }
+
+    /// In-place [`window`](Self::window): keep the leading system messages
+    /// and the last `n` other messages.
+    pub fn trim(&mut self, n: usize) {
+        let system = self
+            .messages
+            .iter()
+            .take_while(|m| m.role == Role::System)
+            .count();
+        let rest = self.messages.len() - system;
+        if rest > n {
+            self.messages.drain(system..system + rest - n);
+        }
+        self.drop_orphan_tool_results(system);
+    }
+
+    /// Drop the oldest non-system messages until the total text length is at
+    /// most `max_chars` — a cheap proxy for a token budget. The leading
+    /// system messages are always kept.
+    pub fn trim_to_chars(&mut self, max_chars: usize) {
+        let system = self
+            .messages
+            .iter()
+            .take_while(|m| m.role == Role::System)
+            .count();
+        let mut total: usize = self.messages.iter().map(|m| m.text().len()).sum();
+        let mut cut = system;
+        while total > max_chars && cut < self.messages.len() {
+            total -= self.messages[cut].text().len();
+            cut += 1;
+        }
+        self.messages.drain(system..cut);
+        self.drop_orphan_tool_results(system);
+    }
+
+    fn drop_orphan_tool_results(&mut self, from: usize) {
+        let orphans = self.messages[from..]
+            .iter()
+            .take_while(|m| m.role == Role::Tool)
+            .count();
+        self.messages.drain(from..from + orphans);
+    }
+
+    /// Read the conversation from `store[CONVERSATION_KEY]`; empty if absent.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something other than
+    /// a list of messages.
+    pub async fn load(store: &SharedStore) -> Result<Self, AgentFlowError> {
+        Ok(load_json(store, CONVERSATION_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Write the conversation to `store[CONVERSATION_KEY]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a message cannot be serialized.
+    pub async fn save(&self, store: &SharedStore) -> Result<(), AgentFlowError> {
+        save_json(store, CONVERSATION_KEY, self).await
+    }
+
+    /// Append `message` to the conversation in `store`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`load`](Self::load) and [`save`](Self::save).
+    pub async fn append(store: &SharedStore, message: ChatMessage) -> Result<(), AgentFlowError> {
+        let mut conversation = Self::load(store).await?;
+        conversation.push(message);
+        conversation.save(store).await
+    }
+}
+
+impl FromIterator<ChatMessage> for Conversation {
+    fn from_iter<I: IntoIterator<Item = ChatMessage>>(iter: I) -> Self {
+        Self {
+            messages: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Accept `"content": "text"`, `"content": [parts…]` or `"content": null`.
+fn content_parts<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ContentPart>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Content {
+        Text(String),
+        Parts(Vec<ContentPart>),
+    }
+    Ok(match Option::<Content>::deserialize(deserializer)? {
+        Some(Content::Text(text)) => vec![ContentPart::Text { text }],
+        Some(Content::Parts(parts)) => parts,
+        None => Vec::new(),
+    })
+}
@@ -1,9 +1,16 @@
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Unified error type for all AgentFlow operations.
 ///
 /// Variants are designed to be actionable — callers can match on the variant
 /// to decide whether to retry, abort, or surface to the user.
+/// [`is_retryable`](Self::is_retryable) gives the default classification.
+///
+/// Flows built with [`Flow::with_error_context`] wrap node errors in
+/// [`Contextual`](Self::Contextual), recording the node, step and flow ID. Use
+/// [`root_cause`](Self::root_cause) to match on the underlying error.
 ///
 /// # Examples
 ///
@@ -13,6 +20,8 @@ use thiserror::Error;
 /// let err = AgentFlowError::NotFound("prompt key missing".into());
 /// assert_eq!(err.to_string(), "Not found: prompt key missing");
 /// ```
+///
+/// [`Flow::with_error_context`]: crate::core::flow::Flow::with_error_context
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum AgentFlowError {
     /// A required key or resource was not found in the store or registry.
@@ -53,11 +62,221 @@ pub enum AgentFlowError {
     #[error("Graph build error: {0}")]
     GraphBuildError(String),
 
+    /// A provider rejected the request because of rate limiting. Transient.
+    ///
+    /// `retry_after` carries the provider's hint (e.g. a `Retry-After`
+    /// header), if any.
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        /// Description of the rejected request.
+        message: String,
+        /// How long the provider asked callers to wait before retrying.
+        retry_after: Option<Duration>,
+    },
+
+    /// The operation was cancelled before it completed.
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    /// A node or model produced output that could not be parsed or failed
+    /// validation (e.g. malformed JSON from an LLM).
+    #[error("Invalid output: {0}")]
+    InvalidOutput(String),
+
+    /// An external tool or process could not be run or exited unsuccessfully.
+    #[error("Tool '{tool}' failed{}: {stderr}", exit_code.map(|c| format!(" with exit code {c}")).unwrap_or_default())]
+    ToolFailure {
+        /// Name of the tool or command.
+        tool: String,
+        /// Process exit code; `None` if the process did not start or was
+        /// killed by a signal.
+        exit_code: Option<i32>,
+        /// Captured standard error, or the spawn error message.
+        stderr: String,
+    },
+
+    /// A [`CircuitBreaker`] is open and rejected the call without running the
+    /// node. Not retryable: the breaker decides when to try again.
+    ///
+    /// [`CircuitBreaker`]: crate::core::circuit_breaker::CircuitBreaker
+    #[error("Circuit open: {0}")]
+    CircuitOpen(String),
+
+    /// Another error annotated with where it happened. Displays only the
+    /// location; the wrapped error is exposed through
+    /// [`std::error::Error::source`], so error reporters such as `anyhow`
+    /// print it once. Use [`report`](AgentFlowError::report) for a one-line
+    /// message including it.
+    #[error("{context}")]
+    Contextual {
+        /// Where the error was raised.
+        context: Box<ErrorContext>,
+        /// The underlying error.
+        source: Box<AgentFlowError>,
+    },
+
     /// Any other error that doesn't fit a specific variant above.
     #[error("Error: {0}")]
     Custom(String),
 }
 
+/// Location of an error inside a flow run.
+///
+/// Displayed as `[flow 'id', node 'name', step N]`, omitting unset parts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Name of the node that raised the error.
+    pub node: Option<String>,
+    /// 1-based step at which the node ran.
+    pub step: Option<usize>,
+    /// ID of the flow, set with [`Flow::with_id`](crate::core::flow::Flow::with_id).
+    pub flow_id: Option<String>,
+}
+
+impl ErrorContext {
+    /// Create an empty context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the node name.
+    pub fn with_node(mut self, node: impl Into<String>) -> Self {
+        self.node = Some(node.into());
+        self
+    }
+
+    /// Set the step number.
+    pub fn with_step(mut self, step: usize) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Set the flow ID.
+    pub fn with_flow_id(mut self, flow_id: impl Into<String>) -> Self {
+        self.flow_id = Some(flow_id.into());
+        self
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(id) = &self.flow_id {
+            parts.push(format!("flow '{}'", id));
+        }
+        if let Some(node) = &self.node {
+            parts.push(format!("node '{}'", node));
+        }
+        if let Some(step) = self.step {
+            parts.push(format!("step {}", step));
+        }
+        write!(f, "[{}]", parts.join(", "))
+    }
+}
+
+impl AgentFlowError {
+    /// Whether retrying the same operation may succeed.
+    ///
+    /// [`Timeout`](Self::Timeout) and [`RateLimited`](Self::RateLimited) are
+    /// transient; [`Contextual`](Self::Contextual) defers to the wrapped error.
+    /// Everything else is treated as fatal.
+    ///
+    /// ```rust
+    /// use agentflow::core::error::{AgentFlowError, ErrorContext};
+    ///
+    /// let timeout = AgentFlowError::Timeout("llm".into());
+    /// assert!(timeout.is_retryable());
+    /// assert!(timeout.with_context(ErrorContext::new().with_node("draft")).is_retryable());
+    /// assert!(!AgentFlowError::InvalidOutput("not JSON".into()).is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AgentFlowError::Timeout(_) | AgentFlowError::RateLimited { .. } => true,
+            AgentFlowError::Contextual { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// The provider's retry hint, if this is (or wraps) a
+    /// [`RateLimited`](Self::RateLimited) error.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self.root_cause() {
+            AgentFlowError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Wrap this error with `context`.
+    ///
+    /// Wrapping an already contextual error fills in the parts of its context
+    /// that are unset instead of nesting, so the innermost location wins.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            AgentFlowError::Contextual {
+                context: mut inner,
+                source,
+            } => {
+                inner.node = inner.node.or(context.node);
+                inner.step = inner.step.or(context.step);
+                inner.flow_id = inner.flow_id.or(context.flow_id);
+                AgentFlowError::Contextual {
+                    context: inner,
+                    source,
+                }
+            }
+            other => AgentFlowError::Contextual {
+                context: Box::new(context),
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// The context attached by [`with_context`](Self::with_context), if any.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            AgentFlowError::Contextual { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The innermost error, with all [`Contextual`](Self::Contextual)
+    /// wrappers removed.
+    ///
+    /// ```rust
+    /// use agentflow::core::error::{AgentFlowError, ErrorContext};
+    ///
+    /// let err = AgentFlowError::NodeFailure("boom".into())
+    ///     .with_context(ErrorContext::new().with_node("draft").with_step(2));
+    /// assert_eq!(err.to_string(), "[node 'draft', step 2]");
+    /// assert!(matches!(err.root_cause(), AgentFlowError::NodeFailure(_)));
+    /// ```
+    pub fn root_cause(&self) -> &AgentFlowError {
+        match self {
+            AgentFlowError::Contextual { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// The whole message on one line, including any wrapped error — for
+    /// logs and for error strings written to a store.
+    ///
+    /// ```rust
+    /// use agentflow::core::error::{AgentFlowError, ErrorContext};
+    ///
+    /// let err = AgentFlowError::NodeFailure("boom".into())
+    ///     .with_context(ErrorContext::new().with_node("draft").with_step(2));
+    /// assert_eq!(err.report(), "[node 'draft', step 2]: Node failure: boom");
+    /// ```
+    pub fn report(&self) -> String {
+        match self {
+            AgentFlowError::Contextual { context, source } => {
+                format!("{}: {}", context, source.report())
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
 impl From<std::io::Error> for AgentFlowError {
     fn from(error: std::io::Error) -> Self {
         AgentFlowError::Custom(format!("IO Error: {}", error))
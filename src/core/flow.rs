@@ -1,5 +1,8 @@
-use crate::core::error::AgentFlowError;
+use crate::core::budget::{self, Budget};
+use crate::core::error::{AgentFlowError, ErrorContext};
+use crate::core::handoff::{Handoff, HANDOFF_CHAIN_KEY, HANDOFF_RECEIVED_KEY};
 use crate::core::node::{Node, ResultNode, SharedStore, SimpleNode};
+use crate::core::telemetry::{usage_entries, FlowContext, LlmUsage, LLM_USAGE_KEY};
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
@@ -18,12 +21,18 @@ use tracing::{debug, info, instrument, warn};
 /// `"action"` is absent, execution stops. The `"action"` key is removed from
 /// the store when the flow completes.
 ///
+/// A node can instead request a [`Handoff`] to any named node; it takes
+/// precedence over `"action"` and needs no edge. Every handoff is appended to
+/// `store["handoff_chain"]`, and the one that started the current node is
+/// kept in `store["handoff_received"]` until the next edge transition.
+///
 /// # Cycle prevention
 ///
 /// Use [`Flow::with_max_steps`] to cap the total number of node
 /// executions. Choose [`Flow::run`] (writes `"error"` key on limit) or
 /// [`Flow::run_safe`] (returns `Err`) depending on whether you need
-/// strict error propagation.
+/// strict error propagation. Handoff loops are bounded separately by
+/// [`Flow::with_handoff_limit`].
 ///
 /// # Example
 ///
@@ -85,6 +94,14 @@ pub struct Flow {
     pub pre_node_hook: Option<FlowHookFn>,
     /// Optional hook executed after every node.
     pub post_node_hook: Option<FlowHookFn>,
+    /// Optional LLM spend limits, checked after every node.
+    pub budget: Option<Budget>,
+    /// Identifier recorded in error contexts.
+    pub id: Option<String>,
+    /// Whether node errors are wrapped in [`AgentFlowError::Contextual`].
+    pub error_context: bool,
+    /// How many times one node may receive control by [`Handoff`] in a run.
+    pub handoff_limit: usize,
 }
 
 impl Flow {
@@ -97,6 +114,10 @@ impl Flow {
             max_steps: None,
             pre_node_hook: None,
             post_node_hook: None,
+            budget: None,
+            id: None,
+            error_context: false,
+            handoff_limit: 3,
         }
     }
 
@@ -110,6 +131,60 @@ impl Flow {
         self
     }
 
+    /// Allow each node to receive control by [`Handoff`] at most `limit`
+    /// times per run (default 3).
+    ///
+    /// Handoffs bypass the edge graph, so [`validate`](Self::validate) cannot
+    /// see their cycles. A handoff past the limit — e.g. agents bouncing a
+    /// task A → B → A → B — stops the flow with
+    /// [`AgentFlowError::ExecutionLimitExceeded`] naming the chain.
+    pub fn with_handoff_limit(mut self, limit: usize) -> Self {
+        self.handoff_limit = limit;
+        self
+    }
+
+    /// Enforce `budget` on this flow's LLM usage.
+    ///
+    /// After every node the flow attributes the calls reported under the
+    /// reserved [`"llm_usage"`](crate::core::telemetry::LLM_USAGE_KEY) key to
+    /// that node (see [`run_with_context`](Self::run_with_context)) and checks
+    /// the limits against the whole run. When a limit is exceeded,
+    /// [`run`](Self::run) writes `"error"` and halts;
+    /// [`run_safe`](Self::run_safe) returns
+    /// `Err(AgentFlowError::ExecutionLimitExceeded)` with a per-node breakdown.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set the ID reported in error contexts. Implies
+    /// [`with_error_context`](Self::with_error_context).
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self.error_context = true;
+        self
+    }
+
+    /// Wrap errors raised by nodes (and budget overruns) in
+    /// [`AgentFlowError::Contextual`] with the node name, step and flow ID.
+    ///
+    /// Off by default so existing matches on the bare variant keep working;
+    /// use [`AgentFlowError::root_cause`] when it is on.
+    pub fn with_error_context(mut self) -> Self {
+        self.error_context = true;
+        self
+    }
+
+    /// Attach the node, step and flow ID to `error` if error context is on.
+    fn contextualize(&self, error: AgentFlowError, node: &str, step: usize) -> AgentFlowError {
+        if !self.error_context {
+            return error;
+        }
+        let mut context = ErrorContext::new().with_node(node).with_step(step);
+        context.flow_id = self.id.clone();
+        error.with_context(context)
+    }
+
     /// Set a hook that will be called before every node execution.
     pub fn with_pre_node_hook<F, Fut>(mut self, hook: F) -> Self
     where
@@ -228,56 +303,18 @@ impl Flow {
     ///    to detect cycles. If a cycle is found and `max_steps` is not set, returns
     ///    a `GraphBuildError`.
     pub fn validate(&self) -> Result<(), AgentFlowError> {
-        let start_node = self.start_node.as_ref().ok_or_else(|| {
-            AgentFlowError::GraphBuildError("No start node defined in Flow".into())
-        })?;
-
-        if !self.nodes.contains_key(start_node) {
-            return Err(AgentFlowError::GraphBuildError(format!(
-                "Start node '{}' not found in registered nodes",
-                start_node
-            )));
-        }
-
-        let mut graph = petgraph::graph::DiGraph::<&str, &str>::new();
-        let mut node_indices = HashMap::new();
-
-        for name in self.nodes.keys() {
-            node_indices.insert(name.as_str(), graph.add_node(name.as_str()));
-        }
-
-        for (from_node, actions) in &self.edges {
-            let from_idx = match node_indices.get(from_node.as_str()) {
-                Some(idx) => *idx,
-                None => continue,
-            };
-            for (action, to_node) in actions {
-                if let Some(&to_idx) = node_indices.get(to_node.as_str()) {
-                    graph.add_edge(from_idx, to_idx, action.as_str());
-                } else {
-                    return Err(AgentFlowError::GraphBuildError(format!(
-                        "Edge from '{}' via action '{}' points to missing node '{}'",
-                        from_node, action, to_node
-                    )));
-                }
-            }
-        }
-
-        if self.max_steps.is_none() {
-            let sccs = petgraph::algo::tarjan_scc(&graph);
-            for scc in sccs {
-                if scc.len() > 1 || (scc.len() == 1 && graph.contains_edge(scc[0], scc[0])) {
-                    let mut cycle_nodes = scc
-                        .iter()
-                        .filter_map(|idx| graph.node_weight(*idx).copied())
-                        .collect::<Vec<_>>();
-                    cycle_nodes.sort();
-                    return Err(AgentFlowError::GraphBuildError(format!("Infinite cycle detected involving nodes {:?}. Use `with_max_steps` to explicitly allow cyclic flows.", cycle_nodes)));
-                }
-            }
-        }
-
-        Ok(())
+        let edges = self.edges.iter().flat_map(|(from, actions)| {
+            actions
+                .iter()
+                .map(move |(action, to)| (from.as_str(), Some(action.as_str()), to.as_str()))
+        });
+        validate_graph(
+            "Flow",
+            self.nodes.keys().map(String::as_str),
+            edges,
+            self.start_node.as_deref(),
+            self.max_steps.is_some(),
+        )
     }
 
     /// Shared execution logic for [`run`](Self::run) and [`run_safe`](Self::run_safe).
@@ -285,10 +322,15 @@ impl Flow {
     /// `on_limit_exceeded` controls behavior when `max_steps` is reached:
     /// - `Ok(store)` path  → write `"error"` key and return (used by `run`)
     /// - `Err(…)` path     → return `Err(AgentFlowError::ExecutionLimitExceeded)` (used by `run_safe`)
+    ///
+    /// LLM usage reported under [`LLM_USAGE_KEY`] is settled into `usage`
+    /// after every node and handed back under the same key when the run ends,
+    /// so that an enclosing flow still sees it.
     async fn run_internal(
         &self,
-        mut store: SharedStore,
+        store: SharedStore,
         safe: bool,
+        usage: &mut FlowContext,
     ) -> Result<SharedStore, AgentFlowError> {
         if let Err(e) = self.validate() {
             if safe {
@@ -302,6 +344,38 @@ impl Flow {
             }
         }
 
+        let mut reported = {
+            let mut guard = store.write().await;
+            // A handoff received by the caller is not one to this run's nodes.
+            guard.remove(HANDOFF_RECEIVED_KEY);
+            usage_entries(guard.remove(LLM_USAGE_KEY))
+        };
+        let result = self
+            .run_steps(store.clone(), safe, usage, &mut reported)
+            .await;
+        let mut guard = result.as_ref().unwrap_or(&store).write().await;
+        guard.remove(HANDOFF_RECEIVED_KEY);
+        // Keep entries that were never settled rather than overwrite them.
+        reported.extend(usage_entries(guard.remove(LLM_USAGE_KEY)));
+        if !reported.is_empty() {
+            guard.insert(
+                LLM_USAGE_KEY.to_string(),
+                serde_json::Value::Array(reported),
+            );
+        }
+        drop(guard);
+        result
+    }
+
+    /// The node loop of [`run_internal`](Self::run_internal). Settled usage
+    /// entries are appended to `reported`.
+    async fn run_steps(
+        &self,
+        mut store: SharedStore,
+        safe: bool,
+        usage: &mut FlowContext,
+        reported: &mut Vec<serde_json::Value>,
+    ) -> Result<SharedStore, AgentFlowError> {
         let mut current_node_name = match self.start_node.as_deref() {
             Some(name) => name.to_string(),
             None => return Ok(store),
@@ -309,6 +383,7 @@ impl Flow {
 
         let mut steps = 0;
         let limit = self.max_steps.unwrap_or(usize::MAX);
+        let mut handoffs: Vec<String> = Vec::new();
 
         while let Some(node) = self.nodes.get(&current_node_name) {
             if steps >= limit {
@@ -344,6 +419,19 @@ impl Flow {
                 FlowNode::Result(n) => match n.call(store.clone()).await {
                     Ok(s) => s,
                     Err(e) => {
+                        // The calls of a failed node still count.
+                        if let Err(budget_error) = settle_llm_usage(
+                            &store,
+                            usage,
+                            reported,
+                            &current_node_name,
+                            self.budget.as_ref(),
+                        )
+                        .await
+                        {
+                            warn!(node = %current_node_name, error = %budget_error, "Failed node also exceeded budget");
+                        }
+                        let e = self.contextualize(e, &current_node_name, steps);
                         if safe {
                             return Err(e);
                         } else {
@@ -361,16 +449,65 @@ impl Flow {
                 store = hook(&current_node_name, store).await;
             }
 
-            // Consume the "action" key to route, preventing it from leaking to the next node
-            let action = store
-                .write()
+            if let Err(e) = settle_llm_usage(
+                &store,
+                usage,
+                reported,
+                &current_node_name,
+                self.budget.as_ref(),
+            )
+            .await
+            {
+                let e = self.contextualize(e, &current_node_name, steps);
+                warn!(node = %current_node_name, error = %e, "Flow stopped by budget");
+                if safe {
+                    return Err(e);
+                }
+                store.write().await.insert(
+                    "error".to_string(),
+                    serde_json::Value::String(e.to_string()),
+                );
+                break;
+            }
+
+            // A handoff overrides edge routing
+            match self
+                .take_handoff(&store, &current_node_name, &mut handoffs)
                 .await
-                .remove("action")
-                .and_then(|v| match v {
-                    serde_json::Value::String(s) => Some(s),
-                    _ => None,
-                })
-                .unwrap_or_else(|| "default".to_string());
+            {
+                Ok(Some(next_node)) => {
+                    store.write().await.remove("action");
+                    debug!(step = steps, node = %current_node_name, to = %next_node, "Flow handoff");
+                    current_node_name = next_node;
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let e = self.contextualize(e, &current_node_name, steps);
+                    warn!(node = %current_node_name, error = %e, "Flow stopped by handoff");
+                    if safe {
+                        return Err(e);
+                    }
+                    store.write().await.insert(
+                        "error".to_string(),
+                        serde_json::Value::String(e.to_string()),
+                    );
+                    break;
+                }
+            }
+
+            // Consume the "action" key to route, preventing it from leaking to
+            // the next node, and forget the handoff the current node received
+            let action = {
+                let mut guard = store.write().await;
+                guard.remove(HANDOFF_RECEIVED_KEY);
+                guard.remove("action")
+            }
+            .and_then(|v| match v {
+                serde_json::Value::String(s) => Some(s),
+                _ => None,
+            })
+            .unwrap_or_else(|| "default".to_string());
 
             println!("Action: {}", action);
 
@@ -394,6 +531,50 @@ impl Flow {
         Ok(store)
     }
 
+    /// Consume a pending [`Handoff`] from `from`, record it in the chain and
+    /// return its target. `handoffs` holds the targets taken in this run.
+    async fn take_handoff(
+        &self,
+        store: &SharedStore,
+        from: &str,
+        handoffs: &mut Vec<String>,
+    ) -> Result<Option<String>, AgentFlowError> {
+        let Some(mut handoff) = Handoff::take(store).await? else {
+            return Ok(None);
+        };
+        if !self.nodes.contains_key(&handoff.to) {
+            return Err(AgentFlowError::NotFound(format!(
+                "Handoff from '{}' to unknown node '{}'",
+                from, handoff.to
+            )));
+        }
+        handoffs.push(handoff.to.clone());
+        if handoffs.iter().filter(|to| **to == handoff.to).count() > self.handoff_limit {
+            return Err(AgentFlowError::ExecutionLimitExceeded(format!(
+                "Handoff loop: '{}' received control more than {} times (handoffs: {})",
+                handoff.to,
+                self.handoff_limit,
+                handoffs.join(" -> ")
+            )));
+        }
+        handoff.from = Some(from.to_string());
+        info!(from = %from, to = %handoff.to, reason = %handoff.reason, "Flow handoff");
+        let to = handoff.to.clone();
+        let record = serde_json::to_value(handoff)?;
+        let mut guard = store.write().await;
+        guard.insert(HANDOFF_RECEIVED_KEY.to_string(), record.clone());
+        match guard.get_mut(HANDOFF_CHAIN_KEY) {
+            Some(serde_json::Value::Array(chain)) => chain.push(record),
+            _ => {
+                guard.insert(
+                    HANDOFF_CHAIN_KEY.to_string(),
+                    serde_json::Value::Array(vec![record]),
+                );
+            }
+        }
+        Ok(Some(to))
+    }
+
     /// Execute the flow from the start node.
     ///
     /// On [`max_steps`](Self::max_steps) exceeded, inserts
@@ -401,9 +582,21 @@ impl Flow {
     /// and returns. Use [`run_safe`](Self::run_safe) for strict error handling.
     #[instrument(name = "flow.run", skip(self, store), fields(start = self.start_node.as_deref().unwrap_or("none"), max_steps = self.max_steps))]
     pub async fn run(&self, store: SharedStore) -> SharedStore {
+        self.run_with_context(store).await.0
+    }
+
+    /// Like [`run`](Self::run), also returning the run's [`FlowContext`]:
+    /// the LLM calls each node reported under
+    /// [`"llm_usage"`](crate::core::telemetry::LLM_USAGE_KEY), attributed to
+    /// that node and priced with the [`Budget`]'s price table if there is one.
+    ///
+    /// The calls are also left under `"llm_usage"` in the returned store so
+    /// that an enclosing flow can account for them.
+    pub async fn run_with_context(&self, store: SharedStore) -> (SharedStore, FlowContext) {
+        let mut usage = FlowContext::new();
         // When `safe = false`, `run_internal` always returns `Ok(store)`.
-        match self.run_internal(store, false).await {
-            Ok(s) => s,
+        match self.run_internal(store, false, &mut usage).await {
+            Ok(s) => (s, usage),
             Err(_) => unreachable!("run_internal with safe=false never returns Err"),
         }
     }
@@ -420,7 +613,8 @@ impl Flow {
     /// is exceeded.
     #[instrument(name = "flow.run_safe", skip(self, store), fields(start = self.start_node.as_deref().unwrap_or("none"), max_steps = self.max_steps))]
     pub async fn run_safe(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
-        self.run_internal(store, true).await
+        self.run_internal(store, true, &mut FlowContext::new())
+            .await
     }
 
     /// Look up a node by name. Returns `None` if not registered.
@@ -438,6 +632,97 @@ impl Flow {
     }
 }
 
+/// Graph checks shared by [`Flow::validate`] and
+/// [`TypedFlow::validate`](crate::core::typed_flow::TypedFlow::validate).
+///
+/// `edges` are `(from, action, to)`; the action only appears in error
+/// messages. Edges leaving an unregistered node can never be taken and are
+/// ignored. Cycles are an error unless `bounded` (i.e. `max_steps` is set).
+pub(crate) fn validate_graph<'a>(
+    kind: &str,
+    nodes: impl Iterator<Item = &'a str>,
+    edges: impl Iterator<Item = (&'a str, Option<&'a str>, &'a str)>,
+    start: Option<&str>,
+    bounded: bool,
+) -> Result<(), AgentFlowError> {
+    let start_node = start.ok_or_else(|| {
+        AgentFlowError::GraphBuildError(format!("No start node defined in {}", kind))
+    })?;
+
+    let mut graph = petgraph::graph::DiGraph::<&str, ()>::new();
+    let mut node_indices = HashMap::new();
+    for name in nodes {
+        node_indices.insert(name, graph.add_node(name));
+    }
+
+    if !node_indices.contains_key(start_node) {
+        return Err(AgentFlowError::GraphBuildError(format!(
+            "Start node '{}' not found in registered nodes",
+            start_node
+        )));
+    }
+
+    for (from_node, action, to_node) in edges {
+        let Some(&from_idx) = node_indices.get(from_node) else {
+            continue;
+        };
+        match (node_indices.get(to_node), action) {
+            (Some(&to_idx), _) => {
+                graph.add_edge(from_idx, to_idx, ());
+            }
+            (None, Some(action)) => {
+                return Err(AgentFlowError::GraphBuildError(format!(
+                    "Edge from '{}' via action '{}' points to missing node '{}'",
+                    from_node, action, to_node
+                )));
+            }
+            (None, None) => {
+                return Err(AgentFlowError::GraphBuildError(format!(
+                    "Edge from '{}' points to missing node '{}'",
+                    from_node, to_node
+                )));
+            }
+        }
+    }
+
+    if !bounded {
+        let sccs = petgraph::algo::tarjan_scc(&graph);
+        for scc in sccs {
+            if scc.len() > 1 || (scc.len() == 1 && graph.contains_edge(scc[0], scc[0])) {
+                let mut cycle_nodes = scc
+                    .iter()
+                    .filter_map(|idx| graph.node_weight(*idx).copied())
+                    .collect::<Vec<_>>();
+                cycle_nodes.sort();
+                return Err(AgentFlowError::GraphBuildError(format!("Infinite cycle detected involving nodes {:?}. Use `with_max_steps` to explicitly allow cyclic flows.", cycle_nodes)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Move the calls reported under [`LLM_USAGE_KEY`] into `usage`, attribute
+/// them to `node` and, if there is a `budget`, check its limits. The raw
+/// entries are appended to `reported`.
+async fn settle_llm_usage(
+    store: &SharedStore,
+    usage: &mut FlowContext,
+    reported: &mut Vec<serde_json::Value>,
+    node: &str,
+    budget: Option<&Budget>,
+) -> Result<(), AgentFlowError> {
+    let calls = usage_entries(store.write().await.remove(LLM_USAGE_KEY));
+    for call in &calls {
+        match serde_json::from_value::<LlmUsage>(call.clone()) {
+            Ok(call) => usage.record_llm_call(call),
+            Err(e) => warn!(node = %node, error = %e, "Ignoring malformed llm_usage entry"),
+        }
+    }
+    reported.extend(calls);
+    budget::settle(usage, node, budget)
+}
+
 impl Node<SharedStore, SharedStore> for Flow {
     fn call(&self, input: SharedStore) -> Pin<Box<dyn Future<Output = SharedStore> + Send + '_>> {
         Box::pin(self.run(input))
@@ -457,6 +742,10 @@ impl Clone for Flow {
             max_steps: self.max_steps,
             pre_node_hook: self.pre_node_hook.clone(),
             post_node_hook: self.post_node_hook.clone(),
+            budget: self.budget.clone(),
+            id: self.id.clone(),
+            error_context: self.error_context,
+            handoff_limit: self.handoff_limit,
         }
     }
 }
@@ -0,0 +1,128 @@
+use crate::core::error::AgentFlowError;
+use crate::core::node::SharedStore;
+use crate::core::store::{load_json, save_json, take_json};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Store key where a node requests a [`Handoff`].
+pub const HANDOFF_KEY: &str = "handoff";
+
+/// Store key where [`Flow`](crate::core::flow::Flow) records every handoff
+/// taken on a store. Runs do not reset it, so the chain also spans nested
+/// flows and later runs that reuse the store; remove the key to start afresh.
+pub const HANDOFF_CHAIN_KEY: &str = "handoff_chain";
+
+/// Store key where [`Flow`](crate::core::flow::Flow) keeps the handoff that
+/// started the current node. It is removed on the next edge transition and
+/// when the run ends.
+pub const HANDOFF_RECEIVED_KEY: &str = "handoff_received";
+
+/// A transfer of control from one agent node to another.
+///
+/// A node writes a handoff with [`save`](Self::save) instead of setting
+/// `store["action"]`. After the node returns, [`Flow`](crate::core::flow::Flow)
+/// removes it, fills in [`from`](Self::from), appends it to
+/// `store["handoff_chain"]` and runs the node named [`to`](Self::to) next —
+/// no edge is needed. The receiving node reads why it was called and the
+/// payload with [`received`](Self::received).
+///
+/// Because handoffs bypass the edge graph, the flow bounds them instead: see
+/// [`Flow::with_handoff_limit`](crate::core::flow::Flow::with_handoff_limit).
+///
+/// # Example
+///
+/// ```rust
+/// use agentflow::core::handoff::Handoff;
+/// use agentflow::prelude::*;
+/// use serde_json::json;
+///
+/// let triage = create_result_node(|store: SharedStore| async move {
+///     Handoff::new("billing", "customer asks about an invoice")
+///         .with_context(json!({"invoice": "INV-42"}))
+///         .save(&store)
+///         .await?;
+///     Ok(store)
+/// });
+/// let billing = create_result_node(|store: SharedStore| async move {
+///     let handoff = Handoff::received(&store).await?.expect("called via handoff");
+///     let reply = format!("Looking up {}", handoff.context["invoice"]);
+///     store.write().await.insert("reply".into(), json!(reply));
+///     Ok(store)
+/// });
+///
+/// let mut flow = Flow::new();
+/// flow.add_result_node("triage", triage);
+/// flow.add_result_node("billing", billing);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Handoff {
+    /// Node that handed off; set by the flow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Name of the node to run next.
+    pub to: String,
+    /// Why control is transferred.
+    pub reason: String,
+    /// Payload for the receiving node.
+    #[serde(default)]
+    pub context: Value,
+}
+
+impl Handoff {
+    /// Hand control to the node named `to`, because of `reason`.
+    pub fn new(to: &str, reason: &str) -> Self {
+        Self {
+            from: None,
+            to: to.to_string(),
+            reason: reason.to_string(),
+            context: Value::Null,
+        }
+    }
+
+    /// Attach a payload for the receiving node.
+    pub fn with_context(mut self, context: Value) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Request the handoff by writing it to `store["handoff"]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the handoff cannot be serialized.
+    pub async fn save(&self, store: &SharedStore) -> Result<(), AgentFlowError> {
+        save_json(store, HANDOFF_KEY, self).await
+    }
+
+    /// Remove and return a pending handoff from `store["handoff"]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn take(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        take_json(store, HANDOFF_KEY).await
+    }
+
+    /// The handoffs taken so far on `store`, across runs, oldest first, from
+    /// `store["handoff_chain"]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn chain(store: &SharedStore) -> Result<Vec<Self>, AgentFlowError> {
+        Ok(load_json(store, HANDOFF_CHAIN_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// The handoff that started the current node, from
+    /// `store["handoff_received"]`. `None` if the node was reached by an
+    /// edge or is the start node, even if earlier handoffs were taken.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn received(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        load_json(store, HANDOFF_RECEIVED_KEY).await
+    }
+}
@@ -0,0 +1,531 @@
+//! Capacity limits, TTLs and eviction for [`Store`].
+//!
+//! Long-running flows tend to accumulate transcripts and retrieved documents
+//! in the store until memory runs out. Attach [`StoreLimits`] with
+//! [`Store::with_limits`] to bound it:
+//!
+//! - **`max_total_bytes`** — once the store grows past this, keys are evicted
+//!   according to the [`EvictionPolicy`] until the new write fits.
+//! - **`max_key_bytes`** — a single value larger than this is rejected.
+//! - **TTLs** — a default TTL for every key, or a per-key TTL with
+//!   [`Store::set_with_ttl`]. Expired keys read as absent and are purged.
+//! - **Protected keys** — never evicted. If a write can only fit by evicting a
+//!   protected key, the write is rejected and a
+//!   [`StoreEvent::ProtectedEvictionBlocked`] event fires.
+//!
+//! Sizes are the length of the key plus the value's compact JSON encoding.
+//! Limits are enforced on writes made through the [`Store`] API; writes made
+//! directly through the [`SharedStore`] lock are picked up the next time
+//! [`Store::enforce_limits`] runs.
+//!
+//! Limits belong to the `Store` handle, not to the [`SharedStore`] it wraps:
+//! [`Store::into_shared`] and [`Store::from_shared`] do not carry them, and
+//! [`Flow`] nodes write through the raw lock. To bound a store driven by a
+//! flow, keep the limited `Store` and enforce it after every node:
+//!
+//! ```rust,no_run
+//! use agentflow::core::flow::Flow;
+//! use agentflow::core::limits::StoreLimits;
+//! use agentflow::core::store::Store;
+//!
+//! let store = Store::new().with_limits(StoreLimits::new().with_max_total_bytes(1 << 20));
+//! let limited = store.clone();
+//! let flow = Flow::new().with_post_node_hook(move |_node: &str, shared| {
+//!     let limited = limited.clone();
+//!     async move {
+//!         if let Err(e) = limited.enforce_limits().await {
+//!             tracing::warn!(error = %e, "store over its limits");
+//!         }
+//!         shared
+//!     }
+//! });
+//! ```
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::limits::{EvictionPolicy, StoreLimits};
+//! use agentflow::core::store::Store;
+//! use std::time::Duration;
+//!
+//! let store = Store::new().with_limits(
+//!     StoreLimits::new()
+//!         .with_max_total_bytes(1 << 20)
+//!         .with_max_key_bytes(64 << 10)
+//!         .with_default_ttl(Duration::from_secs(600))
+//!         .with_eviction(EvictionPolicy::Lru)
+//!         .protect("conversation")
+//!         .on_event(|event| eprintln!("store: {event:?}")),
+//! );
+//! ```
+//!
+//! [`Store`]: crate::core::store::Store
+//! [`Store::with_limits`]: crate::core::store::Store::with_limits
+//! [`Store::set_with_ttl`]: crate::core::store::Store::set_with_ttl
+//! [`Store::enforce_limits`]: crate::core::store::Store::enforce_limits
+//! [`Store::into_shared`]: crate::core::store::Store::into_shared
+//! [`Store::from_shared`]: crate::core::store::Store::from_shared
+//! [`Flow`]: crate::core::flow::Flow
+//! [`SharedStore`]: crate::core::node::SharedStore
+
+use crate::core::error::AgentFlowError;
+use crate::core::sync::lock;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Callback choosing the next key to evict from a list of candidates.
+///
+/// Return `None` (or a key that is not a candidate) to refuse eviction, which
+/// rejects the write and emits [`StoreEvent::EvictionRefused`].
+pub type EvictionFn = Arc<dyn Fn(&[KeyStats]) -> Option<String> + Send + Sync>;
+
+/// Callback receiving every [`StoreEvent`].
+pub type StoreEventFn = Arc<dyn Fn(&StoreEvent) + Send + Sync>;
+
+/// Which key to evict when `max_total_bytes` would be exceeded.
+#[derive(Clone, Default)]
+pub enum EvictionPolicy {
+    /// Evict the least-recently read or written key.
+    #[default]
+    Lru,
+    /// Evict the key that was first inserted longest ago.
+    OldestFirst,
+    /// Let a callback pick the victim. See [`EvictionFn`].
+    Custom(EvictionFn),
+}
+
+impl std::fmt::Debug for EvictionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvictionPolicy::Lru => f.write_str("Lru"),
+            EvictionPolicy::OldestFirst => f.write_str("OldestFirst"),
+            EvictionPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Bookkeeping for one key, passed to [`EvictionPolicy::Custom`].
+#[derive(Debug, Clone)]
+pub struct KeyStats {
+    /// The key.
+    pub key: String,
+    /// Estimated size in bytes (key + compact JSON value).
+    pub size_bytes: usize,
+    /// When the key was first inserted.
+    pub inserted_at: Instant,
+    /// When the key was last read or written through the `Store` API.
+    pub last_access: Instant,
+    /// When the key expires, if it has a TTL.
+    pub expires_at: Option<Instant>,
+}
+
+/// Why a key left the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionReason {
+    /// Its TTL elapsed.
+    Expired,
+    /// It was chosen by the eviction policy to make room.
+    Capacity,
+}
+
+/// Notification emitted by a limited store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreEvent {
+    /// `key` was removed by the store itself.
+    Evicted {
+        /// The removed key.
+        key: String,
+        /// Its size at removal.
+        size_bytes: usize,
+        /// Why it was removed.
+        reason: EvictionReason,
+    },
+    /// A write to `key` was rejected because the entry alone exceeds
+    /// `max_key_bytes` or `max_total_bytes`, so no eviction could make room.
+    Rejected {
+        /// The key being written.
+        key: String,
+        /// The size of the rejected entry.
+        size_bytes: usize,
+        /// The cap it exceeds.
+        limit: usize,
+    },
+    /// A write to `key` was rejected because making room would have required
+    /// evicting one of `protected` keys.
+    ProtectedEvictionBlocked {
+        /// The key being written.
+        key: String,
+        /// Protected keys that would have had to go.
+        protected: Vec<String>,
+    },
+    /// A write to `key` was rejected because an [`EvictionPolicy::Custom`]
+    /// callback declined to choose a victim among evictable keys.
+    EvictionRefused {
+        /// The key being written.
+        key: String,
+    },
+}
+
+/// Capacity and expiry configuration for a [`Store`].
+///
+/// All limits are off by default; enable the ones you need with the
+/// `with_*` builders.
+///
+/// [`Store`]: crate::core::store::Store
+#[derive(Clone, Default)]
+pub struct StoreLimits {
+    /// Upper bound on the sum of all entry sizes.
+    pub max_total_bytes: Option<usize>,
+    /// Upper bound on a single entry's size.
+    pub max_key_bytes: Option<usize>,
+    /// TTL applied to keys written without an explicit TTL.
+    pub default_ttl: Option<Duration>,
+    /// How to choose keys to evict.
+    pub eviction: EvictionPolicy,
+    /// Keys that are never evicted (they can still expire if given a TTL).
+    pub protected_keys: HashSet<String>,
+    /// Optional event listener.
+    pub on_event: Option<StoreEventFn>,
+}
+
+impl StoreLimits {
+    /// No limits, LRU eviction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bound the total size of the store.
+    pub fn with_max_total_bytes(mut self, bytes: usize) -> Self {
+        self.max_total_bytes = Some(bytes);
+        self
+    }
+
+    /// Bound the size of a single entry.
+    pub fn with_max_key_bytes(mut self, bytes: usize) -> Self {
+        self.max_key_bytes = Some(bytes);
+        self
+    }
+
+    /// Expire keys `ttl` after they were last written.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Choose the eviction policy.
+    pub fn with_eviction(mut self, policy: EvictionPolicy) -> Self {
+        self.eviction = policy;
+        self
+    }
+
+    /// Never evict `key`.
+    pub fn protect(mut self, key: impl Into<String>) -> Self {
+        self.protected_keys.insert(key.into());
+        self
+    }
+
+    /// Register a listener for [`StoreEvent`]s.
+    pub fn on_event<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&StoreEvent) + Send + Sync + 'static,
+    {
+        self.on_event = Some(Arc::new(f));
+        self
+    }
+}
+
+/// Shared limit state held by a `Store` and all of its clones.
+pub(crate) struct LimitState {
+    limits: StoreLimits,
+    meta: Mutex<HashMap<String, KeyStats>>,
+}
+
+/// Estimated size of one entry: key bytes plus compact JSON value bytes.
+pub(crate) fn entry_size(key: &str, value: &Value) -> usize {
+    key.len() + serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
+}
+
+impl LimitState {
+    pub(crate) fn new(limits: StoreLimits, data: &HashMap<String, Value>) -> Self {
+        let now = Instant::now();
+        let meta = data
+            .iter()
+            .map(|(k, v)| {
+                let stats = KeyStats {
+                    key: k.clone(),
+                    size_bytes: entry_size(k, v),
+                    inserted_at: now,
+                    last_access: now,
+                    expires_at: limits.default_ttl.map(|ttl| now + ttl),
+                };
+                (k.clone(), stats)
+            })
+            .collect();
+        Self {
+            limits,
+            meta: Mutex::new(meta),
+        }
+    }
+
+    fn meta(&self) -> std::sync::MutexGuard<'_, HashMap<String, KeyStats>> {
+        lock(&self.meta)
+    }
+
+    fn emit(&self, event: StoreEvent) {
+        debug!(?event, "Store limit event");
+        if let Some(f) = &self.limits.on_event {
+            f(&event);
+        }
+    }
+
+    /// Returns `true` if `key` has a TTL that has elapsed.
+    pub(crate) fn is_expired(&self, key: &str) -> bool {
+        let now = Instant::now();
+        self.meta()
+            .get(key)
+            .and_then(|m| m.expires_at)
+            .is_some_and(|at| at <= now)
+    }
+
+    /// Record a read of `key`.
+    pub(crate) fn touch(&self, key: &str) {
+        if let Some(m) = self.meta().get_mut(key) {
+            m.last_access = Instant::now();
+        }
+    }
+
+    /// Forget `key` after it was removed by the caller.
+    pub(crate) fn forget(&self, key: &str) {
+        self.meta().remove(key);
+    }
+
+    /// Forget every key after the store was cleared.
+    pub(crate) fn forget_all(&self) {
+        self.meta().clear();
+    }
+
+    /// Remove expired keys from `data`, returning their names.
+    pub(crate) fn purge_expired(&self, data: &mut HashMap<String, Value>) -> Vec<String> {
+        let now = Instant::now();
+        let expired: Vec<(String, usize)> = self
+            .meta()
+            .values()
+            .filter(|m| m.expires_at.is_some_and(|at| at <= now))
+            .map(|m| (m.key.clone(), m.size_bytes))
+            .collect();
+        for (key, size_bytes) in &expired {
+            data.remove(key);
+            self.forget(key);
+            self.emit(StoreEvent::Evicted {
+                key: key.clone(),
+                size_bytes: *size_bytes,
+                reason: EvictionReason::Expired,
+            });
+        }
+        expired.into_iter().map(|(k, _)| k).collect()
+    }
+
+    /// Check that `key = value` may be written, evicting other keys from
+    /// `data` as needed. Returns the keys that were removed.
+    ///
+    /// On error nothing has been evicted (expired keys may have been purged).
+    pub(crate) fn admit(
+        &self,
+        data: &mut HashMap<String, Value>,
+        key: &str,
+        value: &Value,
+        ttl: Option<Duration>,
+    ) -> Result<Vec<String>, AgentFlowError> {
+        let size = entry_size(key, value);
+        if let Some(limit) = self.limits.max_key_bytes {
+            if size > limit {
+                self.emit(StoreEvent::Rejected {
+                    key: key.to_string(),
+                    size_bytes: size,
+                    limit,
+                });
+                return Err(AgentFlowError::ExecutionLimitExceeded(format!(
+                    "Value for key '{}' is {} bytes, over the per-key limit of {} bytes",
+                    key, size, limit
+                )));
+            }
+        }
+
+        if let Some(limit) = self.limits.max_total_bytes.filter(|&limit| size > limit) {
+            self.emit(StoreEvent::Rejected {
+                key: key.to_string(),
+                size_bytes: size,
+                limit,
+            });
+            return Err(AgentFlowError::ExecutionLimitExceeded(format!(
+                "Value for key '{}' is {} bytes, over the store limit of {} bytes",
+                key, size, limit
+            )));
+        }
+
+        let mut removed = self.purge_expired(data);
+
+        if let Some(max_total) = self.limits.max_total_bytes {
+            let victims = self.pick_victims(key, size, max_total)?;
+            for victim in victims {
+                let size_bytes = self.meta().remove(&victim).map(|m| m.size_bytes);
+                data.remove(&victim);
+                self.emit(StoreEvent::Evicted {
+                    key: victim.clone(),
+                    size_bytes: size_bytes.unwrap_or(0),
+                    reason: EvictionReason::Capacity,
+                });
+                removed.push(victim);
+            }
+        }
+
+        let now = Instant::now();
+        let ttl = ttl.or(self.limits.default_ttl);
+        let mut meta = self.meta();
+        let entry = meta.entry(key.to_string()).or_insert_with(|| KeyStats {
+            key: key.to_string(),
+            size_bytes: size,
+            inserted_at: now,
+            last_access: now,
+            expires_at: None,
+        });
+        entry.size_bytes = size;
+        entry.last_access = now;
+        entry.expires_at = ttl.map(|ttl| now + ttl);
+        Ok(removed)
+    }
+
+    /// Choose keys to evict so that writing `size` bytes under `key` keeps the
+    /// total within `max_total`. Does not mutate anything.
+    fn pick_victims(
+        &self,
+        key: &str,
+        size: usize,
+        max_total: usize,
+    ) -> Result<Vec<String>, AgentFlowError> {
+        let meta = self.meta();
+        let mut total: usize = meta
+            .values()
+            .filter(|m| m.key != key)
+            .map(|m| m.size_bytes)
+            .sum::<usize>()
+            + size;
+        if total <= max_total {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates: Vec<KeyStats> = meta
+            .values()
+            .filter(|m| m.key != key && !self.limits.protected_keys.contains(&m.key))
+            .cloned()
+            .collect();
+        drop(meta);
+
+        let mut victims = Vec::new();
+        while total > max_total {
+            let victim = match &self.limits.eviction {
+                EvictionPolicy::Lru => candidates
+                    .iter()
+                    .min_by_key(|m| m.last_access)
+                    .map(|m| m.key.clone()),
+                EvictionPolicy::OldestFirst => candidates
+                    .iter()
+                    .min_by_key(|m| m.inserted_at)
+                    .map(|m| m.key.clone()),
+                EvictionPolicy::Custom(f) => f(&candidates),
+            };
+            let Some(pos) = victim.and_then(|v| candidates.iter().position(|m| m.key == v)) else {
+                if !candidates.is_empty() {
+                    warn!(
+                        key,
+                        "Custom eviction policy refused to pick a victim; rejecting"
+                    );
+                    self.emit(StoreEvent::EvictionRefused {
+                        key: key.to_string(),
+                    });
+                    return Err(AgentFlowError::ExecutionLimitExceeded(format!(
+                        "Writing key '{}' would exceed the store limit of {} bytes; \
+                         the eviction policy refused to evict any of {} candidate keys",
+                        key,
+                        max_total,
+                        candidates.len()
+                    )));
+                }
+                let mut protected: Vec<String> = self
+                    .meta()
+                    .keys()
+                    .filter(|k| self.limits.protected_keys.contains(*k))
+                    .cloned()
+                    .collect();
+                protected.sort();
+                warn!(
+                    key,
+                    ?protected,
+                    "Store write needs to evict a protected key; rejecting"
+                );
+                self.emit(StoreEvent::ProtectedEvictionBlocked {
+                    key: key.to_string(),
+                    protected: protected.clone(),
+                });
+                return Err(AgentFlowError::ExecutionLimitExceeded(format!(
+                    "Writing key '{}' would exceed the store limit of {} bytes; \
+                     remaining keys are protected: {:?}",
+                    key, max_total, protected
+                )));
+            };
+            let stats = candidates.swap_remove(pos);
+            total -= stats.size_bytes;
+            victims.push(stats.key);
+        }
+        Ok(victims)
+    }
+
+    /// Bring an existing map within limits: refresh sizes (to account for
+    /// writes that bypassed the `Store` API), purge expired keys and evict
+    /// down to `max_total_bytes`. Returns the removed keys.
+    pub(crate) fn enforce(
+        &self,
+        data: &mut HashMap<String, Value>,
+    ) -> Result<Vec<String>, AgentFlowError> {
+        {
+            let now = Instant::now();
+            let mut meta = self.meta();
+            meta.retain(|k, _| data.contains_key(k));
+            for (k, v) in data.iter() {
+                let size = entry_size(k, v);
+                meta.entry(k.clone())
+                    .and_modify(|m| m.size_bytes = size)
+                    .or_insert_with(|| KeyStats {
+                        key: k.clone(),
+                        size_bytes: size,
+                        inserted_at: now,
+                        last_access: now,
+                        expires_at: self.limits.default_ttl.map(|ttl| now + ttl),
+                    });
+            }
+        }
+
+        let mut removed = self.purge_expired(data);
+        if let Some(max_total) = self.limits.max_total_bytes {
+            for victim in self.pick_victims("", 0, max_total)? {
+                let size_bytes = self.meta().remove(&victim).map(|m| m.size_bytes);
+                data.remove(&victim);
+                self.emit(StoreEvent::Evicted {
+                    key: victim.clone(),
+                    size_bytes: size_bytes.unwrap_or(0),
+                    reason: EvictionReason::Capacity,
+                });
+                removed.push(victim);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Current total of all tracked entry sizes.
+    pub(crate) fn total_bytes(&self) -> usize {
+        self.meta().values().map(|m| m.size_bytes).sum()
+    }
+}
@@ -2,31 +2,69 @@
 
 /// Batch execution primitives.
 pub mod batch;
+/// Adapters between `Flow` and `TypedFlow`.
+pub mod bridge;
+/// Model pricing and spend limits.
+pub mod budget;
+/// Circuit breaker for fallible nodes.
+pub mod circuit_breaker;
+/// Chat messages and conversation history.
+pub mod conversation;
 /// AgentFlow unified error types.
 pub mod error;
 /// Graph-based flow orchestrator.
 pub mod flow;
+/// Typed control transfer between agent nodes.
+pub mod handoff;
+/// Store capacity limits, TTLs and eviction.
+pub mod limits;
 /// Core node traits and types.
 pub mod node;
 pub mod parallel;
+/// Store snapshots and persistent backends.
+pub mod persistence;
+/// Shared rate limiters and concurrency pools.
+pub mod resource_pool;
+/// Retry policies and backoff schedules.
+pub mod retry;
+/// Secret values and redaction helpers.
+pub mod secret;
 /// Shared state storage.
 pub mod store;
+/// Poison-tolerant locking.
+pub(crate) mod sync;
 /// Telemetry metrics and context.
 pub mod telemetry;
 /// Strongly-typed flow orchestrator.
 pub mod typed_flow;
+/// Fork / join parallelism for typed flows.
+pub mod typed_parallel;
 /// Strongly-typed state storage.
 pub mod typed_store;
 
 pub use batch::{Batch, ParallelBatch};
-pub use error::AgentFlowError;
+pub use bridge::{FlowTypedNode, TypedFlowNode};
+pub use budget::{Budget, ModelPrice, PriceTable};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerNode, CircuitEvent, CircuitState};
+pub use conversation::{ChatMessage, ContentPart, Conversation, Role, ToolCall};
+pub use error::{AgentFlowError, ErrorContext};
 pub use flow::Flow;
+pub use handoff::Handoff;
+pub use limits::{EvictionPolicy, StoreEvent, StoreLimits};
 pub use node::{
     create_batch_node, create_diff_node, create_node, create_result_node, Node, NodeResult,
     ResultNode, SharedStore, SimpleNode, StateDiff,
 };
 pub use parallel::ParallelFlow;
+pub use persistence::{FileLogBackend, StoreBackend, StoreFormat, StoreOp};
+pub use resource_pool::{PrioritySemaphore, RateLimit, RateLimiter, ResourcePool, Throttled};
+pub use retry::{Backoff, RetryPolicy};
+pub use secret::SecretString;
 pub use store::Store;
-pub use telemetry::FlowContext;
-pub use typed_flow::{create_typed_node, SimpleTypedNode, TypedFlow, TypedNode};
+pub use telemetry::{FlowContext, LlmUsage, UsageTotals};
+pub use typed_flow::{
+    create_typed_node, create_typed_result_node, FlowAction, IntoTypedFlowNode, SimpleTypedNode,
+    TypedFlow, TypedNode, TypedNodeResult, TypedResultNode,
+};
+pub use typed_parallel::TypedParallel;
 pub use typed_store::TypedStore;
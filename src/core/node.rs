@@ -1,4 +1,5 @@
 use crate::core::error::AgentFlowError;
+use crate::core::retry::RetryPolicy;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::future::Future;
@@ -19,6 +20,11 @@ use dyn_clone::DynClone;
 /// - `"action"` — read by [`Flow`] after each node to determine the next
 ///   transition. Nodes should write this key to control routing.
 ///   It is automatically removed by `Flow` when execution ends.
+/// - `"conversation"` — the shared chat history, a JSON array of messages.
+///   See [`Conversation`](crate::core::conversation::Conversation).
+/// - `"llm_usage"` — LLM calls made by the current node, consumed after each
+///   node by a `Flow` with a [`Budget`]. See
+///   [`record_llm_usage`](crate::core::telemetry::record_llm_usage).
 ///
 /// # Deadlock prevention
 ///
@@ -38,6 +44,7 @@ use dyn_clone::DynClone;
 /// ```
 ///
 /// [`Flow`]: crate::core::flow::Flow
+/// [`Budget`]: crate::core::budget::Budget
 pub type SharedStore = Arc<tokio::sync::RwLock<HashMap<String, Value>>>;
 
 /// Core async node trait.
@@ -358,6 +365,12 @@ where
 ///
 /// If all retries fail and no `fallback` is provided, a JSON error object is
 /// passed to `post`. If a `fallback` function is provided it is called instead.
+///
+/// `max_retries` is the total number of `exec` attempts; `0` is treated as
+/// `1`, so `exec` always runs at least once.
+///
+/// Equivalent to [`create_retry_node_with_policy`] with
+/// [`RetryPolicy::fixed`].
 pub fn create_retry_node<PrepF, PrepFut, ExecF, ExecFut, PostF, PostFut>(
     prep: PrepF,
     exec: ExecF,
@@ -366,6 +379,32 @@ pub fn create_retry_node<PrepF, PrepFut, ExecF, ExecFut, PostF, PostFut>(
     wait_millis: u64,
     fallback: Option<fn(&SharedStore, &Value, &AgentFlowError) -> SharedStore>,
 ) -> SimpleNode
+where
+    PrepF: Fn(SharedStore) -> PrepFut + Send + Sync + Clone + 'static,
+    PrepFut: Future<Output = Value> + Send + 'static,
+    ExecF: Fn(&SharedStore, &Value) -> ExecFut + Send + Sync + Clone + 'static,
+    ExecFut: Future<Output = Result<Value, AgentFlowError>> + Send + 'static,
+    PostF: Fn(SharedStore, &Value, &Value) -> PostFut + Send + Sync + Clone + 'static,
+    PostFut: Future<Output = SharedStore> + Send + 'static,
+{
+    let policy = RetryPolicy::fixed(max_retries, Duration::from_millis(wait_millis));
+    create_retry_node_with_policy(prep, exec, post, policy, fallback)
+}
+
+/// [`create_retry_node`] driven by a [`RetryPolicy`].
+///
+/// Every `exec` error is retried unless the policy's [`retry_if`] predicate
+/// rejects it. The policy's output predicate does not apply here, since
+/// `exec` returns a value rather than a store.
+///
+/// [`retry_if`]: RetryPolicy::retry_if
+pub fn create_retry_node_with_policy<PrepF, PrepFut, ExecF, ExecFut, PostF, PostFut>(
+    prep: PrepF,
+    exec: ExecF,
+    post: PostF,
+    policy: RetryPolicy,
+    fallback: Option<fn(&SharedStore, &Value, &AgentFlowError) -> SharedStore>,
+) -> SimpleNode
 where
     PrepF: Fn(SharedStore) -> PrepFut + Send + Sync + Clone + 'static,
     PrepFut: Future<Output = Value> + Send + 'static,
@@ -379,8 +418,7 @@ where
         prep: PrepF,
         exec: ExecF,
         post: PostF,
-        max_retries: usize,
-        wait_millis: u64,
+        policy: RetryPolicy,
         fallback: Option<fn(&SharedStore, &Value, &AgentFlowError) -> SharedStore>,
     }
 
@@ -401,36 +439,37 @@ where
             let prep = self.prep.clone();
             let exec = self.exec.clone();
             let post = self.post.clone();
-            let max_retries = self.max_retries;
-            let wait_millis = self.wait_millis;
             let fallback = self.fallback;
             Box::pin(async move {
                 let prep_res = prep(input.clone()).await;
-                let mut last_err: Option<AgentFlowError> = None;
-                let mut exec_res: Option<Value> = None;
-                for attempt in 0..max_retries {
+                let mut retry = self.policy.start();
+                let outcome = loop {
                     match exec(&input, &prep_res).await {
-                        Ok(val) => {
-                            exec_res = Some(val);
-                            break;
-                        }
+                        Ok(val) => break Ok(val),
                         Err(e) => {
-                            last_err = Some(e);
-                            if attempt + 1 < max_retries && wait_millis > 0 {
-                                tokio::time::sleep(Duration::from_millis(wait_millis)).await;
+                            let delay = if self.policy.should_retry_error(&e, true) {
+                                retry.next_delay(Some(&e))
+                            } else {
+                                None
+                            };
+                            match delay {
+                                Some(delay) => tokio::time::sleep(delay).await,
+                                None => break Err(e),
                             }
                         }
                     }
-                }
-                let exec_val = if let Some(val) = exec_res {
-                    val
-                } else if let Some(fallback_fn) = fallback {
-                    let default_err = AgentFlowError::NodeFailure("no error recorded".into());
-                    let _fallback_store =
-                        fallback_fn(&input, &prep_res, last_err.as_ref().unwrap_or(&default_err));
-                    serde_json::json!({"error": "fallback triggered"})
-                } else {
-                    serde_json::json!({"error": format!("Node failed after {} retries: {:?}", max_retries, last_err)})
+                };
+                let exec_val = match outcome {
+                    Ok(val) => val,
+                    Err(e) => match fallback {
+                        Some(fallback_fn) => {
+                            let _fallback_store = fallback_fn(&input, &prep_res, &e);
+                            serde_json::json!({"error": "fallback triggered"})
+                        }
+                        None => {
+                            serde_json::json!({"error": format!("Node failed after {} retries: {:?}", retry.attempts(), Some(e))})
+                        }
+                    },
                 };
                 post(input, &prep_res, &exec_val).await
             })
@@ -441,8 +480,7 @@ where
         prep,
         exec,
         post,
-        max_retries,
-        wait_millis,
+        policy,
         fallback,
     })
 }
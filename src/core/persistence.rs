@@ -0,0 +1,301 @@
+//! Store persistence: snapshot files and pluggable write-through backends.
+//!
+//! A [`Store`] lives in memory by default. This module adds two ways to keep
+//! its contents across process restarts:
+//!
+//! 1. **Snapshots** — [`Store::save_to`] / [`Store::load_from`] write or read
+//!    the whole map in one go, either as JSON or as compact binary
+//!    (MessagePack). See [`StoreFormat`].
+//! 2. **Backends** — a [`StoreBackend`] receives every mutation made through
+//!    the [`Store`] API as a [`StoreOp`] and can replay them on start-up.
+//!    [`FileLogBackend`] is an append-only JSON-lines log that survives
+//!    crashes: a torn final line is dropped on replay.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::persistence::FileLogBackend;
+//! use agentflow::core::store::Store;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), agentflow::core::error::AgentFlowError> {
+//!     // First run: every write is appended to `session.log`.
+//!     let store = Store::with_backend(FileLogBackend::new("session.log")).await?;
+//!     store.set_string("user_name", "Alice").await;
+//!
+//!     // Next run: the log is replayed, so the value is already there.
+//!     let store = Store::with_backend(FileLogBackend::new("session.log")).await?;
+//!     assert_eq!(store.get_string("user_name").await.as_deref(), Some("Alice"));
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`Store`]: crate::core::store::Store
+//! [`Store::save_to`]: crate::core::store::Store::save_to
+//! [`Store::load_from`]: crate::core::store::Store::load_from
+
+use crate::core::error::AgentFlowError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tracing::{debug, warn};
+
+/// On-disk encoding used by [`Store::save_to`] and [`Store::load_from`].
+///
+/// [`Store::save_to`]: crate::core::store::Store::save_to
+/// [`Store::load_from`]: crate::core::store::Store::load_from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreFormat {
+    /// Pretty-printed JSON. Human-readable and diff-friendly.
+    Json,
+    /// MessagePack. Smaller and faster to parse than JSON.
+    Binary,
+}
+
+impl StoreFormat {
+    /// Pick a format from a file extension: `.bin`, `.msgpack` and `.mpk`
+    /// select [`Binary`](Self::Binary); anything else selects
+    /// [`Json`](Self::Json).
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("bin") | Some("msgpack") | Some("mpk") => StoreFormat::Binary,
+            _ => StoreFormat::Json,
+        }
+    }
+
+    /// Encode a store snapshot into bytes.
+    pub fn encode(&self, data: &HashMap<String, Value>) -> Result<Vec<u8>, AgentFlowError> {
+        match self {
+            StoreFormat::Json => Ok(serde_json::to_vec_pretty(data)?),
+            StoreFormat::Binary => rmp_serde::to_vec(data).map_err(|e| {
+                AgentFlowError::Custom(format!("Failed to encode store snapshot: {}", e))
+            }),
+        }
+    }
+
+    /// Decode bytes produced by [`encode`](Self::encode).
+    pub fn decode(&self, bytes: &[u8]) -> Result<HashMap<String, Value>, AgentFlowError> {
+        match self {
+            StoreFormat::Json => Ok(serde_json::from_slice(bytes)?),
+            StoreFormat::Binary => rmp_serde::from_slice(bytes).map_err(|e| {
+                AgentFlowError::Custom(format!("Failed to decode store snapshot: {}", e))
+            }),
+        }
+    }
+}
+
+/// A single mutation recorded by a [`StoreBackend`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum StoreOp {
+    /// `key` was inserted or overwritten with `value`.
+    Set {
+        /// The key written.
+        key: String,
+        /// The new value.
+        value: Value,
+    },
+    /// `key` was removed.
+    Remove {
+        /// The key removed.
+        key: String,
+    },
+    /// Every key was removed.
+    Clear,
+}
+
+impl StoreOp {
+    /// Apply this operation to an in-memory map.
+    pub fn apply(self, data: &mut HashMap<String, Value>) {
+        match self {
+            StoreOp::Set { key, value } => {
+                data.insert(key, value);
+            }
+            StoreOp::Remove { key } => {
+                data.remove(&key);
+            }
+            StoreOp::Clear => data.clear(),
+        }
+    }
+}
+
+/// Boxed future returned by [`StoreBackend`] methods.
+pub type BackendFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, AgentFlowError>> + Send + 'a>>;
+
+/// Durable storage behind a [`Store`].
+///
+/// Attach a backend with [`Store::with_backend`]. The store calls
+/// [`load`](Self::load) once to restore its contents, then
+/// [`record`](Self::record) after every mutation made through the `Store`
+/// API.
+///
+/// Writes made directly through the underlying [`SharedStore`] lock bypass the
+/// backend — call [`Store::compact`] afterwards to persist them.
+///
+/// [`Store`]: crate::core::store::Store
+/// [`Store::with_backend`]: crate::core::store::Store::with_backend
+/// [`Store::compact`]: crate::core::store::Store::compact
+/// [`SharedStore`]: crate::core::node::SharedStore
+pub trait StoreBackend: Send + Sync {
+    /// Restore the last persisted state. An empty map means "nothing saved yet".
+    fn load(&self) -> BackendFuture<'_, HashMap<String, Value>>;
+
+    /// Persist a single mutation.
+    fn record(&self, op: StoreOp) -> BackendFuture<'_, ()>;
+
+    /// Replace everything persisted so far with `snapshot`.
+    fn compact(&self, snapshot: HashMap<String, Value>) -> BackendFuture<'_, ()>;
+}
+
+/// Append-only JSON-lines log of [`StoreOp`]s.
+///
+/// Each mutation is appended as one line and flushed before the call returns,
+/// so a process crash loses at most the write in flight. On [`load`] the log
+/// is replayed from the top; a truncated or corrupt **final** line is dropped
+/// with a warning and cut from the file, so later appends start on a clean
+/// line. Corruption earlier in the file is reported as an error.
+///
+/// The log grows without bound; call [`Store::compact`] periodically to
+/// rewrite it as one `set` line per live key.
+///
+/// [`load`]: StoreBackend::load
+/// [`Store::compact`]: crate::core::store::Store::compact
+pub struct FileLogBackend {
+    path: PathBuf,
+    file: tokio::sync::Mutex<Option<tokio::fs::File>>,
+}
+
+impl FileLogBackend {
+    /// Create a backend that logs to `path`. The file is created on the first
+    /// write if it does not exist.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn append_line(&self, line: &[u8]) -> Result<(), AgentFlowError> {
+        let mut guard = self.file.lock().await;
+        if guard.is_none() {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+            // Never append onto an unterminated line left by a crash.
+            if !ends_with_newline(&self.path).await? {
+                file.write_all(b"\n").await?;
+            }
+            *guard = Some(file);
+        }
+        if let Some(file) = guard.as_mut() {
+            file.write_all(line).await?;
+            file.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` is missing, empty or ends with `\n`.
+async fn ends_with_newline(path: &Path) -> Result<bool, AgentFlowError> {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+        Err(e) => return Err(e.into()),
+    };
+    let len = file.metadata().await?.len();
+    if len == 0 {
+        return Ok(true);
+    }
+    file.seek(std::io::SeekFrom::Start(len - 1)).await?;
+    let mut last = [0u8; 1];
+    file.read_exact(&mut last).await?;
+    Ok(last[0] == b'\n')
+}
+
+impl StoreBackend for FileLogBackend {
+    fn load(&self) -> BackendFuture<'_, HashMap<String, Value>> {
+        Box::pin(async move {
+            let content = match tokio::fs::read_to_string(&self.path).await {
+                Ok(c) => c,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+                Err(e) => return Err(e.into()),
+            };
+
+            let mut lines = Vec::new();
+            let mut offset = 0;
+            for line in content.split_inclusive('\n') {
+                if !line.trim().is_empty() {
+                    lines.push((offset, line));
+                }
+                offset += line.len();
+            }
+            let mut data = HashMap::new();
+            for (idx, &(start, line)) in lines.iter().enumerate() {
+                match serde_json::from_str::<StoreOp>(line) {
+                    Ok(op) => op.apply(&mut data),
+                    Err(e) if idx + 1 == lines.len() => {
+                        warn!(path = %self.path.display(), error = %e, "Dropping torn final line in store log");
+                        // Cut the fragment off so the next append starts on
+                        // a clean line instead of extending it.
+                        let _guard = self.file.lock().await;
+                        let file = tokio::fs::OpenOptions::new()
+                            .write(true)
+                            .open(&self.path)
+                            .await?;
+                        file.set_len(start as u64).await?;
+                        file.sync_all().await?;
+                    }
+                    Err(e) => {
+                        return Err(AgentFlowError::Custom(format!(
+                            "Corrupt store log '{}' at line {}: {}",
+                            self.path.display(),
+                            idx + 1,
+                            e
+                        )));
+                    }
+                }
+            }
+            debug!(path = %self.path.display(), ops = lines.len(), keys = data.len(), "Replayed store log");
+            Ok(data)
+        })
+    }
+
+    fn record(&self, op: StoreOp) -> BackendFuture<'_, ()> {
+        Box::pin(async move {
+            let mut line = serde_json::to_vec(&op)?;
+            line.push(b'\n');
+            self.append_line(&line).await
+        })
+    }
+
+    fn compact(&self, snapshot: HashMap<String, Value>) -> BackendFuture<'_, ()> {
+        Box::pin(async move {
+            let mut buf = Vec::new();
+            for (key, value) in snapshot {
+                serde_json::to_writer(&mut buf, &StoreOp::Set { key, value })?;
+                buf.push(b'\n');
+            }
+
+            // Hold the append lock for the whole swap so no write is lost
+            // between the rewrite and the rename.
+            let mut guard = self.file.lock().await;
+            let tmp = self.path.with_extension("compact.tmp");
+            tokio::fs::write(&tmp, &buf).await?;
+            tokio::fs::rename(&tmp, &self.path).await?;
+            *guard = None;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,604 @@
+//! Process-wide rate limiters and concurrency pools shared across flows.
+//!
+//! [`ParallelBatch::with_concurrency_limit`] bounds one batch; a provider
+//! quota has to be shared by every flow running in the process. A
+//! [`ResourcePool`] is a registry of named resources:
+//!
+//! - [`RateLimiter`] — a token bucket, e.g. 10 requests/s or 90 000 LLM
+//!   tokens/min ([`RateLimit`]).
+//! - [`PrioritySemaphore`] — at most N concurrent holders.
+//!
+//! Waiters are served by priority (higher first) and, within a priority, in
+//! arrival order, so a burst from one flow cannot starve another. Each
+//! resource keeps [`ResourceMetrics`] with the time callers spent waiting.
+//!
+//! Use [`ResourcePool::global`] to share resources across the whole process,
+//! and [`Throttled`] to make a node acquire them before it runs.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::resource_pool::{RateLimit, ResourcePool, Throttled};
+//! use agentflow::prelude::*;
+//!
+//! # let call_llm = create_result_node(|s: SharedStore| async move { Ok(s) });
+//! let pool = ResourcePool::global();
+//! let requests = pool.register_rate_limiter("openai.requests", RateLimit::per_second(10.0));
+//! let slots = pool.register_semaphore("openai.concurrent", 8);
+//!
+//! // Every flow that builds this node shares the same quota.
+//! let node = Throttled::new(call_llm)
+//!     .with_rate_limit(requests, 1.0)
+//!     .with_semaphore(slots)
+//!     .with_priority(10);
+//! let mut flow = Flow::new();
+//! flow.add_result_node("answer", Box::new(node));
+//! ```
+//!
+//! [`ParallelBatch::with_concurrency_limit`]: crate::core::batch::ParallelBatch::with_concurrency_limit
+
+use crate::core::error::AgentFlowError;
+use crate::core::node::{Node, NodeResult, SharedStore};
+use crate::core::sync::lock;
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Longest a [`RateLimiter`] head waiter sleeps before checking the bucket
+/// again, for limits so slow that the exact wait does not fit a `Duration`.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(3600);
+
+/// Refill rate and burst size of a [`RateLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Units added to the bucket per second.
+    pub per_second: f64,
+    /// Bucket size: the most units that can be taken at once.
+    pub burst: f64,
+}
+
+impl RateLimit {
+    /// `rate` units per second, with a burst of one second's worth.
+    pub fn per_second(rate: f64) -> Self {
+        Self {
+            per_second: rate,
+            burst: rate.max(1.0),
+        }
+    }
+
+    /// `rate` units per minute (e.g. LLM tokens/min), with a burst of one
+    /// minute's worth.
+    pub fn per_minute(rate: f64) -> Self {
+        Self {
+            per_second: rate / 60.0,
+            burst: rate.max(1.0),
+        }
+    }
+
+    /// Override the bucket size.
+    pub fn with_burst(mut self, burst: f64) -> Self {
+        self.burst = burst;
+        self
+    }
+}
+
+/// Wait-time statistics of one resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceMetrics {
+    /// Successful acquisitions.
+    pub acquisitions: u64,
+    /// Callers currently waiting.
+    pub waiting: usize,
+    /// Sum of time spent waiting by all acquisitions.
+    pub total_wait: Duration,
+    /// Longest single wait.
+    pub max_wait: Duration,
+}
+
+impl ResourceMetrics {
+    /// Average wait per acquisition.
+    pub fn mean_wait(&self) -> Duration {
+        match u32::try_from(self.acquisitions) {
+            Ok(0) => Duration::ZERO,
+            Ok(n) => self.total_wait / n,
+            Err(_) => self.total_wait.div_f64(self.acquisitions as f64),
+        }
+    }
+
+    fn record(&mut self, waited: Duration) {
+        self.acquisitions += 1;
+        self.total_wait += waited;
+        self.max_wait = self.max_wait.max(waited);
+    }
+}
+
+/// Registry of named, shared [`RateLimiter`]s and [`PrioritySemaphore`]s.
+///
+/// Handles are cheap to clone and refer to the same underlying resource.
+#[derive(Default)]
+pub struct ResourcePool {
+    rate_limiters: Mutex<HashMap<String, RateLimiter>>,
+    semaphores: Mutex<HashMap<String, PrioritySemaphore>>,
+}
+
+impl ResourcePool {
+    /// An empty pool, independent of [`global`](Self::global).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide pool.
+    pub fn global() -> &'static ResourcePool {
+        static GLOBAL: OnceLock<ResourcePool> = OnceLock::new();
+        GLOBAL.get_or_init(ResourcePool::new)
+    }
+
+    /// Register a rate limiter, or return the existing one named `name`
+    /// (whose limit is kept).
+    pub fn register_rate_limiter(&self, name: &str, limit: RateLimit) -> RateLimiter {
+        lock(&self.rate_limiters)
+            .entry(name.to_string())
+            .or_insert_with(|| RateLimiter::new(name, limit))
+            .clone()
+    }
+
+    /// Register a semaphore with `permits` slots, or return the existing one
+    /// named `name` (whose size is kept).
+    pub fn register_semaphore(&self, name: &str, permits: usize) -> PrioritySemaphore {
+        lock(&self.semaphores)
+            .entry(name.to_string())
+            .or_insert_with(|| PrioritySemaphore::new(name, permits))
+            .clone()
+    }
+
+    /// The rate limiter named `name`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::NotFound`] if it is not registered.
+    pub fn rate_limiter(&self, name: &str) -> Result<RateLimiter, AgentFlowError> {
+        lock(&self.rate_limiters)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AgentFlowError::NotFound(format!("Rate limiter '{}'", name)))
+    }
+
+    /// The semaphore named `name`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::NotFound`] if it is not registered.
+    pub fn semaphore(&self, name: &str) -> Result<PrioritySemaphore, AgentFlowError> {
+        lock(&self.semaphores)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AgentFlowError::NotFound(format!("Semaphore '{}'", name)))
+    }
+
+    /// Metrics of every registered resource, keyed by name.
+    pub fn metrics(&self) -> HashMap<String, ResourceMetrics> {
+        let mut all: HashMap<_, _> = lock(&self.rate_limiters)
+            .iter()
+            .map(|(name, r)| (name.clone(), r.metrics()))
+            .collect();
+        all.extend(
+            lock(&self.semaphores)
+                .iter()
+                .map(|(name, s)| (name.clone(), s.metrics())),
+        );
+        all
+    }
+}
+
+/// FIFO-within-priority queue of waiters.
+#[derive(Default)]
+struct Waiters {
+    queue: BTreeSet<(Reverse<i32>, u64)>,
+    next_seq: u64,
+}
+
+impl Waiters {
+    fn push(&mut self, priority: i32) -> (Reverse<i32>, u64) {
+        let ticket = (Reverse(priority), self.next_seq);
+        self.next_seq += 1;
+        self.queue.insert(ticket);
+        ticket
+    }
+
+    fn is_head(&self, ticket: &(Reverse<i32>, u64)) -> bool {
+        self.queue.first() == Some(ticket)
+    }
+}
+
+/// Removes a waiter from its queue if the acquiring future is dropped.
+struct Ticket<'a, S: HasWaiters> {
+    state: &'a Mutex<S>,
+    notify: &'a Notify,
+    ticket: (Reverse<i32>, u64),
+    active: bool,
+}
+
+trait HasWaiters {
+    fn waiters(&mut self) -> &mut Waiters;
+}
+
+impl<S: HasWaiters> Drop for Ticket<'_, S> {
+    fn drop(&mut self) {
+        if self.active {
+            lock(self.state).waiters().queue.remove(&self.ticket);
+            self.notify.notify_waiters();
+        }
+    }
+}
+
+/// A shared token bucket. See the [module docs](self).
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<RateLimiterInner>,
+}
+
+struct RateLimiterInner {
+    name: String,
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+    notify: Notify,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+    waiters: Waiters,
+    metrics: ResourceMetrics,
+}
+
+impl HasWaiters for BucketState {
+    fn waiters(&mut self) -> &mut Waiters {
+        &mut self.waiters
+    }
+}
+
+impl BucketState {
+    fn refill(&mut self, limit: &RateLimit) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst);
+        self.refilled_at = now;
+    }
+}
+
+impl RateLimiter {
+    /// A standalone limiter, starting with a full bucket. Prefer
+    /// [`ResourcePool::register_rate_limiter`] to share it.
+    pub fn new(name: &str, limit: RateLimit) -> Self {
+        Self {
+            inner: Arc::new(RateLimiterInner {
+                name: name.to_string(),
+                limit,
+                state: Mutex::new(BucketState {
+                    tokens: limit.burst,
+                    refilled_at: Instant::now(),
+                    waiters: Waiters::default(),
+                    metrics: ResourceMetrics::default(),
+                }),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// The limiter's name.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// The configured limit.
+    pub fn limit(&self) -> RateLimit {
+        self.inner.limit
+    }
+
+    /// Wait-time statistics.
+    pub fn metrics(&self) -> ResourceMetrics {
+        let state = lock(&self.inner.state);
+        ResourceMetrics {
+            waiting: state.waiters.queue.len(),
+            ..state.metrics
+        }
+    }
+
+    /// Take `amount` units, waiting behind higher-priority and earlier
+    /// callers. Returns how long the call waited.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::ExecutionLimitExceeded`] if `amount` is negative,
+    /// not finite or larger than the bucket, or if the refill rate is not a
+    /// positive finite number, since the request could never be satisfied.
+    pub async fn acquire(&self, amount: f64, priority: i32) -> Result<Duration, AgentFlowError> {
+        let inner = &*self.inner;
+        let per_second = inner.limit.per_second;
+        if !amount.is_finite()
+            || amount < 0.0
+            || amount > inner.limit.burst
+            || !per_second.is_finite()
+            || per_second <= 0.0
+        {
+            return Err(AgentFlowError::ExecutionLimitExceeded(format!(
+                "Rate limiter '{}' cannot grant {} units (burst {}, {}/s)",
+                inner.name, amount, inner.limit.burst, inner.limit.per_second
+            )));
+        }
+        let started = Instant::now();
+        let mut ticket = Ticket {
+            state: &inner.state,
+            notify: &inner.notify,
+            ticket: lock(&inner.state).waiters.push(priority),
+            active: true,
+        };
+        loop {
+            let notified = inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let wait = {
+                let mut state = lock(&inner.state);
+                if state.waiters.is_head(&ticket.ticket) {
+                    state.refill(&inner.limit);
+                    if state.tokens >= amount {
+                        state.tokens -= amount;
+                        state.waiters.queue.remove(&ticket.ticket);
+                        ticket.active = false;
+                        let waited = started.elapsed();
+                        state.metrics.record(waited);
+                        drop(state);
+                        inner.notify.notify_waiters();
+                        debug!(limiter = %inner.name, amount, ?waited, "Rate limiter granted");
+                        return Ok(waited);
+                    }
+                    let deficit = amount - state.tokens;
+                    Some(
+                        Duration::try_from_secs_f64(deficit / per_second)
+                            .map_or(MAX_RATE_LIMIT_WAIT, |wait| wait.min(MAX_RATE_LIMIT_WAIT)),
+                    )
+                } else {
+                    None
+                }
+            };
+            match wait {
+                // Head of the queue: wait for the bucket, or for a
+                // higher-priority arrival to take our place.
+                Some(wait) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => {}
+                        _ = &mut notified => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+}
+
+/// A shared counting semaphore with priority ordering. See the
+/// [module docs](self).
+#[derive(Clone)]
+pub struct PrioritySemaphore {
+    inner: Arc<SemaphoreInner>,
+}
+
+struct SemaphoreInner {
+    name: String,
+    permits: usize,
+    state: Mutex<SemaphoreState>,
+    notify: Notify,
+}
+
+struct SemaphoreState {
+    available: usize,
+    waiters: Waiters,
+    metrics: ResourceMetrics,
+}
+
+impl HasWaiters for SemaphoreState {
+    fn waiters(&mut self) -> &mut Waiters {
+        &mut self.waiters
+    }
+}
+
+impl PrioritySemaphore {
+    /// A standalone semaphore with `permits` slots (at least 1). Prefer
+    /// [`ResourcePool::register_semaphore`] to share it.
+    pub fn new(name: &str, permits: usize) -> Self {
+        let permits = permits.max(1);
+        Self {
+            inner: Arc::new(SemaphoreInner {
+                name: name.to_string(),
+                permits,
+                state: Mutex::new(SemaphoreState {
+                    available: permits,
+                    waiters: Waiters::default(),
+                    metrics: ResourceMetrics::default(),
+                }),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// The semaphore's name.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Total number of slots.
+    pub fn permits(&self) -> usize {
+        self.inner.permits
+    }
+
+    /// Slots currently free.
+    pub fn available(&self) -> usize {
+        lock(&self.inner.state).available
+    }
+
+    /// Wait-time statistics.
+    pub fn metrics(&self) -> ResourceMetrics {
+        let state = lock(&self.inner.state);
+        ResourceMetrics {
+            waiting: state.waiters.queue.len(),
+            ..state.metrics
+        }
+    }
+
+    /// Wait for a slot behind higher-priority and earlier callers. The slot
+    /// is released when the returned permit is dropped.
+    pub async fn acquire(&self, priority: i32) -> PoolPermit {
+        let inner = &*self.inner;
+        let started = Instant::now();
+        let mut ticket = Ticket {
+            state: &inner.state,
+            notify: &inner.notify,
+            ticket: lock(&inner.state).waiters.push(priority),
+            active: true,
+        };
+        loop {
+            let notified = inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut state = lock(&inner.state);
+                if state.available > 0 && state.waiters.is_head(&ticket.ticket) {
+                    state.available -= 1;
+                    state.waiters.queue.remove(&ticket.ticket);
+                    ticket.active = false;
+                    let waited = started.elapsed();
+                    state.metrics.record(waited);
+                    drop(state);
+                    // The next waiter may be able to take another free slot.
+                    inner.notify.notify_waiters();
+                    debug!(semaphore = %inner.name, ?waited, "Semaphore granted");
+                    return PoolPermit {
+                        semaphore: self.clone(),
+                        waited,
+                    };
+                }
+            }
+            notified.await;
+        }
+    }
+}
+
+/// A slot of a [`PrioritySemaphore`], released on drop.
+pub struct PoolPermit {
+    semaphore: PrioritySemaphore,
+    waited: Duration,
+}
+
+impl PoolPermit {
+    /// How long the holder waited for this slot.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl Drop for PoolPermit {
+    fn drop(&mut self) {
+        let inner = &self.semaphore.inner;
+        lock(&inner.state).available += 1;
+        inner.notify.notify_waiters();
+    }
+}
+
+/// Wraps a node so it acquires shared resources before each call.
+///
+/// Rate limits are acquired first, then the semaphore slot, which is held for
+/// the duration of the call. Implements [`NodeResult`] (acquisition errors are
+/// returned) when `N` does, and [`Node`] over [`SharedStore`] (acquisition
+/// errors are written to `"error"` and the node is skipped) when `N` does.
+#[derive(Clone)]
+pub struct Throttled<N> {
+    node: N,
+    rate_limits: Vec<(RateLimiter, f64)>,
+    semaphore: Option<PrioritySemaphore>,
+    priority: i32,
+}
+
+impl<N> Throttled<N> {
+    /// Wrap `node` with no resources attached yet.
+    pub fn new(node: N) -> Self {
+        Self {
+            node,
+            rate_limits: Vec::new(),
+            semaphore: None,
+            priority: 0,
+        }
+    }
+
+    /// Take `amount` units from `limiter` before each call.
+    pub fn with_rate_limit(mut self, limiter: RateLimiter, amount: f64) -> Self {
+        self.rate_limits.push((limiter, amount));
+        self
+    }
+
+    /// Hold a slot of `semaphore` for the duration of each call.
+    pub fn with_semaphore(mut self, semaphore: PrioritySemaphore) -> Self {
+        self.semaphore = Some(semaphore);
+        self
+    }
+
+    /// Queue priority; higher is served first. Defaults to 0.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    async fn acquire(&self) -> Result<Option<PoolPermit>, AgentFlowError> {
+        for (limiter, amount) in &self.rate_limits {
+            limiter.acquire(*amount, self.priority).await?;
+        }
+        Ok(match &self.semaphore {
+            Some(semaphore) => Some(semaphore.acquire(self.priority).await),
+            None => None,
+        })
+    }
+}
+
+impl<N, I, O> NodeResult<I, O> for Throttled<N>
+where
+    N: NodeResult<I, O> + Clone,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    fn call(
+        &self,
+        input: I,
+    ) -> Pin<Box<dyn Future<Output = Result<O, AgentFlowError>> + Send + '_>> {
+        Box::pin(async move {
+            let _permit = self.acquire().await?;
+            self.node.call(input).await
+        })
+    }
+}
+
+impl<N> Node<SharedStore, SharedStore> for Throttled<N>
+where
+    N: Node<SharedStore, SharedStore> + Clone,
+{
+    fn call(&self, input: SharedStore) -> Pin<Box<dyn Future<Output = SharedStore> + Send + '_>> {
+        Box::pin(async move {
+            match self.acquire().await {
+                Ok(_permit) => self.node.call(input).await,
+                Err(e) => {
+                    warn!(error = %e.report(), "Throttled node could not acquire resources");
+                    input
+                        .write()
+                        .await
+                        .insert("error".to_string(), Value::String(e.report()));
+                    input
+                }
+            }
+        })
+    }
+}
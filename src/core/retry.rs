@@ -0,0 +1,297 @@
+//! Retry policies: how many attempts, how long to wait between them, and
+//! which failures are worth retrying.
+//!
+//! A [`RetryPolicy`] is accepted by [`Agent::with_policy`],
+//! [`create_retry_node_with_policy`] and
+//! [`create_corrective_retry_node_with_policy`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use agentflow::core::error::AgentFlowError;
+//! use agentflow::core::retry::RetryPolicy;
+//! use std::time::Duration;
+//!
+//! // Up to 5 attempts, 200 ms → 400 ms → 800 ms …, capped at 5 s, giving up
+//! // after 20 s in total. Rate-limit hints are honoured by default.
+//! let policy = RetryPolicy::exponential(5, Duration::from_millis(200), Duration::from_secs(5))
+//!     .with_max_elapsed(Duration::from_secs(20))
+//!     .retry_if(|e: &AgentFlowError| e.is_retryable());
+//! assert_eq!(policy.max_attempts, 5);
+//! ```
+//!
+//! [`Agent::with_policy`]: crate::patterns::agent::Agent::with_policy
+//! [`create_retry_node_with_policy`]: crate::core::node::create_retry_node_with_policy
+//! [`create_corrective_retry_node_with_policy`]: crate::utils::tool::create_corrective_retry_node_with_policy
+
+use crate::core::error::AgentFlowError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Decides whether a failed attempt should be retried.
+pub type RetryErrorPredicate = Arc<dyn Fn(&AgentFlowError) -> bool + Send + Sync>;
+
+/// Decides whether a successful attempt's output store should be rejected
+/// and the attempt retried.
+pub type RetryOutputPredicate = Arc<dyn Fn(&HashMap<String, Value>) -> bool + Send + Sync>;
+
+/// Delay schedule between attempts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// The same delay before every retry.
+    Fixed(Duration),
+    /// `initial`, `initial * factor`, `initial * factor²`, … capped at `max`.
+    Exponential {
+        /// Delay before the first retry.
+        initial: Duration,
+        /// Multiplier applied after each retry.
+        factor: f64,
+        /// Upper bound on any single delay.
+        max: Duration,
+    },
+    /// "Decorrelated jitter": each delay is random in
+    /// `[base, previous * 3]`, capped at `max`. Spreads out clients that
+    /// failed at the same moment so they do not retry in lockstep.
+    DecorrelatedJitter {
+        /// Minimum delay.
+        base: Duration,
+        /// Upper bound on any single delay.
+        max: Duration,
+    },
+}
+
+/// How an operation is retried.
+///
+/// - [`max_attempts`](Self::max_attempts) — total attempts, including the
+///   first. Constructors raise 0 to 1: the operation always runs at least
+///   once.
+/// - [`backoff`](Self::backoff) — delay schedule between attempts.
+/// - [`max_elapsed`](Self::max_elapsed) — no retry is started if its delay
+///   would end after this much time since the first attempt.
+/// - [`respect_retry_after`](Self::respect_retry_after) — wait at least the
+///   `retry_after` of [`AgentFlowError::RateLimited`] errors (on by default).
+/// - [`retry_if`](Self::retry_if) / [`retry_if_output`](Self::retry_if_output)
+///   — which errors, and which successful outputs, trigger a retry.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first.
+    pub max_attempts: usize,
+    /// Delay schedule between attempts.
+    pub backoff: Backoff,
+    /// Upper bound on the total time spent retrying.
+    pub max_elapsed: Option<Duration>,
+    /// Whether `RateLimited { retry_after }` hints extend the delay.
+    pub respect_retry_after: bool,
+    retry_on_error: Option<RetryErrorPredicate>,
+    retry_on_output: Option<RetryOutputPredicate>,
+}
+
+impl RetryPolicy {
+    /// `max_attempts` attempts with no delay between them.
+    pub fn new(max_attempts: usize) -> Self {
+        Self::fixed(max_attempts, Duration::ZERO)
+    }
+
+    /// `max_attempts` attempts, waiting `delay` between them.
+    pub fn fixed(max_attempts: usize, delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::Fixed(delay),
+            max_elapsed: None,
+            respect_retry_after: true,
+            retry_on_error: None,
+            retry_on_output: None,
+        }
+    }
+
+    /// `max_attempts` attempts with delays doubling from `initial` up to `max`.
+    pub fn exponential(max_attempts: usize, initial: Duration, max: Duration) -> Self {
+        Self::fixed(max_attempts, Duration::ZERO).with_backoff(Backoff::Exponential {
+            initial,
+            factor: 2.0,
+            max,
+        })
+    }
+
+    /// `max_attempts` attempts with [decorrelated jitter](Backoff::DecorrelatedJitter).
+    pub fn decorrelated_jitter(max_attempts: usize, base: Duration, max: Duration) -> Self {
+        Self::fixed(max_attempts, Duration::ZERO)
+            .with_backoff(Backoff::DecorrelatedJitter { base, max })
+    }
+
+    /// Replace the delay schedule.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Stop retrying once `max_elapsed` would be exceeded.
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Whether to wait for `RateLimited { retry_after }` hints.
+    pub fn with_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    /// Retry only errors for which `predicate` returns `true`.
+    ///
+    /// Without a predicate, each caller applies its own default (documented
+    /// on the caller).
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&AgentFlowError) -> bool + Send + Sync + 'static,
+    {
+        self.retry_on_error = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Also retry successful attempts whose output store matches `predicate`
+    /// (e.g. an LLM reply that failed validation).
+    pub fn retry_if_output<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&HashMap<String, Value>) -> bool + Send + Sync + 'static,
+    {
+        self.retry_on_output = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Whether `error` should be retried, falling back to `default` when no
+    /// [`retry_if`](Self::retry_if) predicate is set.
+    pub fn should_retry_error(&self, error: &AgentFlowError, default: bool) -> bool {
+        match &self.retry_on_error {
+            Some(predicate) => predicate(error),
+            None => default,
+        }
+    }
+
+    /// Whether `output` should be rejected and retried, falling back to
+    /// `default` when no [`retry_if_output`](Self::retry_if_output)
+    /// predicate is set.
+    pub fn should_retry_output(&self, output: &HashMap<String, Value>, default: bool) -> bool {
+        match &self.retry_on_output {
+            Some(predicate) => predicate(output),
+            None => default,
+        }
+    }
+
+    /// Start tracking one retried operation.
+    pub fn start(&self) -> RetryState<'_> {
+        RetryState {
+            policy: self,
+            attempts: 1,
+            started: Instant::now(),
+            previous: None,
+            rng: seed(),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// A single attempt.
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("max_elapsed", &self.max_elapsed)
+            .field("respect_retry_after", &self.respect_retry_after)
+            .field("retry_on_error", &self.retry_on_error.is_some())
+            .field("retry_on_output", &self.retry_on_output.is_some())
+            .finish()
+    }
+}
+
+/// Progress of one operation under a [`RetryPolicy`].
+///
+/// Created by [`RetryPolicy::start`] after the first attempt has been made.
+#[derive(Debug)]
+pub struct RetryState<'a> {
+    policy: &'a RetryPolicy,
+    attempts: usize,
+    started: Instant,
+    previous: Option<Duration>,
+    rng: u64,
+}
+
+impl RetryState<'_> {
+    /// Number of attempts made so far.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Delay before the next attempt, or `None` if the attempts or the
+    /// elapsed-time budget are used up. `error` is the failure that caused the
+    /// retry, used for its `retry_after` hint.
+    ///
+    /// Each `Some` counts as one more attempt.
+    pub fn next_delay(&mut self, error: Option<&AgentFlowError>) -> Option<Duration> {
+        if self.attempts >= self.policy.max_attempts {
+            return None;
+        }
+        let mut delay = match self.policy.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                let exp = i32::try_from(self.attempts - 1).unwrap_or(i32::MAX);
+                let secs = initial.as_secs_f64() * factor.max(1.0).powi(exp);
+                // Out-of-range or non-finite delays saturate to `max`;
+                // `Duration::from_secs_f64` would panic on them.
+                Duration::try_from_secs_f64(secs).map_or(max, |d| d.min(max))
+            }
+            Backoff::DecorrelatedJitter { base, max } => {
+                let upper = self.previous.unwrap_or(base).saturating_mul(3).max(base);
+                let span = (upper - base).as_nanos() as f64;
+                let fraction = (self.next_random() >> 11) as f64 / (1u64 << 53) as f64;
+                (base + Duration::from_nanos((span * fraction) as u64)).min(max)
+            }
+        };
+        if self.policy.respect_retry_after {
+            if let Some(hint) = error.and_then(AgentFlowError::retry_after) {
+                delay = delay.max(hint);
+            }
+        }
+        if let Some(max_elapsed) = self.policy.max_elapsed {
+            let ends = self.started.elapsed().checked_add(delay);
+            if ends.map_or(true, |end| end > max_elapsed) {
+                return None;
+            }
+        }
+        self.previous = Some(delay);
+        self.attempts += 1;
+        Some(delay)
+    }
+
+    /// xorshift64; quality is irrelevant, only spreading matters.
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+/// A non-zero seed that differs between calls and processes.
+fn seed() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    (nanos ^ count.wrapping_mul(0x9E37_79B9_7F4A_7C15)) | 1
+}
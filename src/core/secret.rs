@@ -0,0 +1,282 @@
+//! Secret values that stay out of logs, traces and persisted snapshots.
+//!
+//! API keys and user tokens often have to travel through the [`SharedStore`]
+//! so that the node making the call can read them. Stored as plain strings,
+//! they leak into every `{:?}` dump, hook and snapshot. This module stores
+//! them as a **marked** JSON value instead:
+//!
+//! ```json
+//! { "$secret": "sk-live-…" }
+//! ```
+//!
+//! - [`SecretString`] is the typed handle. Its `Debug`, `Display` and
+//!   `Serialize` impls all print [`REDACTED`]; only
+//!   [`expose_secret`](SecretString::expose_secret) returns the real value.
+//! - [`Store::set_secret`] / [`Store::get_secret`] write and read marked
+//!   values. Plain getters such as [`Store::get_string`] return `None` for
+//!   them, so a secret is only readable by a node that explicitly asks for it.
+//! - [`redact_value`] and [`redact_map`] replace marked values with
+//!   [`REDACTED`] and mask any copy of a secret embedded in another string.
+//!   [`Store::save_to`], store backends and the `Debug` impl of [`Store`] use
+//!   them, so secrets never reach disk or logs. A persisted secret is
+//!   therefore gone: after a reload the key holds the plain string
+//!   `"[REDACTED]"`, and [`Store::require_secret`] reports it as redacted.
+//!   Set secrets again from their source (environment, vault) on start-up.
+//! - [`redaction_hook`] is a flow middleware that masks secrets that a node
+//!   copied into other keys (e.g. an LLM echoing a token back).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::store::Store;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let store = Store::new();
+//!     store.set_secret("api_key", "sk-live-123").await;
+//!
+//!     let key = store.get_secret("api_key").await.unwrap();
+//!     println!("{key:?}");                 // [REDACTED]
+//!     let _header = format!("Bearer {}", key.expose_secret());
+//! }
+//! ```
+//!
+//! [`SharedStore`]: crate::core::node::SharedStore
+//! [`Store`]: crate::core::store::Store
+//! [`Store::set_secret`]: crate::core::store::Store::set_secret
+//! [`Store::get_secret`]: crate::core::store::Store::get_secret
+//! [`Store::get_string`]: crate::core::store::Store::get_string
+//! [`Store::require_secret`]: crate::core::store::Store::require_secret
+//! [`Store::save_to`]: crate::core::store::Store::save_to
+
+use crate::core::node::SharedStore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// JSON object key that marks a value as secret.
+pub const SECRET_MARKER: &str = "$secret";
+
+/// Placeholder written in place of a secret wherever it would be exposed.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A string whose value is never printed or serialized.
+///
+/// `Debug`, `Display` and `Serialize` all produce [`REDACTED`]. Deserializing
+/// accepts either a bare string or a marked `{"$secret": "…"}` object.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wrap a secret value.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Return the real value. Call this only at the point of use, e.g. when
+    /// building an `Authorization` header.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert into the marked JSON representation stored in a [`SharedStore`].
+    ///
+    /// [`SharedStore`]: crate::core::node::SharedStore
+    pub fn into_value(self) -> Value {
+        let mut obj = serde_json::Map::new();
+        obj.insert(SECRET_MARKER.to_string(), Value::String(self.0));
+        Value::Object(obj)
+    }
+
+    /// Read a marked JSON value. Returns `None` if `value` is not a secret.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        secret_str(value).map(Self::new)
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl std::fmt::Display for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        match &value {
+            Value::String(s) => Ok(Self::new(s.clone())),
+            other => Self::from_value(other)
+                .ok_or_else(|| serde::de::Error::custom("expected a string or a marked secret")),
+        }
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+/// Return `true` if `value` is a marked secret.
+pub fn is_secret(value: &Value) -> bool {
+    secret_str(value).is_some()
+}
+
+fn secret_str(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(obj) if obj.len() == 1 => obj.get(SECRET_MARKER).and_then(|v| v.as_str()),
+        _ => None,
+    }
+}
+
+/// Collect the plain-text value of every marked secret in `map`, including
+/// secrets nested inside arrays and objects.
+pub fn collect_secrets(map: &HashMap<String, Value>) -> Vec<String> {
+    fn walk(value: &Value, out: &mut Vec<String>) {
+        if let Some(s) = secret_str(value) {
+            if !s.is_empty() {
+                out.push(s.to_string());
+            }
+            return;
+        }
+        match value {
+            Value::Array(items) => items.iter().for_each(|v| walk(v, out)),
+            Value::Object(obj) => obj.values().for_each(|v| walk(v, out)),
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    map.values().for_each(|v| walk(v, &mut out));
+    // Mask longer secrets first so a secret that contains another is not
+    // left half-masked.
+    out.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    out.dedup();
+    out
+}
+
+/// Replace every occurrence of any of `secrets` inside `text` with [`REDACTED`].
+pub fn mask_secrets(text: &str, secrets: &[String]) -> String {
+    let mut masked = text.to_string();
+    for secret in secrets {
+        if masked.contains(secret.as_str()) {
+            masked = masked.replace(secret.as_str(), REDACTED);
+        }
+    }
+    masked
+}
+
+/// Return a copy of `value` with marked secrets replaced by [`REDACTED`] and
+/// any of `secrets` masked inside plain strings.
+pub fn redact_value(value: &Value, secrets: &[String]) -> Value {
+    if is_secret(value) {
+        return Value::String(REDACTED.to_string());
+    }
+    match value {
+        Value::String(s) => Value::String(mask_secrets(s, secrets)),
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| redact_value(v, secrets)).collect())
+        }
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), redact_value(v, secrets)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Like [`redact_value`], but leaves marked secrets — at any depth — intact.
+fn mask_leaks(value: &Value, secrets: &[String]) -> Value {
+    match value {
+        _ if is_secret(value) => value.clone(),
+        Value::String(s) => Value::String(mask_secrets(s, secrets)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| mask_leaks(v, secrets)).collect()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), mask_leaks(v, secrets)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Return a redacted copy of a whole store snapshot, safe to log, export or
+/// persist.
+pub fn redact_map(map: &HashMap<String, Value>) -> HashMap<String, Value> {
+    let secrets = collect_secrets(map);
+    map.iter()
+        .map(|(k, v)| (k.clone(), redact_value(v, &secrets)))
+        .collect()
+}
+
+/// Mask, **in place**, every secret value that has been copied into another
+/// (non-secret) value of the store. Marked secrets themselves, including ones
+/// nested inside objects and arrays, are untouched.
+///
+/// Returns the number of keys that were rewritten.
+pub async fn mask_leaked_secrets(store: &SharedStore) -> usize {
+    let mut guard = store.write().await;
+    let secrets = collect_secrets(&guard);
+    if secrets.is_empty() {
+        return 0;
+    }
+    let mut rewritten = 0;
+    for value in guard.values_mut() {
+        let redacted = mask_leaks(value, &secrets);
+        if redacted != *value {
+            *value = redacted;
+            rewritten += 1;
+        }
+    }
+    rewritten
+}
+
+/// Flow middleware that masks leaked secrets after every node.
+///
+/// Pass it to [`Flow::with_post_node_hook`]:
+///
+/// ```rust,no_run
+/// use agentflow::core::flow::Flow;
+/// use agentflow::core::secret::redaction_hook;
+///
+/// let flow = Flow::new().with_post_node_hook(redaction_hook());
+/// ```
+///
+/// [`Flow::with_post_node_hook`]: crate::core::flow::Flow::with_post_node_hook
+pub fn redaction_hook(
+) -> impl Fn(&str, SharedStore) -> Pin<Box<dyn Future<Output = SharedStore> + Send>>
+       + Send
+       + Sync
+       + 'static {
+    |node: &str, store: SharedStore| {
+        let node = node.to_string();
+        Box::pin(async move {
+            let rewritten = mask_leaked_secrets(&store).await;
+            if rewritten > 0 {
+                tracing::warn!(node = %node, keys = rewritten, "Masked secret values leaked into store");
+            }
+            store
+        })
+    }
+}
@@ -1,7 +1,16 @@
 use crate::core::error::AgentFlowError;
+use crate::core::limits::{LimitState, StoreLimits};
 use crate::core::node::SharedStore;
+use crate::core::persistence::{StoreBackend, StoreFormat, StoreOp};
+use crate::core::secret::{collect_secrets, redact_map, redact_value, SecretString, REDACTED};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
 
 /// Typed helper wrapper around a [`SharedStore`].
 ///
@@ -31,8 +40,29 @@ use std::collections::HashMap;
 ///     let name: String = store.require_string("name").await.unwrap();
 /// }
 /// ```
+///
+/// # Persistence
+///
+/// Snapshot the whole store with [`save_to`](Self::save_to) /
+/// [`load_from`](Self::load_from), or attach a
+/// [`StoreBackend`] with [`with_backend`](Self::with_backend) so every write
+/// is persisted as it happens. See [`crate::core::persistence`].
+///
+/// # Secrets
+///
+/// Values written with [`set_secret`](Self::set_secret) are readable only
+/// through [`get_secret`](Self::get_secret). They are redacted in the `Debug`
+/// output, in snapshots and in backend writes. See [`crate::core::secret`].
+///
+/// # Limits
+///
+/// Attach [`StoreLimits`] with [`with_limits`](Self::with_limits) to cap the
+/// store's size, expire keys and evict under pressure. See
+/// [`crate::core::limits`].
 pub struct Store {
     inner: SharedStore,
+    backend: Option<Arc<dyn StoreBackend>>,
+    limits: Option<Arc<LimitState>>,
 }
 
 impl Store {
@@ -40,12 +70,174 @@ impl Store {
     pub fn new() -> Self {
         Self {
             inner: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            backend: None,
+            limits: None,
         }
     }
 
     /// Wrap an existing [`SharedStore`] in a `Store`.
+    ///
+    /// The new handle has no backend and no [limits](Self::with_limits),
+    /// even if `store` came from a `Store` that had them.
     pub fn from_shared(store: SharedStore) -> Self {
-        Self { inner: store }
+        Self {
+            inner: store,
+            backend: None,
+            limits: None,
+        }
+    }
+
+    /// Create a store backed by `backend`, restoring whatever it last persisted.
+    ///
+    /// Every subsequent mutation made through this `Store` (and its clones) is
+    /// forwarded to the backend. Backend write failures are logged with
+    /// `tracing::warn!` and do not abort the in-memory write.
+    ///
+    /// # Errors
+    ///
+    /// Returns the backend's error if the persisted state cannot be loaded.
+    pub async fn with_backend(
+        backend: impl StoreBackend + 'static,
+    ) -> Result<Self, AgentFlowError> {
+        let data = backend.load().await?;
+        Ok(Self {
+            inner: std::sync::Arc::new(tokio::sync::RwLock::new(data)),
+            backend: Some(Arc::new(backend)),
+            limits: None,
+        })
+    }
+
+    /// Write a snapshot of the store to `path`.
+    ///
+    /// The format is chosen from the extension with [`StoreFormat::from_path`]:
+    /// `.bin` / `.msgpack` / `.mpk` are written as MessagePack, anything else
+    /// as JSON. Use [`save_as`](Self::save_as) to pick the format explicitly.
+    pub async fn save_to(&self, path: impl AsRef<Path>) -> Result<(), AgentFlowError> {
+        let format = StoreFormat::from_path(&path);
+        self.save_as(path, format).await
+    }
+
+    /// Write a snapshot of the store to `path` in the given `format`.
+    ///
+    /// Secrets are redacted: they are reloaded as the plain string
+    /// `"[REDACTED]"`, not as secrets. See [`crate::core::secret`].
+    pub async fn save_as(
+        &self,
+        path: impl AsRef<Path>,
+        format: StoreFormat,
+    ) -> Result<(), AgentFlowError> {
+        let bytes = {
+            let guard = self.inner.read().await;
+            format.encode(&redact_map(&guard))?
+        };
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+
+    /// Load a store from a snapshot written by [`save_to`](Self::save_to).
+    ///
+    /// The format is chosen from the extension, as for `save_to`.
+    pub async fn load_from(path: impl AsRef<Path>) -> Result<Self, AgentFlowError> {
+        let format = StoreFormat::from_path(&path);
+        Self::load_as(path, format).await
+    }
+
+    /// Load a store from a snapshot at `path` encoded in `format`.
+    pub async fn load_as(
+        path: impl AsRef<Path>,
+        format: StoreFormat,
+    ) -> Result<Self, AgentFlowError> {
+        let bytes = tokio::fs::read(path).await?;
+        let data = format.decode(&bytes)?;
+        Ok(Self::from_shared(std::sync::Arc::new(
+            tokio::sync::RwLock::new(data),
+        )))
+    }
+
+    /// Rewrite the backend's persisted state as a (redacted) snapshot of the
+    /// current contents. Does nothing if no backend is attached.
+    ///
+    /// Call this after writing through the raw [`SharedStore`] (which
+    /// bypasses the backend), or periodically to shrink an append-only log.
+    pub async fn compact(&self) -> Result<(), AgentFlowError> {
+        if let Some(backend) = &self.backend {
+            let snapshot = redact_map(&*self.inner.read().await);
+            backend.compact(snapshot).await?;
+        }
+        Ok(())
+    }
+
+    /// Apply capacity limits, TTLs and eviction to this store and its clones.
+    ///
+    /// Existing contents are counted immediately but not evicted until the
+    /// next write or [`enforce_limits`](Self::enforce_limits) call. If another
+    /// task holds the write lock at this moment, existing contents are only
+    /// counted from the next `enforce_limits` call.
+    pub fn with_limits(mut self, limits: StoreLimits) -> Self {
+        let state = match self.inner.try_read() {
+            Ok(guard) => LimitState::new(limits, &guard),
+            Err(_) => LimitState::new(limits, &HashMap::new()),
+        };
+        self.limits = Some(Arc::new(state));
+        self
+    }
+
+    /// Purge expired keys and evict down to `max_total_bytes`, re-measuring
+    /// every entry so that writes made through the raw [`SharedStore`] are
+    /// accounted for. Returns the removed keys. Does nothing without limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(AgentFlowError::ExecutionLimitExceeded)` if the store is
+    /// over its limit and only protected keys remain.
+    pub async fn enforce_limits(&self) -> Result<Vec<String>, AgentFlowError> {
+        let Some(limits) = &self.limits else {
+            return Ok(Vec::new());
+        };
+        let mut guard = self.inner.write().await;
+        let removed = limits.enforce(&mut guard)?;
+        for key in &removed {
+            self.persist(StoreOp::Remove { key: key.clone() }).await;
+        }
+        Ok(removed)
+    }
+
+    /// Estimated size of the store in bytes, as tracked by its limits.
+    /// Returns `None` if no limits are attached.
+    pub fn total_bytes(&self) -> Option<usize> {
+        self.limits.as_ref().map(|l| l.total_bytes())
+    }
+
+    /// Expire `key` if its TTL has elapsed, otherwise record the access for LRU.
+    async fn on_access(&self, key: &str) {
+        let Some(limits) = &self.limits else {
+            return;
+        };
+        if limits.is_expired(key) {
+            self.purge_expired().await;
+        } else {
+            limits.touch(key);
+        }
+    }
+
+    async fn purge_expired(&self) {
+        let Some(limits) = &self.limits else {
+            return;
+        };
+        let mut guard = self.inner.write().await;
+        for key in limits.purge_expired(&mut guard) {
+            self.persist(StoreOp::Remove { key }).await;
+        }
+    }
+
+    /// Forward `op` to the backend, if any. Called while the write guard is
+    /// still held so the log order matches the in-memory order.
+    async fn persist(&self, op: StoreOp) {
+        if let Some(backend) = &self.backend {
+            if let Err(e) = backend.record(op).await {
+                warn!(error = %e, "Store backend failed to persist write");
+            }
+        }
     }
 
     /// Consume `self` and return the underlying [`SharedStore`].
@@ -60,6 +252,7 @@ impl Store {
 
     /// Get the value at `key` as a `String`, or `None` if absent or not a string.
     pub async fn get_string(&self, key: &str) -> Option<String> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard
             .get(key)
@@ -69,38 +262,40 @@ impl Store {
 
     /// Get the value at `key` as an `i64`, or `None` if absent or not an integer.
     pub async fn get_i64(&self, key: &str) -> Option<i64> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard.get(key).and_then(|v| v.as_i64())
     }
 
     /// Get the value at `key` as an `f64`, or `None` if absent or not a float.
     pub async fn get_f64(&self, key: &str) -> Option<f64> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard.get(key).and_then(|v| v.as_f64())
     }
 
     /// Get the value at `key` as a `bool`, or `None` if absent or not a boolean.
     pub async fn get_bool(&self, key: &str) -> Option<bool> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard.get(key).and_then(|v| v.as_bool())
     }
 
     /// Get a clone of the raw [`Value`] at `key`, or `None` if absent.
     pub async fn get(&self, key: &str) -> Option<Value> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard.get(key).cloned()
     }
 
     /// Insert a string value.
     pub async fn set_string(&self, key: impl Into<String>, value: impl Into<String>) {
-        let mut guard = self.inner.write().await;
-        guard.insert(key.into(), Value::String(value.into()));
+        self.set(key, Value::String(value.into())).await;
     }
 
     /// Insert an integer value.
     pub async fn set_i64(&self, key: impl Into<String>, value: i64) {
-        let mut guard = self.inner.write().await;
-        guard.insert(key.into(), Value::Number(value.into()));
+        self.set(key, Value::Number(value.into())).await;
     }
 
     /// Insert a float value.
@@ -133,26 +328,124 @@ impl Store {
     /// entirely and using [`Store::get_f64`] returning `None` as the signal,
     /// or store the value as a string (`"NaN"`) and parse it yourself.
     pub async fn set_f64(&self, key: impl Into<String>, value: f64) {
-        let mut guard = self.inner.write().await;
         if let Some(num) = serde_json::Number::from_f64(value) {
-            guard.insert(key.into(), Value::Number(num));
+            self.set(key, Value::Number(num)).await;
         }
     }
 
     /// Insert a boolean value.
     pub async fn set_bool(&self, key: impl Into<String>, value: bool) {
-        let mut guard = self.inner.write().await;
-        guard.insert(key.into(), Value::Bool(value));
+        self.set(key, Value::Bool(value)).await;
     }
 
     /// Insert a raw [`Value`].
+    ///
+    /// If the store has [limits](Self::with_limits) and the write is rejected,
+    /// the value is dropped and a warning is logged. Use
+    /// [`try_set`](Self::try_set) to observe the rejection.
     pub async fn set(&self, key: impl Into<String>, value: Value) {
+        let key = key.into();
+        if let Err(e) = self.insert(key.clone(), value, None).await {
+            warn!(key = %key, error = %e, "Store rejected write");
+        }
+    }
+
+    /// Insert a raw [`Value`], returning an error if the store's limits
+    /// reject it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(AgentFlowError::ExecutionLimitExceeded)` if the value is
+    /// larger than `max_key_bytes`, or if making room would require evicting a
+    /// protected key.
+    pub async fn try_set(
+        &self,
+        key: impl Into<String>,
+        value: Value,
+    ) -> Result<(), AgentFlowError> {
+        self.insert(key.into(), value, None).await
+    }
+
+    /// Insert a raw [`Value`] that expires after `ttl`, overriding the
+    /// default TTL. Without limits attached the TTL is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_set`](Self::try_set).
+    pub async fn set_with_ttl(
+        &self,
+        key: impl Into<String>,
+        value: Value,
+        ttl: Duration,
+    ) -> Result<(), AgentFlowError> {
+        self.insert(key.into(), value, Some(ttl)).await
+    }
+
+    async fn insert(
+        &self,
+        key: String,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), AgentFlowError> {
         let mut guard = self.inner.write().await;
-        guard.insert(key.into(), value);
+        let evicted = match &self.limits {
+            Some(limits) => limits.admit(&mut guard, &key, &value, ttl)?,
+            None => Vec::new(),
+        };
+        guard.insert(key.clone(), value.clone());
+        if self.backend.is_some() {
+            for evicted_key in evicted {
+                self.persist(StoreOp::Remove { key: evicted_key }).await;
+            }
+            let value = redact_value(&value, &collect_secrets(&guard));
+            self.persist(StoreOp::Set { key, value }).await;
+        }
+        Ok(())
+    }
+
+    /// Insert a secret value under `key`.
+    ///
+    /// The value is stored as a marked object (see [`crate::core::secret`]),
+    /// so plain getters like [`get_string`](Self::get_string) return `None`
+    /// for it and it is redacted wherever the store is printed or persisted.
+    /// Persisted copies cannot be turned back into the secret; set it again
+    /// after reloading.
+    pub async fn set_secret(&self, key: impl Into<String>, value: impl Into<SecretString>) {
+        self.set(key, value.into().into_value()).await;
+    }
+
+    /// Get the secret at `key`, or `None` if absent or not a secret.
+    pub async fn get_secret(&self, key: &str) -> Option<SecretString> {
+        self.on_access(key).await;
+        let guard = self.inner.read().await;
+        guard.get(key).and_then(SecretString::from_value)
+    }
+
+    /// Get the secret at `key`, or:
+    /// - `Err(AgentFlowError::NotFound)` if the key is absent.
+    /// - `Err(AgentFlowError::TypeMismatch)` if the value is not a secret,
+    ///   including a secret that was redacted when persisted and reloaded.
+    pub async fn require_secret(&self, key: &str) -> Result<SecretString, AgentFlowError> {
+        self.on_access(key).await;
+        let guard = self.inner.read().await;
+        match guard.get(key) {
+            None => Err(AgentFlowError::NotFound(format!(
+                "Required secret key '{}' not found",
+                key
+            ))),
+            Some(Value::String(s)) if s == REDACTED => Err(AgentFlowError::TypeMismatch(format!(
+                "Key '{}' holds a secret that was redacted when persisted; set it again after loading",
+                key
+            ))),
+            Some(v) => SecretString::from_value(v).ok_or_else(|| {
+                AgentFlowError::TypeMismatch(format!("Key '{}' is not a secret", key))
+            }),
+        }
     }
 
     /// Get the raw [`Value`] at `key`, or `Err(AgentFlowError::NotFound)` if absent.
     pub async fn require(&self, key: &str) -> Result<Value, AgentFlowError> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard
             .get(key)
@@ -164,6 +457,7 @@ impl Store {
     /// - `Err(AgentFlowError::NotFound)` if the key is absent.
     /// - `Err(AgentFlowError::TypeMismatch)` if the value is not a string.
     pub async fn require_string(&self, key: &str) -> Result<String, AgentFlowError> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         match guard.get(key) {
             None => Err(AgentFlowError::NotFound(format!(
@@ -180,6 +474,7 @@ impl Store {
     /// - `Err(AgentFlowError::NotFound)` if the key is absent.
     /// - `Err(AgentFlowError::TypeMismatch)` if the value is not an integer.
     pub async fn require_i64(&self, key: &str) -> Result<i64, AgentFlowError> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         match guard.get(key) {
             None => Err(AgentFlowError::NotFound(format!(
@@ -196,6 +491,7 @@ impl Store {
     /// - `Err(AgentFlowError::NotFound)` if the key is absent.
     /// - `Err(AgentFlowError::TypeMismatch)` if the value is not a float.
     pub async fn require_f64(&self, key: &str) -> Result<f64, AgentFlowError> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         match guard.get(key) {
             None => Err(AgentFlowError::NotFound(format!(
@@ -212,6 +508,7 @@ impl Store {
     /// - `Err(AgentFlowError::NotFound)` if the key is absent.
     /// - `Err(AgentFlowError::TypeMismatch)` if the value is not a boolean.
     pub async fn require_bool(&self, key: &str) -> Result<bool, AgentFlowError> {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         match guard.get(key) {
             None => Err(AgentFlowError::NotFound(format!(
@@ -226,6 +523,7 @@ impl Store {
 
     /// Returns `true` if the store contains `key`.
     pub async fn contains_key(&self, key: &str) -> bool {
+        self.on_access(key).await;
         let guard = self.inner.read().await;
         guard.contains_key(key)
     }
@@ -233,29 +531,46 @@ impl Store {
     /// Remove `key` from the store, returning its value if it was present.
     pub async fn remove(&self, key: &str) -> Option<Value> {
         let mut guard = self.inner.write().await;
-        guard.remove(key)
+        let removed = guard.remove(key);
+        if let Some(limits) = &self.limits {
+            limits.forget(key);
+        }
+        if removed.is_some() {
+            self.persist(StoreOp::Remove {
+                key: key.to_string(),
+            })
+            .await;
+        }
+        removed
     }
 
     /// Remove all entries from the store.
     pub async fn clear(&self) {
         let mut guard = self.inner.write().await;
         guard.clear();
+        if let Some(limits) = &self.limits {
+            limits.forget_all();
+        }
+        self.persist(StoreOp::Clear).await;
     }
 
     /// Return all keys currently in the store.
     pub async fn keys(&self) -> Vec<String> {
+        self.purge_expired().await;
         let guard = self.inner.read().await;
         guard.keys().cloned().collect()
     }
 
     /// Return the number of entries in the store.
     pub async fn len(&self) -> usize {
+        self.purge_expired().await;
         let guard = self.inner.read().await;
         guard.len()
     }
 
     /// Return `true` if the store contains no entries.
     pub async fn is_empty(&self) -> bool {
+        self.purge_expired().await;
         let guard = self.inner.read().await;
         guard.is_empty()
     }
@@ -267,12 +582,108 @@ impl Default for Store {
     }
 }
 
+impl std::fmt::Debug for Store {
+    /// Prints a redacted snapshot of the contents. If the store is currently
+    /// write-locked, prints `<locked>` instead of waiting.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut dbg = f.debug_struct("Store");
+        match self.inner.try_read() {
+            Ok(guard) => dbg.field("data", &redact_map(&guard)),
+            Err(_) => dbg.field("data", &"<locked>"),
+        };
+        dbg.field("persistent", &self.backend.is_some()).finish()
+    }
+}
+
 impl Clone for Store {
     /// Cloning a `Store` clones the `Arc` — both instances share the same
-    /// underlying data.
+    /// underlying data and the same backend, if any.
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            backend: self.backend.clone(),
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+/// Read `store[key]` as a `T`. An absent or `null` key is `Ok(None)`.
+///
+/// The typed records that patterns keep under reserved keys (the
+/// conversation, a plan, a handoff, …) are loaded through this.
+///
+/// # Errors
+///
+/// [`AgentFlowError::TypeMismatch`] if the key holds something that does not
+/// deserialize as `T`.
+pub async fn load_json<T: DeserializeOwned>(
+    store: &SharedStore,
+    key: &str,
+) -> Result<Option<T>, AgentFlowError> {
+    let value = store.read().await.get(key).cloned();
+    parse_json(key, value)
+}
+
+/// Like [`load_json`], but also removes the key.
+///
+/// # Errors
+///
+/// [`AgentFlowError::TypeMismatch`] if the key held something that does not
+/// deserialize as `T`. The key is removed either way.
+pub async fn take_json<T: DeserializeOwned>(
+    store: &SharedStore,
+    key: &str,
+) -> Result<Option<T>, AgentFlowError> {
+    let value = store.write().await.remove(key);
+    parse_json(key, value)
+}
+
+/// Serialize `value` into `store[key]`.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be serialized.
+pub async fn save_json<T: Serialize + ?Sized>(
+    store: &SharedStore,
+    key: &str,
+    value: &T,
+) -> Result<(), AgentFlowError> {
+    let value = serde_json::to_value(value)?;
+    store.write().await.insert(key.to_string(), value);
+    Ok(())
+}
+
+fn parse_json<T: DeserializeOwned>(
+    key: &str,
+    value: Option<Value>,
+) -> Result<Option<T>, AgentFlowError> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value).map(Some).map_err(|e| {
+            AgentFlowError::TypeMismatch(format!(
+                "Store key '{}' does not hold a valid {}: {}",
+                key,
+                short_type_name::<T>(),
+                e
+            ))
+        }),
+    }
+}
+
+/// `T`'s name without module paths, e.g. `Vec<Violation>`.
+fn short_type_name<T: ?Sized>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut short = String::with_capacity(full.len());
+    let mut segment = String::new();
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            short.push(c);
         }
     }
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
 }
@@ -0,0 +1,9 @@
+use std::sync::{Mutex, MutexGuard};
+
+/// Lock `mutex`, recovering the guard if a previous holder panicked.
+///
+/// The data behind this crate's mutexes (counters, logs, queues) stays
+/// consistent across a panic, so poisoning is not treated as an error.
+pub(crate) fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
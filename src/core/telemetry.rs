@@ -1,6 +1,128 @@
+use crate::core::sync::lock;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+/// Reserved [`SharedStore`] key where untyped nodes report LLM usage.
+///
+/// The value is one [`LlmUsage`] object or an array of them; use
+/// [`record_llm_usage`] to append to it. A [`Flow`] attributes the entries to
+/// the node that reported them, checks its [`Budget`] if it has one, and
+/// leaves all of the run's entries under the key when it returns.
+///
+/// [`SharedStore`]: crate::core::node::SharedStore
+/// [`Flow`]: crate::core::flow::Flow
+/// [`Budget`]: crate::core::budget::Budget
+pub const LLM_USAGE_KEY: &str = "llm_usage";
+
+/// Token counts reported for one LLM call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmUsage {
+    /// Model name, as used in the [`PriceTable`](crate::core::budget::PriceTable).
+    pub model: String,
+    /// Prompt tokens.
+    #[serde(default)]
+    pub input_tokens: usize,
+    /// Completion tokens.
+    #[serde(default)]
+    pub output_tokens: usize,
+}
+
+impl LlmUsage {
+    /// Usage of one call to `model`.
+    pub fn new(model: impl Into<String>, input_tokens: usize, output_tokens: usize) -> Self {
+        Self {
+            model: model.into(),
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    /// Input plus output tokens.
+    pub fn total_tokens(&self) -> usize {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Accumulated LLM usage for one node or one model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Number of LLM calls.
+    pub calls: usize,
+    /// Prompt tokens.
+    pub input_tokens: usize,
+    /// Completion tokens.
+    pub output_tokens: usize,
+    /// Cost in the price table's currency. `0.0` for calls made without a
+    /// price table.
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    /// Input plus output tokens.
+    pub fn total_tokens(&self) -> usize {
+        self.input_tokens + self.output_tokens
+    }
+
+    fn add(&mut self, other: &UsageTotals) {
+        self.calls += other.calls;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost += other.cost;
+    }
+}
+
+/// Append `usage` to the [`LLM_USAGE_KEY`] of an untyped store.
+pub async fn record_llm_usage(store: &crate::core::node::SharedStore, usage: LlmUsage) {
+    let value = match serde_json::to_value(&usage) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    let mut guard = store.write().await;
+    match guard.get_mut(LLM_USAGE_KEY) {
+        Some(serde_json::Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let previous = existing.take();
+            *existing = serde_json::Value::Array(vec![previous, value]);
+        }
+        None => {
+            guard.insert(LLM_USAGE_KEY.to_string(), value);
+        }
+    }
+}
+
+/// The entries of an [`LLM_USAGE_KEY`] value, which holds one object or an
+/// array of them.
+pub(crate) fn usage_entries(value: Option<serde_json::Value>) -> Vec<serde_json::Value> {
+    match value {
+        None => Vec::new(),
+        Some(serde_json::Value::Array(items)) => items,
+        Some(single) => vec![single],
+    }
+}
+
+/// Remove the [`LLM_USAGE_KEY`] of an untyped store and return the calls it
+/// held. Malformed entries are skipped.
+pub async fn take_llm_usage(store: &crate::core::node::SharedStore) -> Vec<LlmUsage> {
+    let value = store.write().await.remove(LLM_USAGE_KEY);
+    usage_entries(value)
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value(entry).ok())
+        .collect()
+}
+
+/// Move the calls recorded in `from` to the [`LLM_USAGE_KEY`] of `to`, e.g.
+/// from a sub-store back to the store of the node that created it.
+pub(crate) async fn forward_llm_usage(
+    from: &crate::core::node::SharedStore,
+    to: &crate::core::node::SharedStore,
+) {
+    for usage in take_llm_usage(from).await {
+        record_llm_usage(to, usage).await;
+    }
+}
+
 /// A context object that flows through nodes to accumulate telemetry metrics.
 ///
 /// It tracks LLM token usage, total execution time, and individual node execution latencies.
@@ -12,6 +134,17 @@ pub struct FlowContext {
     pub start_time: Instant,
     /// Execution duration broken down by node name.
     pub node_durations: HashMap<String, Duration>,
+    /// LLM usage broken down by the node that made the calls.
+    pub usage_by_node: HashMap<String, UsageTotals>,
+    /// LLM usage broken down by model.
+    pub usage_by_model: HashMap<String, UsageTotals>,
+    /// Calls recorded by the running node, not yet attributed to it.
+    pending_calls: Vec<LlmUsage>,
+    /// Extra copies of every recorded call, shared by clones, so that the
+    /// spend of an attempt whose store is dropped can still be recovered.
+    /// One log per open [`start_call_log`](Self::start_call_log), innermost
+    /// last.
+    call_logs: Vec<Arc<Mutex<Vec<LlmUsage>>>>,
 }
 
 impl Default for FlowContext {
@@ -27,14 +160,90 @@ impl FlowContext {
             token_usage: 0,
             start_time: Instant::now(),
             node_durations: HashMap::new(),
+            usage_by_node: HashMap::new(),
+            usage_by_model: HashMap::new(),
+            pending_calls: Vec::new(),
+            call_logs: Vec::new(),
         }
     }
 
-    /// Add to the total token count.
+    /// Add to the total token count, which a [`Budget`]'s token limit is
+    /// checked against.
+    ///
+    /// [`Budget`]: crate::core::budget::Budget
     pub fn add_tokens(&mut self, tokens: usize) {
         self.token_usage += tokens;
     }
 
+    /// Record one LLM call made by the running node.
+    ///
+    /// The tokens count towards [`token_usage`](Self::token_usage) at once.
+    /// When the node finishes, [`TypedFlow`] attributes the call to it, prices
+    /// it and checks the flow's [`Budget`].
+    ///
+    /// [`TypedFlow`]: crate::core::typed_flow::TypedFlow
+    /// [`Budget`]: crate::core::budget::Budget
+    pub fn record_llm_call(&mut self, usage: LlmUsage) {
+        self.token_usage += usage.total_tokens();
+        for log in &self.call_logs {
+            lock(log).push(usage.clone());
+        }
+        self.pending_calls.push(usage);
+    }
+
+    /// Record a call copied from a log of a clone of this context. It is
+    /// not copied to this context's logs, which hold it already.
+    pub(crate) fn recover_llm_call(&mut self, usage: LlmUsage) {
+        self.token_usage += usage.total_tokens();
+        self.pending_calls.push(usage);
+    }
+
+    /// Total LLM calls attributed to nodes.
+    pub fn total_calls(&self) -> usize {
+        self.usage_by_node.values().map(|u| u.calls).sum()
+    }
+
+    /// Total cost of the LLM calls attributed to nodes.
+    pub fn total_cost(&self) -> f64 {
+        self.usage_by_node.values().map(|u| u.cost).sum()
+    }
+
+    /// Start copying every call recorded from now on — by this context or
+    /// any clone of it — into the returned log. Logs nest: an outer log also
+    /// receives the calls copied to the logs started inside it.
+    pub(crate) fn start_call_log(&mut self) -> Arc<Mutex<Vec<LlmUsage>>> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        self.call_logs.push(log.clone());
+        log
+    }
+
+    /// Stop the log started last by [`start_call_log`](Self::start_call_log).
+    pub(crate) fn stop_call_log(&mut self) {
+        self.call_logs.pop();
+    }
+
+    pub(crate) fn take_pending_calls(&mut self) -> Vec<LlmUsage> {
+        std::mem::take(&mut self.pending_calls)
+    }
+
+    /// Attribute a call that has already been counted in `token_usage`.
+    pub(crate) fn attribute_call(&mut self, node: &str, usage: &LlmUsage, cost: f64) {
+        let call = UsageTotals {
+            calls: 1,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost,
+        };
+        self.usage_by_node
+            .entry(node.to_string())
+            .or_default()
+            .add(&call);
+        self.usage_by_model
+            .entry(usage.model.clone())
+            .or_default()
+            .add(&call);
+    }
+
     /// Record the duration for a specific node's execution.
     pub fn record_node_duration(&mut self, node_name: &str, duration: Duration) {
         let entry = self
@@ -44,6 +253,34 @@ impl FlowContext {
         *entry += duration;
     }
 
+    /// Fold another context's counters into this one: token usage is added
+    /// and per-node durations and LLM usage are summed. `start_time` is left
+    /// unchanged.
+    ///
+    /// Used by [`TypedParallel`] to roll branch telemetry up into the parent.
+    ///
+    /// [`TypedParallel`]: crate::core::typed_parallel::TypedParallel
+    pub fn merge(&mut self, other: &FlowContext) {
+        self.token_usage += other.token_usage;
+        for (node, duration) in &other.node_durations {
+            self.record_node_duration(node, *duration);
+        }
+        for (node, usage) in &other.usage_by_node {
+            self.usage_by_node
+                .entry(node.clone())
+                .or_default()
+                .add(usage);
+        }
+        for (model, usage) in &other.usage_by_model {
+            self.usage_by_model
+                .entry(model.clone())
+                .or_default()
+                .add(usage);
+        }
+        self.pending_calls
+            .extend(other.pending_calls.iter().cloned());
+    }
+
     /// Get the total elapsed time since the context was created.
     pub fn total_elapsed(&self) -> Duration {
         self.start_time.elapsed()
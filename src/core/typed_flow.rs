@@ -1,4 +1,8 @@
-use crate::core::error::AgentFlowError;
+use crate::core::budget::{self, Budget};
+use crate::core::error::{AgentFlowError, ErrorContext};
+use crate::core::flow::validate_graph;
+use crate::core::retry::RetryPolicy;
+use crate::core::sync::lock;
 use crate::core::typed_store::TypedStore;
 use dyn_clone::DynClone;
 use std::collections::HashMap;
@@ -52,6 +56,185 @@ where
     Box::new(FuncNode(func, std::marker::PhantomData))
 }
 
+/// An action enum used to route a [`TypedFlow`], with stable string names.
+///
+/// Usually derived with `#[derive(FlowAction)]` from `agentflow-macros`,
+/// which maps each unit variant to its `snake_case` name (override with
+/// `#[action(rename = "...")]`). The names are what a typed flow reports when
+/// it is bridged into a string-routed [`Flow`].
+///
+/// ```rust
+/// use agentflow::FlowAction;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
+/// enum Action {
+///     Review,
+///     #[action(rename = "redo")]
+///     Revise,
+/// }
+///
+/// assert_eq!(Action::Review.name(), "review");
+/// assert_eq!(Action::from_name("redo"), Some(Action::Revise));
+/// assert_eq!(Action::all().len(), 2);
+/// ```
+///
+/// [`Flow`]: crate::core::flow::Flow
+pub trait FlowAction: std::hash::Hash + Eq + Clone + Send + Sync + Sized + 'static {
+    /// Stable name of this action.
+    fn name(&self) -> &'static str;
+
+    /// Parse an action from its [`name`](Self::name).
+    fn from_name(name: &str) -> Option<Self>;
+
+    /// Every action, in declaration order.
+    fn all() -> Vec<Self>;
+}
+
+/// Fallible counterpart of [`TypedNode`], mirroring [`NodeResult`] in the
+/// untyped API.
+///
+/// Register it with [`TypedFlow::add_result_node`]. An `Err` is routed along
+/// the node's error edge (see [`TypedFlow::add_error_edge`]) or ends the run.
+///
+/// [`NodeResult`]: crate::core::node::NodeResult
+pub trait TypedNodeResult<T, E>: Send + Sync + DynClone {
+    /// Consume `input` and return the mutated store plus an optional
+    /// transition action, or an error.
+    fn call(&self, input: TypedStore<T>) -> TypedResultNodeFuture<'_, T, E>;
+}
+dyn_clone::clone_trait_object!(<T, E> TypedNodeResult<T, E>);
+
+/// Boxed future returned by [`TypedNodeResult::call`].
+pub type TypedResultNodeFuture<'a, T, E> =
+    Pin<Box<dyn Future<Output = Result<(TypedStore<T>, Option<E>), AgentFlowError>> + Send + 'a>>;
+
+/// Boxed, type-erased [`TypedNodeResult`] used in a [`TypedFlow`].
+pub type TypedResultNode<T, E> = Box<dyn TypedNodeResult<T, E>>;
+
+/// Blanket implementation so a [`TypedResultNode`] is itself a [`TypedNodeResult`].
+impl<T, E> TypedNodeResult<T, E> for Box<dyn TypedNodeResult<T, E>> {
+    fn call(&self, input: TypedStore<T>) -> TypedResultNodeFuture<'_, T, E> {
+        (**self).call(input)
+    }
+}
+
+/// Helper to create a [`TypedResultNode`] from a fallible async function.
+pub fn create_typed_result_node<T, E, F, Fut>(func: F) -> TypedResultNode<T, E>
+where
+    T: Send + Sync + 'static,
+    E: Send + Sync + Clone + 'static,
+    F: Fn(TypedStore<T>) -> Fut + Send + Sync + Clone + 'static,
+    Fut: Future<Output = Result<(TypedStore<T>, Option<E>), AgentFlowError>> + Send + 'static,
+{
+    struct ResultFuncNode<T, E, F>(F, std::marker::PhantomData<(T, E)>);
+
+    impl<T, E, F: Clone> Clone for ResultFuncNode<T, E, F> {
+        fn clone(&self) -> Self {
+            ResultFuncNode(self.0.clone(), std::marker::PhantomData)
+        }
+    }
+
+    impl<T, E, F, Fut> TypedNodeResult<T, E> for ResultFuncNode<T, E, F>
+    where
+        T: Send + Sync + 'static,
+        E: Send + Sync + Clone + 'static,
+        F: Fn(TypedStore<T>) -> Fut + Send + Sync + Clone,
+        Fut: Future<Output = Result<(TypedStore<T>, Option<E>), AgentFlowError>> + Send + 'static,
+    {
+        fn call(&self, input: TypedStore<T>) -> TypedResultNodeFuture<'_, T, E> {
+            Box::pin(self.0(input))
+        }
+    }
+
+    Box::new(ResultFuncNode(func, std::marker::PhantomData))
+}
+
+/// Future returned by a registered result node: the store to continue with
+/// (the last good snapshot on failure) and the outcome.
+type GuardedFuture<'a, T, E> =
+    Pin<Box<dyn Future<Output = (TypedStore<T>, Result<Option<E>, AgentFlowError>)> + Send + 'a>>;
+
+/// A [`TypedResultNode`] wrapped with a state snapshot and retry policy, so
+/// that the flow itself never needs `T: Clone`.
+trait GuardedResultNode<T, E>: Send + Sync + DynClone {
+    fn call(&self, input: TypedStore<T>) -> GuardedFuture<'_, T, E>;
+}
+dyn_clone::clone_trait_object!(<T, E> GuardedResultNode<T, E>);
+
+struct RetryingResultNode<T, E> {
+    node: TypedResultNode<T, E>,
+    policy: RetryPolicy,
+}
+
+impl<T, E> Clone for RetryingResultNode<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            node: self.node.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<T, E> GuardedResultNode<T, E> for RetryingResultNode<T, E>
+where
+    T: Clone + Send + Sync + 'static,
+    E: Send + Sync + Clone + 'static,
+{
+    fn call(&self, input: TypedStore<T>) -> GuardedFuture<'_, T, E> {
+        Box::pin(async move {
+            let mut snapshot = input;
+            let mut retry = self.policy.start();
+            loop {
+                let mut attempt = snapshot.clone();
+                let log = attempt.context.start_call_log();
+                let error = match self.node.call(attempt).await {
+                    Ok((mut store, action)) => {
+                        store.context.stop_call_log();
+                        return (store, Ok(action));
+                    }
+                    Err(e) => e,
+                };
+                // The failed attempt's store is gone; keep its LLM spend so
+                // the flow still attributes it and checks the budget.
+                let spent = std::mem::take(&mut *lock(&log));
+                for usage in spent {
+                    snapshot.context.recover_llm_call(usage);
+                }
+
+                // A suspension is a deliberate pause, not a failure.
+                let retryable = !matches!(error, AgentFlowError::Suspended(_))
+                    && self.policy.should_retry_error(&error, error.is_retryable());
+                let delay = if retryable {
+                    retry.next_delay(Some(&error))
+                } else {
+                    None
+                };
+                let Some(delay) = delay else {
+                    return (snapshot, Err(error));
+                };
+                warn!(attempt = retry.attempts(), max_attempts = self.policy.max_attempts, error = %error.report(), "TypedFlow result node failed; retrying");
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        })
+    }
+}
+
+enum RegisteredNode<T, E> {
+    Simple(SimpleTypedNode<T, E>),
+    Result(Box<dyn GuardedResultNode<T, E>>),
+}
+
+impl<T, E> Clone for RegisteredNode<T, E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Simple(n) => Self::Simple(n.clone()),
+            Self::Result(n) => Self::Result(n.clone()),
+        }
+    }
+}
+
 /// Asynchronous hook function type for `TypedFlow`.
 pub type TypedFlowHookFn<T> = std::sync::Arc<
     dyn Fn(
@@ -65,8 +248,9 @@ pub type TypedFlowHookFn<T> = std::sync::Arc<
 
 /// A flow orchestrator that strictly uses `TypedStore<T>` and enum-based transitions `E`.
 pub struct TypedFlow<T, E> {
-    nodes: HashMap<String, SimpleTypedNode<T, E>>,
+    nodes: HashMap<String, RegisteredNode<T, E>>,
     edges: HashMap<String, HashMap<E, String>>,
+    error_edges: HashMap<String, String>,
     start_node: Option<String>,
     /// Maximum number of node executions before the flow is forcibly stopped.
     /// `None` means unlimited (use with care in graphs that may cycle).
@@ -75,6 +259,12 @@ pub struct TypedFlow<T, E> {
     pub pre_node_hook: Option<TypedFlowHookFn<T>>,
     /// Optional hook executed after every node.
     pub post_node_hook: Option<TypedFlowHookFn<T>>,
+    /// Optional LLM spend limits, checked after every node.
+    pub budget: Option<Budget>,
+    /// Identifier recorded in error contexts.
+    pub id: Option<String>,
+    /// Whether node errors are wrapped in [`AgentFlowError::Contextual`].
+    pub error_context: bool,
 }
 
 impl<T, E> TypedFlow<T, E>
@@ -86,10 +276,14 @@ where
         Self {
             nodes: HashMap::new(),
             edges: HashMap::new(),
+            error_edges: HashMap::new(),
             start_node: None,
             max_steps: None,
             pre_node_hook: None,
             post_node_hook: None,
+            budget: None,
+            id: None,
+            error_context: false,
         }
     }
 
@@ -99,6 +293,48 @@ where
         self
     }
 
+    /// Enforce `budget` on this flow's LLM usage.
+    ///
+    /// Calls recorded with [`FlowContext::record_llm_call`] are attributed to
+    /// the node that made them and checked after every node. When a limit is
+    /// exceeded, [`run`](Self::run) halts with `limit_exceeded` and
+    /// [`TypedStore::last_error`] set; [`run_safe`](Self::run_safe) returns
+    /// `Err(AgentFlowError::ExecutionLimitExceeded)` with a per-node breakdown.
+    ///
+    /// [`FlowContext::record_llm_call`]: crate::core::telemetry::FlowContext::record_llm_call
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set the ID reported in error contexts. Implies
+    /// [`with_error_context`](Self::with_error_context).
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self.error_context = true;
+        self
+    }
+
+    /// Wrap errors raised by nodes (and budget overruns) in
+    /// [`AgentFlowError::Contextual`] with the node name, step and flow ID.
+    ///
+    /// Off by default so existing matches on the bare variant keep working;
+    /// use [`AgentFlowError::root_cause`] when it is on.
+    pub fn with_error_context(mut self) -> Self {
+        self.error_context = true;
+        self
+    }
+
+    /// Attach the node, step and flow ID to `error` if error context is on.
+    fn contextualize(&self, error: AgentFlowError, node: &str, step: usize) -> AgentFlowError {
+        if !self.error_context {
+            return error;
+        }
+        let mut context = ErrorContext::new().with_node(node).with_step(step);
+        context.flow_id = self.id.clone();
+        error.with_context(context)
+    }
+
     /// Set a hook that will be called before every node execution.
     pub fn with_pre_node_hook<F, Fut>(mut self, hook: F) -> Self
     where
@@ -128,7 +364,8 @@ where
         if self.start_node.is_none() {
             self.start_node = Some(name.to_string());
         }
-        self.nodes.insert(name.to_string(), node);
+        self.nodes
+            .insert(name.to_string(), RegisteredNode::Simple(node));
     }
 
     /// Add a directed edge: when `from` emits `action`, transition to `to`.
@@ -139,19 +376,94 @@ where
             .insert(action, to.to_string());
     }
 
+    /// Add an error edge: when the result node `from` returns `Err`, continue
+    /// at `to` instead of stopping.
+    ///
+    /// The handler receives the state as it was before the failing node ran,
+    /// with the error in [`TypedStore::last_error`].
+    pub fn add_error_edge(&mut self, from: &str, to: &str) {
+        self.error_edges.insert(from.to_string(), to.to_string());
+    }
+
+    /// Validate the graph for structural integrity prior to execution.
+    ///
+    /// Performs the same checks as [`Flow::validate`]:
+    /// 1. **Start node**: a start node is defined and registered.
+    /// 2. **Reachability**: every edge points to a registered node.
+    /// 3. **Cycle Detection**: uses Tarjan's Strongly Connected Components
+    ///    algorithm to detect cycles. If a cycle is found and `max_steps` is
+    ///    not set, returns a `GraphBuildError`.
+    ///
+    /// [`Flow::validate`]: crate::core::flow::Flow::validate
+    pub fn validate(&self) -> Result<(), AgentFlowError> {
+        let edges = self
+            .edges
+            .iter()
+            .flat_map(|(from, actions)| {
+                actions
+                    .values()
+                    .map(move |to| (from.as_str(), None, to.as_str()))
+            })
+            .chain(
+                self.error_edges
+                    .iter()
+                    .map(|(from, to)| (from.as_str(), Some("error"), to.as_str())),
+            );
+        validate_graph(
+            "TypedFlow",
+            self.nodes.keys().map(String::as_str),
+            edges,
+            self.start_node.as_deref(),
+            self.max_steps.is_some(),
+        )
+    }
+
     /// Execute the flow from the start node. On `max_steps` exceeded, sets
     /// `limit_exceeded` on the returned store and halts.
+    ///
+    /// If a result node fails and has no error edge, the run stops and the
+    /// error is stored in [`TypedStore::last_error`].
     #[instrument(name = "typed_flow.run", skip(self, store), fields(start = self.start_node.as_deref().unwrap_or("none"), max_steps = self.max_steps))]
     pub async fn run(&self, store: TypedStore<T>) -> TypedStore<T> {
         self.run_internal(store, false)
             .await
+            .map(|(store, _)| store)
             .unwrap_or_else(|_| unreachable!())
     }
 
-    /// Execute the flow from the start node. Returns
-    /// `Err(AgentFlowError::ExecutionLimitExceeded)` if `max_steps` is reached.
+    /// Execute the flow from the start node.
+    ///
+    /// The graph is checked with [`validate`](Self::validate) before any node
+    /// runs.
+    ///
+    /// # Errors
+    ///
+    /// - [`AgentFlowError::GraphBuildError`] if validation fails (missing
+    ///   start node, dangling edge, or a cycle without `max_steps`).
+    /// - [`AgentFlowError::ExecutionLimitExceeded`] if `max_steps` is reached.
+    /// - Any error returned by a result node that has no error edge.
     #[instrument(name = "typed_flow.run_safe", skip(self, store), fields(start = self.start_node.as_deref().unwrap_or("none"), max_steps = self.max_steps))]
     pub async fn run_safe(&self, store: TypedStore<T>) -> Result<TypedStore<T>, AgentFlowError> {
+        self.run_safe_with_action(store)
+            .await
+            .map(|(store, _)| store)
+    }
+
+    /// Like [`run_safe`](Self::run_safe), but also returns the action emitted
+    /// by the last node when it had no outgoing edge for it (`None` if the
+    /// last node returned no action).
+    ///
+    /// This is how a typed flow reports its outcome to an enclosing flow; see
+    /// [`TypedFlowNode`](crate::core::bridge::TypedFlowNode).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`run_safe`](Self::run_safe).
+    pub async fn run_safe_with_action(
+        &self,
+        store: TypedStore<T>,
+    ) -> Result<(TypedStore<T>, Option<E>), AgentFlowError> {
+        self.validate()?;
         self.run_internal(store, true).await
     }
 
@@ -159,11 +471,11 @@ where
         &self,
         store: TypedStore<T>,
         safe: bool,
-    ) -> Result<TypedStore<T>, AgentFlowError> {
+    ) -> Result<(TypedStore<T>, Option<E>), AgentFlowError> {
         let current_node_name = if let Some(name) = &self.start_node {
             name.clone()
         } else {
-            return Ok(store);
+            return Ok((store, None));
         };
 
         let mut steps = 0;
@@ -178,14 +490,14 @@ where
                 if let Some(next) = self.edges.get(&current_name).and_then(|e| e.get(&action)) {
                     next.clone()
                 } else {
-                    return Ok(current_store);
+                    return Ok((current_store, Some(action)));
                 }
             } else {
                 current_name
             };
 
             if !self.nodes.contains_key(&next_node) {
-                return Ok(current_store);
+                return Ok((current_store, None));
             }
 
             if steps >= limit {
@@ -196,7 +508,7 @@ where
                     ));
                 } else {
                     current_store.limit_exceeded = true;
-                    return Ok(current_store);
+                    return Ok((current_store, None));
                 }
             }
             steps += 1;
@@ -204,7 +516,7 @@ where
 
             let node = match self.nodes.get(&next_node) {
                 Some(n) => n,
-                None => return Ok(current_store),
+                None => return Ok((current_store, None)),
             };
 
             if let Some(hook) = &self.pre_node_hook {
@@ -212,7 +524,13 @@ where
             }
 
             let start_time = std::time::Instant::now();
-            let (new_store, new_action_opt) = node.call(current_store).await;
+            let (new_store, outcome) = match node {
+                RegisteredNode::Simple(n) => {
+                    let (s, action) = n.call(current_store).await;
+                    (s, Ok(action))
+                }
+                RegisteredNode::Result(n) => n.call(current_store).await,
+            };
             let elapsed = start_time.elapsed();
 
             current_store = new_store;
@@ -224,8 +542,41 @@ where
                 current_store = hook(&next_node, current_store).await;
             }
 
+            if let Err(e) =
+                budget::settle(&mut current_store.context, &next_node, self.budget.as_ref())
+            {
+                let e = self.contextualize(e, &next_node, steps);
+                warn!(node = %next_node, error = %e.report(), "TypedFlow stopped by budget");
+                if safe {
+                    return Err(e);
+                }
+                current_store.limit_exceeded = true;
+                current_store.last_error = Some(e);
+                return Ok((current_store, None));
+            }
+
+            let new_action_opt = match outcome {
+                Ok(action) => action,
+                Err(e) => {
+                    let e = self.contextualize(e, &next_node, steps);
+                    if let Some(target) = self.error_edges.get(&next_node) {
+                        warn!(node = %next_node, error = %e.report(), target = %target, "TypedFlow routing node failure along error edge");
+                        current_store.last_error = Some(e);
+                        // No action: the loop runs `target` directly.
+                        let _ = tx.send((current_store, None, target.clone())).await;
+                        continue;
+                    }
+                    warn!(node = %next_node, error = %e.report(), "TypedFlow node failed");
+                    if safe {
+                        return Err(e);
+                    }
+                    current_store.last_error = Some(e);
+                    return Ok((current_store, None));
+                }
+            };
+
             if new_action_opt.is_none() {
-                return Ok(current_store);
+                return Ok((current_store, None));
             } else {
                 let _ = tx.send((current_store, new_action_opt, next_node)).await;
             }
@@ -235,6 +586,93 @@ where
     }
 }
 
+impl<T, E> TypedFlow<T, E>
+where
+    T: Clone + Send + Sync + 'static,
+    E: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+{
+    /// Register a fallible typed node. The **first** node added becomes the
+    /// start node.
+    ///
+    /// The state is snapshotted before the node runs, so on `Err` the flow
+    /// continues (along an [error edge](Self::add_error_edge)) or stops with
+    /// the last good state. Requires `T: Clone` for that snapshot.
+    pub fn add_result_node(&mut self, name: &str, node: TypedResultNode<T, E>) {
+        self.add_result_node_with_retry(name, node, 0, 0);
+    }
+
+    /// Like [`add_result_node`](Self::add_result_node), but retries the node
+    /// up to `max_retries` times, waiting `wait_millis` between attempts.
+    ///
+    /// Only [retryable](AgentFlowError::is_retryable) errors are retried.
+    /// Use [`add_result_node_with_policy`](Self::add_result_node_with_policy)
+    /// for backoff or a different error predicate.
+    pub fn add_result_node_with_retry(
+        &mut self,
+        name: &str,
+        node: TypedResultNode<T, E>,
+        max_retries: usize,
+        wait_millis: u64,
+    ) {
+        let policy = RetryPolicy::fixed(
+            max_retries.saturating_add(1),
+            std::time::Duration::from_millis(wait_millis),
+        );
+        self.add_result_node_with_policy(name, node, policy);
+    }
+
+    /// Like [`add_result_node`](Self::add_result_node), but retries the node
+    /// under `policy`.
+    ///
+    /// Each attempt starts from the same snapshot, plus the LLM usage of the
+    /// failed attempts so that retried spend still counts towards the
+    /// [budget](Self::with_budget). Without a
+    /// [`retry_if`](RetryPolicy::retry_if) predicate only
+    /// [retryable](AgentFlowError::is_retryable) errors are retried;
+    /// [`AgentFlowError::Suspended`] never is.
+    pub fn add_result_node_with_policy(
+        &mut self,
+        name: &str,
+        node: TypedResultNode<T, E>,
+        policy: RetryPolicy,
+    ) {
+        if self.start_node.is_none() {
+            self.start_node = Some(name.to_string());
+        }
+        let guarded = RetryingResultNode { node, policy };
+        self.nodes
+            .insert(name.to_string(), RegisteredNode::Result(Box::new(guarded)));
+    }
+}
+
+/// A node value that knows how to register itself in a [`TypedFlow`].
+///
+/// Implemented for [`SimpleTypedNode`] and [`TypedResultNode`] so that the
+/// `typed_flow!` macro can accept either kind of node.
+pub trait IntoTypedFlowNode<T, E> {
+    /// Register `self` in `flow` under `name`.
+    fn add_to(self, flow: &mut TypedFlow<T, E>, name: &str);
+}
+
+impl<T, E> IntoTypedFlowNode<T, E> for SimpleTypedNode<T, E>
+where
+    E: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+{
+    fn add_to(self, flow: &mut TypedFlow<T, E>, name: &str) {
+        flow.add_node(name, self);
+    }
+}
+
+impl<T, E> IntoTypedFlowNode<T, E> for TypedResultNode<T, E>
+where
+    T: Clone + Send + Sync + 'static,
+    E: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+{
+    fn add_to(self, flow: &mut TypedFlow<T, E>, name: &str) {
+        flow.add_result_node(name, self);
+    }
+}
+
 impl<T, E> Clone for TypedFlow<T, E>
 where
     E: Clone,
@@ -247,10 +685,14 @@ where
         Self {
             nodes: new_nodes,
             edges: self.edges.clone(),
+            error_edges: self.error_edges.clone(),
             start_node: self.start_node.clone(),
             max_steps: self.max_steps,
             pre_node_hook: self.pre_node_hook.clone(),
             post_node_hook: self.post_node_hook.clone(),
+            budget: self.budget.clone(),
+            id: self.id.clone(),
+            error_context: self.error_context,
         }
     }
 }
@@ -285,9 +727,8 @@ mod tests {
         let mut flow = TypedFlow::<TestState, TestAction>::new().with_max_steps(10);
 
         let node_a = create_typed_node(|mut store: TypedStore<TestState>| async move {
-            let mut count = 0;
             store.inner.count += 1;
-            count = store.inner.count;
+            let count = store.inner.count;
             store.inner.messages.push(format!("A: {}", count));
             if count < 3 {
                 (store, Some(TestAction::Next))
@@ -366,6 +807,41 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_typed_flow_validate_rejects_dangling_edge_and_unbounded_cycle() {
+        let mut flow = TypedFlow::<TestState, TestAction>::new();
+        assert!(matches!(
+            flow.validate(),
+            Err(AgentFlowError::GraphBuildError(_))
+        ));
+
+        flow.add_node(
+            "A",
+            create_typed_node(|store: TypedStore<TestState>| async move {
+                (store, Some(TestAction::Next))
+            }),
+        );
+        flow.add_edge("A", TestAction::Next, "missing");
+        assert!(matches!(
+            flow.validate(),
+            Err(AgentFlowError::GraphBuildError(msg)) if msg.contains("missing")
+        ));
+
+        let mut flow = TypedFlow::<TestState, TestAction>::new();
+        flow.add_node(
+            "A",
+            create_typed_node(|store: TypedStore<TestState>| async move {
+                (store, Some(TestAction::Loop))
+            }),
+        );
+        flow.add_edge("A", TestAction::Loop, "A");
+        assert!(matches!(
+            flow.validate(),
+            Err(AgentFlowError::GraphBuildError(msg)) if msg.contains("cycle")
+        ));
+        assert!(flow.with_max_steps(3).validate().is_ok());
+    }
+
     #[tokio::test]
     async fn test_typed_flow_run_sets_limit_exceeded_flag() {
         let mut flow = TypedFlow::<TestState, TestAction>::new().with_max_steps(3);
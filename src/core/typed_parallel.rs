@@ -0,0 +1,227 @@
+//! Parallel fork / join execution for [`TypedFlow`].
+//!
+//! [`TypedParallel`] is the typed counterpart of [`ParallelFlow`]: it runs
+//! several [`TypedFlow`] branches concurrently, each on its own **clone** of
+//! the state `T`, and combines the results with a user-supplied
+//! `fn(T, Vec<T>) -> T` merge.
+//!
+//! # How it works
+//!
+//! 1. Each branch receives a clone of the input state and a fresh
+//!    [`FlowContext`], so branches share nothing at runtime.
+//! 2. All branches are awaited with [`futures::future::join_all`].
+//! 3. The merge function receives the original state and the branch states,
+//!    in the order the branches were added.
+//! 4. Every branch's token usage and node durations are folded into the
+//!    parent store's [`FlowContext`]; `limit_exceeded` is set if any branch
+//!    hit its step limit, and `last_error` is taken from the first branch
+//!    that recorded one.
+//!
+//! `TypedParallel` also implements [`TypedNode`], so a fan-out can be a single
+//! step inside a larger `TypedFlow`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use agentflow::core::typed_flow::{create_typed_node, TypedFlow};
+//! use agentflow::core::typed_parallel::TypedParallel;
+//! use agentflow::core::typed_store::TypedStore;
+//!
+//! #[derive(Clone, Default)]
+//! struct Research { notes: Vec<String> }
+//!
+//! #[derive(Clone, PartialEq, Eq, Hash)]
+//! enum Action { Done }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let web = create_typed_node(|mut s: TypedStore<Research>| async move {
+//!         s.inner.notes.push("web".into());
+//!         (s, None)
+//!     });
+//!     let docs = create_typed_node(|mut s: TypedStore<Research>| async move {
+//!         s.inner.notes.push("docs".into());
+//!         (s, None)
+//!     });
+//!
+//!     let fan_out = TypedParallel::<Research, Action>::new(|mut base, branches| {
+//!         for b in branches {
+//!             base.notes.extend(b.notes);
+//!         }
+//!         base
+//!     })
+//!     .add_node_branch("web", web)
+//!     .add_node_branch("docs", docs);
+//!
+//!     let result = fan_out.run(TypedStore::new(Research::default())).await;
+//!     assert_eq!(result.inner.notes.len(), 2);
+//! }
+//! ```
+//!
+//! [`TypedFlow`]: crate::core::typed_flow::TypedFlow
+//! [`TypedNode`]: crate::core::typed_flow::TypedNode
+//! [`ParallelFlow`]: crate::core::parallel::ParallelFlow
+//! [`FlowContext`]: crate::core::telemetry::FlowContext
+
+use crate::core::error::AgentFlowError;
+use crate::core::telemetry::FlowContext;
+use crate::core::typed_flow::{SimpleTypedNode, TypedFlow, TypedNode, TypedNodeFuture};
+use crate::core::typed_store::TypedStore;
+use futures::future::join_all;
+use std::sync::Arc;
+use tracing::{debug, info, instrument};
+
+/// Merge function signature: `(initial_state, branch_states) -> merged_state`.
+pub type TypedMergeFn<T> = Arc<dyn Fn(T, Vec<T>) -> T + Send + Sync>;
+
+/// Runs multiple [`TypedFlow`]s concurrently on cloned state and merges the
+/// results.
+///
+/// See the [module-level documentation](self) for a full example.
+pub struct TypedParallel<T, E> {
+    branches: Vec<TypedFlow<T, E>>,
+    merge_fn: TypedMergeFn<T>,
+    action: Option<E>,
+}
+
+impl<T, E> TypedParallel<T, E>
+where
+    T: Clone + Send + Sync + 'static,
+    E: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+{
+    /// Create an empty fan-out that combines branch states with `merge`.
+    ///
+    /// `merge` receives the state passed to [`run`](Self::run) and one state
+    /// per branch, in the order branches were added.
+    pub fn new<F>(merge: F) -> Self
+    where
+        F: Fn(T, Vec<T>) -> T + Send + Sync + 'static,
+    {
+        Self {
+            branches: Vec::new(),
+            merge_fn: Arc::new(merge),
+            action: None,
+        }
+    }
+
+    /// Add a whole [`TypedFlow`] as a branch.
+    pub fn add_branch(mut self, flow: TypedFlow<T, E>) -> Self {
+        self.branches.push(flow);
+        self
+    }
+
+    /// Add a single node as a branch. It is wrapped in a one-node
+    /// [`TypedFlow`] registered under `name`, so its duration is reported
+    /// under that name.
+    pub fn add_node_branch(mut self, name: &str, node: SimpleTypedNode<T, E>) -> Self {
+        let mut flow = TypedFlow::new();
+        flow.add_node(name, node);
+        self.branches.push(flow);
+        self
+    }
+
+    /// Action returned when this fan-out runs as a [`TypedNode`] inside a
+    /// parent `TypedFlow`. Without it the parent flow stops after the join.
+    pub fn with_action(mut self, action: E) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// Number of registered branches.
+    pub fn len(&self) -> usize {
+        self.branches.len()
+    }
+
+    /// Returns `true` if no branches are registered.
+    pub fn is_empty(&self) -> bool {
+        self.branches.is_empty()
+    }
+
+    /// Execute all branches concurrently and return the merged store.
+    ///
+    /// Branches run with [`TypedFlow::run`]; a branch that hits its step limit
+    /// sets `limit_exceeded` on the merged store.
+    #[instrument(name = "typed_parallel.run", skip(self, store), fields(branches = self.branches.len()))]
+    pub async fn run(&self, store: TypedStore<T>) -> TypedStore<T> {
+        debug!(
+            branch_count = self.branches.len(),
+            "TypedParallel spawning branches"
+        );
+        let futs = self.branches.iter().enumerate().map(|(i, flow)| {
+            let branch_store = TypedStore::new(store.inner.clone());
+            async move {
+                debug!(branch = i, "TypedParallel branch started");
+                let result = flow.run(branch_store).await;
+                debug!(branch = i, "TypedParallel branch finished");
+                result
+            }
+        });
+        let branches = join_all(futs).await;
+        self.merge_branches(store, branches)
+    }
+
+    /// Execute all branches concurrently with [`TypedFlow::run_safe`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first branch error (in branch order), e.g.
+    /// `GraphBuildError` or `ExecutionLimitExceeded`. All branches still run
+    /// to completion before the error is returned.
+    #[instrument(name = "typed_parallel.run_safe", skip(self, store), fields(branches = self.branches.len()))]
+    pub async fn run_safe(&self, store: TypedStore<T>) -> Result<TypedStore<T>, AgentFlowError> {
+        let futs = self.branches.iter().map(|flow| {
+            let branch_store = TypedStore::new(store.inner.clone());
+            flow.run_safe(branch_store)
+        });
+        let results = join_all(futs).await;
+        let branches = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+        Ok(self.merge_branches(store, branches))
+    }
+
+    fn merge_branches(
+        &self,
+        mut store: TypedStore<T>,
+        branches: Vec<TypedStore<T>>,
+    ) -> TypedStore<T> {
+        let mut states = Vec::with_capacity(branches.len());
+        let mut contexts: Vec<FlowContext> = Vec::with_capacity(branches.len());
+        for branch in branches {
+            store.limit_exceeded |= branch.limit_exceeded;
+            if store.last_error.is_none() {
+                store.last_error = branch.last_error;
+            }
+            contexts.push(branch.context);
+            states.push(branch.inner);
+        }
+
+        info!(
+            branch_count = states.len(),
+            "TypedParallel all branches done; merging"
+        );
+        for ctx in &contexts {
+            store.context.merge(ctx);
+        }
+        store.inner = (self.merge_fn)(store.inner, states);
+        store
+    }
+}
+
+impl<T, E> TypedNode<T, E> for TypedParallel<T, E>
+where
+    T: Clone + Send + Sync + 'static,
+    E: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+{
+    fn call(&self, input: TypedStore<T>) -> TypedNodeFuture<'_, T, E> {
+        Box::pin(async move { (self.run(input).await, self.action.clone()) })
+    }
+}
+
+impl<T, E: Clone> Clone for TypedParallel<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            branches: self.branches.clone(),
+            merge_fn: self.merge_fn.clone(),
+            action: self.action.clone(),
+        }
+    }
+}
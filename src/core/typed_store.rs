@@ -62,6 +62,18 @@ pub struct TypedStore<T> {
 
     /// Telemetry context tracking execution time and tokens.
     pub context: crate::core::telemetry::FlowContext,
+
+    /// The most recent error returned by a result node.
+    ///
+    /// Set by [`TypedFlow`] when a node registered with
+    /// [`add_result_node`] fails, either before following its error edge or
+    /// when [`TypedFlow::run`] stops on the failure. Error handlers may clear
+    /// it once the failure is dealt with.
+    ///
+    /// [`TypedFlow`]: crate::core::typed_flow::TypedFlow
+    /// [`TypedFlow::run`]: crate::core::typed_flow::TypedFlow::run
+    /// [`add_result_node`]: crate::core::typed_flow::TypedFlow::add_result_node
+    pub last_error: Option<crate::core::error::AgentFlowError>,
 }
 
 impl<T> TypedStore<T> {
@@ -71,6 +83,7 @@ impl<T> TypedStore<T> {
             inner: state,
             limit_exceeded: false,
             context: crate::core::telemetry::FlowContext::new(),
+            last_error: None,
         }
     }
 
@@ -89,6 +102,7 @@ impl<T: Clone> Clone for TypedStore<T> {
             inner: self.inner.clone(),
             limit_exceeded: self.limit_exceeded,
             context: self.context.clone(),
+            last_error: self.last_error.clone(),
         }
     }
 }
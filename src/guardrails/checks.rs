@@ -0,0 +1,476 @@
+//! Built-in [`Guardrail`] checks.
+//!
+//! Text checks look at every string in the value — a plain string, or the
+//! string leaves of an object or array — and redact them in place.
+
+use super::{schema, Finding, Guardrail, GuardrailFuture};
+use crate::core::error::AgentFlowError;
+use regex::Regex;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Apply `f` to every string in `value`; `None` if nothing changed.
+fn rewrite_strings(value: &Value, f: &mut dyn FnMut(&str) -> Option<String>) -> Option<Value> {
+    match value {
+        Value::String(s) => f(s).map(Value::String),
+        Value::Array(items) => {
+            let mut changed = false;
+            let items = items
+                .iter()
+                .map(|item| match rewrite_strings(item, f) {
+                    Some(new) => {
+                        changed = true;
+                        new
+                    }
+                    None => item.clone(),
+                })
+                .collect();
+            changed.then_some(Value::Array(items))
+        }
+        Value::Object(map) => {
+            let mut changed = false;
+            let map = map
+                .iter()
+                .map(|(k, v)| match rewrite_strings(v, f) {
+                    Some(new) => {
+                        changed = true;
+                        (k.clone(), new)
+                    }
+                    None => (k.clone(), v.clone()),
+                })
+                .collect();
+            changed.then_some(Value::Object(map))
+        }
+        _ => None,
+    }
+}
+
+/// Kind of personal data detected by [`Pii`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PiiKind {
+    /// Email addresses, redacted as `[EMAIL]`.
+    Email,
+    /// Phone numbers with separators or a country code, redacted as `[PHONE]`.
+    Phone,
+    /// Payment card numbers passing the Luhn check, redacted as `[CARD]`.
+    Card,
+}
+
+impl PiiKind {
+    fn label(self) -> &'static str {
+        match self {
+            PiiKind::Email => "email",
+            PiiKind::Phone => "phone number",
+            PiiKind::Card => "card number",
+        }
+    }
+
+    fn placeholder(self) -> &'static str {
+        match self {
+            PiiKind::Email => "[EMAIL]",
+            PiiKind::Phone => "[PHONE]",
+            PiiKind::Card => "[CARD]",
+        }
+    }
+
+    fn pattern(self) -> Regex {
+        let pattern = match self {
+            PiiKind::Email => r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b",
+            PiiKind::Phone => {
+                r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{1,4}\)[\s.-]?|\b\d{2,4}[\s.-])\d{3,4}[\s.-]?\d{3,4}\b"
+            }
+            // Any run of digit groups; `redact_cards` picks the card numbers.
+            PiiKind::Card => r"\b[0-9](?:[ -]?[0-9])*\b",
+        };
+        // The patterns are constants; a failure here is a bug in this file.
+        #[allow(clippy::expect_used)]
+        Regex::new(pattern).expect("built-in PII pattern is valid")
+    }
+}
+
+/// Whether the digits of `s` pass the Luhn checksum.
+fn luhn(s: &str) -> bool {
+    let digits: Vec<u32> = s.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (1, doubled) if doubled > 9 => doubled - 9,
+            (1, doubled) => doubled,
+            _ => d,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Replace card numbers in `text` with `[CARD]`, or `None` if there are none.
+///
+/// `runs` matches runs of digit groups separated by single spaces or dashes.
+/// Within a run, every group-aligned span of 13–19 digits is tried, longest
+/// first, so a card number followed by an expiry date or another figure is
+/// still found.
+fn redact_cards(runs: &Regex, text: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut last = 0;
+    let mut hit = false;
+    for run in runs.find_iter(text) {
+        let mut groups = Vec::new();
+        let mut start = run.start();
+        for (i, b) in run.as_str().bytes().enumerate() {
+            if !b.is_ascii_digit() {
+                groups.push((start, run.start() + i));
+                start = run.start() + i + 1;
+            }
+        }
+        groups.push((start, run.end()));
+
+        let mut first = 0;
+        while first < groups.len() {
+            // Extend the span a group at a time until it passes 19 digits.
+            let mut digits = 0;
+            let mut card = None;
+            for (end, &(start, stop)) in groups.iter().enumerate().skip(first) {
+                digits += stop - start;
+                if digits > 19 {
+                    break;
+                }
+                if digits >= 13 && luhn(&text[groups[first].0..stop]) {
+                    card = Some(end);
+                }
+            }
+            match card {
+                Some(end) => {
+                    out.push_str(&text[last..groups[first].0]);
+                    out.push_str(PiiKind::Card.placeholder());
+                    last = groups[end].1;
+                    hit = true;
+                    first = end + 1;
+                }
+                None => first += 1,
+            }
+        }
+    }
+    hit.then(|| {
+        out.push_str(&text[last..]);
+        out
+    })
+}
+
+/// Detects and redacts emails, phone numbers and card numbers.
+#[derive(Debug, Clone)]
+pub struct Pii {
+    kinds: Vec<(PiiKind, Regex)>,
+}
+
+impl Pii {
+    /// Detect every [`PiiKind`].
+    pub fn new() -> Self {
+        Self::only(&[PiiKind::Card, PiiKind::Email, PiiKind::Phone])
+    }
+
+    /// Detect only `kinds`.
+    pub fn only(kinds: &[PiiKind]) -> Self {
+        // Cards first so their digit groups are not taken for phone numbers.
+        let mut kinds = kinds.to_vec();
+        kinds.sort_by_key(|k| *k != PiiKind::Card);
+        kinds.dedup();
+        Self {
+            kinds: kinds.into_iter().map(|k| (k, k.pattern())).collect(),
+        }
+    }
+}
+
+impl Default for Pii {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Guardrail for Pii {
+    fn name(&self) -> &str {
+        "pii"
+    }
+
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a> {
+        Box::pin(async move {
+            let mut found: Vec<&str> = Vec::new();
+            let redacted = rewrite_strings(value, &mut |text| {
+                let mut current = text.to_string();
+                for (kind, re) in &self.kinds {
+                    let next = match kind {
+                        PiiKind::Card => redact_cards(re, &current),
+                        _ => re
+                            .is_match(&current)
+                            .then(|| re.replace_all(&current, kind.placeholder()).into_owned()),
+                    };
+                    if let Some(next) = next {
+                        current = next;
+                        if !found.contains(&kind.label()) {
+                            found.push(kind.label());
+                        }
+                    }
+                }
+                (current != text).then_some(current)
+            });
+            Ok(redacted.map(|redacted| {
+                Finding::new(format!("contains {}", found.join(", "))).with_redacted(redacted)
+            }))
+        })
+    }
+}
+
+/// Flags text matching a regular expression and redacts the matches.
+#[derive(Debug, Clone)]
+pub struct RegexCheck {
+    name: String,
+    pattern: Regex,
+    replacement: String,
+}
+
+impl RegexCheck {
+    /// A check called `name` for `pattern`, redacting matches as
+    /// `[REDACTED]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::Custom`] if `pattern` is not a valid regex.
+    pub fn new(name: &str, pattern: &str) -> Result<Self, AgentFlowError> {
+        let pattern = Regex::new(pattern).map_err(|e| {
+            AgentFlowError::Custom(format!("Invalid guardrail pattern for '{}': {}", name, e))
+        })?;
+        Ok(Self {
+            name: name.to_string(),
+            pattern,
+            replacement: "[REDACTED]".to_string(),
+        })
+    }
+
+    /// Redact matches as `replacement` instead.
+    pub fn with_replacement(mut self, replacement: &str) -> Self {
+        self.replacement = replacement.to_string();
+        self
+    }
+}
+
+impl Guardrail for RegexCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a> {
+        Box::pin(async move {
+            let mut matches = 0;
+            let redacted = rewrite_strings(value, &mut |text| {
+                let count = self.pattern.find_iter(text).count();
+                matches += count;
+                (count > 0).then(|| {
+                    self.pattern
+                        .replace_all(text, regex::NoExpand(&self.replacement))
+                        .into_owned()
+                })
+            });
+            Ok(redacted.map(|redacted| {
+                Finding::new(format!(
+                    "{} match(es) of /{}/",
+                    matches,
+                    self.pattern.as_str()
+                ))
+                .with_redacted(redacted)
+            }))
+        })
+    }
+}
+
+/// Flags values longer than a character limit; redaction truncates strings.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxLength {
+    max_chars: usize,
+}
+
+impl MaxLength {
+    /// Allow at most `max_chars` characters. Non-string values are measured
+    /// as serialized JSON and cannot be redacted.
+    pub fn new(max_chars: usize) -> Self {
+        Self { max_chars }
+    }
+}
+
+impl Guardrail for MaxLength {
+    fn name(&self) -> &str {
+        "max_length"
+    }
+
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a> {
+        Box::pin(async move {
+            let (len, redacted) = match value {
+                Value::String(s) => (
+                    s.chars().count(),
+                    Some(Value::String(s.chars().take(self.max_chars).collect())),
+                ),
+                other => (other.to_string().chars().count(), None),
+            };
+            if len <= self.max_chars {
+                return Ok(None);
+            }
+            let finding = Finding::new(format!(
+                "{} characters exceeds the limit of {}",
+                len, self.max_chars
+            ));
+            Ok(Some(match redacted {
+                Some(redacted) => finding.with_redacted(redacted),
+                None => finding,
+            }))
+        })
+    }
+}
+
+/// Flags whole-word, case-insensitive occurrences of banned terms and
+/// redacts them as `[REDACTED]`.
+#[derive(Debug, Clone)]
+pub struct BannedTerms {
+    pattern: Option<Regex>,
+}
+
+impl BannedTerms {
+    /// Ban `terms`. Empty terms are ignored.
+    ///
+    /// A term that starts or ends with a letter, digit or `_` must not be
+    /// part of a longer word on that side; punctuation such as the `++` of
+    /// `"c++"` or the `@` of `"@admin"` needs no boundary.
+    pub fn new(terms: &[&str]) -> Self {
+        let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let alternatives: Vec<String> = terms
+            .iter()
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                let start = if is_word(t.chars().next()) { r"\b" } else { "" };
+                let end = if is_word(t.chars().last()) { r"\b" } else { "" };
+                format!("{}{}{}", start, regex::escape(t), end)
+            })
+            .collect();
+        // Escaped literals always form a valid pattern.
+        let pattern = (!alternatives.is_empty())
+            .then(|| Regex::new(&format!("(?i){}", alternatives.join("|"))).ok())
+            .flatten();
+        Self { pattern }
+    }
+}
+
+impl Guardrail for BannedTerms {
+    fn name(&self) -> &str {
+        "banned_terms"
+    }
+
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a> {
+        Box::pin(async move {
+            let Some(pattern) = &self.pattern else {
+                return Ok(None);
+            };
+            let mut found: Vec<String> = Vec::new();
+            let redacted = rewrite_strings(value, &mut |text| {
+                let mut hit = false;
+                for m in pattern.find_iter(text) {
+                    hit = true;
+                    let term = m.as_str().to_lowercase();
+                    if !found.contains(&term) {
+                        found.push(term);
+                    }
+                }
+                hit.then(|| pattern.replace_all(text, "[REDACTED]").into_owned())
+            });
+            Ok(redacted.map(|redacted| {
+                Finding::new(format!("contains banned terms: {}", found.join(", ")))
+                    .with_redacted(redacted)
+            }))
+        })
+    }
+}
+
+/// Flags values that do not conform to a JSON Schema.
+///
+/// A string value is parsed as JSON first, so raw LLM output can be checked
+/// directly. See [`schema`](super::schema) for the supported keywords.
+/// Violations cannot be redacted.
+#[derive(Debug, Clone)]
+pub struct JsonSchema {
+    schema: Value,
+}
+
+impl JsonSchema {
+    /// Validate against `schema`.
+    pub fn new(schema: Value) -> Self {
+        Self { schema }
+    }
+}
+
+impl Guardrail for JsonSchema {
+    fn name(&self) -> &str {
+        "json_schema"
+    }
+
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a> {
+        Box::pin(async move {
+            let parsed;
+            let value = match value {
+                Value::String(s) => match serde_json::from_str::<Value>(s) {
+                    Ok(v) => {
+                        parsed = v;
+                        &parsed
+                    }
+                    Err(e) => return Ok(Some(Finding::new(format!("not valid JSON: {}", e)))),
+                },
+                other => other,
+            };
+            let errors = schema::validate(&self.schema, value);
+            Ok((!errors.is_empty()).then(|| Finding::new(errors.join("; "))))
+        })
+    }
+}
+
+type CustomFn = Arc<
+    dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<Option<Finding>, AgentFlowError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// An async validator built with [`custom`].
+#[derive(Clone)]
+pub struct CustomCheck {
+    name: String,
+    func: CustomFn,
+}
+
+/// A check called `name` that runs `func` on a copy of the value — e.g. a
+/// moderation API call or an LLM-as-judge.
+///
+/// ```rust
+/// use agentflow::guardrails::{custom, Finding};
+///
+/// let polite = custom("polite", |value| async move {
+///     let rude = value.as_str().is_some_and(|s| s.contains("RTFM"));
+///     Ok(rude.then(|| Finding::new("impolite reply")))
+/// });
+/// ```
+pub fn custom<F, Fut>(name: &str, func: F) -> CustomCheck
+where
+    F: Fn(Value) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Option<Finding>, AgentFlowError>> + Send + 'static,
+{
+    CustomCheck {
+        name: name.to_string(),
+        func: Arc::new(move |value| Box::pin(func(value))),
+    }
+}
+
+impl Guardrail for CustomCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a> {
+        Box::pin((self.func)(value.clone()))
+    }
+}
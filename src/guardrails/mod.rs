@@ -0,0 +1,322 @@
+//! Input and output validation around LLM nodes.
+//!
+//! A [`Guardrails`] node runs a list of [`Guardrail`] checks against one
+//! value — a store key such as `store["response"]`, or the last message of
+//! `store["conversation"]` — and applies each check's [`OnViolation`] policy:
+//!
+//! - [`Block`](OnViolation::Block) — fail with [`AgentFlowError::InvalidOutput`];
+//! - [`Redact`](OnViolation::Redact) — replace the value with the check's
+//!   redacted version and continue;
+//! - [`Route`](OnViolation::Route) — continue, setting `store["action"]` so a
+//!   [`Flow`](crate::core::flow::Flow) moves to a handler node.
+//!
+//! Every violation is appended to `store["guardrail_violations"]`, whatever
+//! the policy. Put one `Guardrails` node before an LLM node to screen input
+//! and another after it to screen output.
+//!
+//! Built-in checks live in [`checks`]: [`Pii`], [`RegexCheck`],
+//! [`MaxLength`], [`BannedTerms`], [`JsonSchema`] and [`custom`] for async
+//! validators.
+//!
+//! # Example
+//!
+//! ```rust
+//! use agentflow::guardrails::{BannedTerms, Guardrails, MaxLength, OnViolation, Pii};
+//! use agentflow::prelude::*;
+//! use serde_json::json;
+//!
+//! # #[tokio::main] async fn main() {
+//! let output_guard = Guardrails::new("response")
+//!     .with_check(Pii::new(), OnViolation::Redact)
+//!     .with_check(MaxLength::new(2000), OnViolation::Redact)
+//!     .with_check(BannedTerms::new(&["internal-only"]), OnViolation::route("escalate"));
+//!
+//! let store = Store::new().into_shared();
+//! store.write().await.insert("response".into(), json!("Mail jane@example.com"));
+//! let store = output_guard.call(store).await.unwrap();
+//! assert_eq!(store.read().await["response"], "Mail [EMAIL]");
+//! # }
+//! ```
+
+pub mod checks;
+pub mod schema;
+
+pub use checks::{
+    custom, BannedTerms, CustomCheck, JsonSchema, MaxLength, Pii, PiiKind, RegexCheck,
+};
+
+use crate::core::conversation::{ContentPart, Conversation};
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, SharedStore};
+use crate::core::store::load_json;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::{debug, instrument, warn};
+
+/// Store key where [`Guardrails`] records every [`Violation`].
+pub const VIOLATIONS_KEY: &str = "guardrail_violations";
+
+/// Boxed future returned by [`Guardrail::check`].
+pub type GuardrailFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<Finding>, AgentFlowError>> + Send + 'a>>;
+
+/// A problem found by a [`Guardrail`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// What is wrong.
+    pub message: String,
+    /// The value with the problem removed, if the check can redact it.
+    pub redacted: Option<Value>,
+}
+
+impl Finding {
+    /// A finding that cannot be redacted.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            redacted: None,
+        }
+    }
+
+    /// Offer `value` as the redacted replacement.
+    pub fn with_redacted(mut self, value: Value) -> Self {
+        self.redacted = Some(value);
+        self
+    }
+}
+
+/// One validation check.
+///
+/// Implement [`check`](Self::check) to return `Ok(None)` for a clean value
+/// and `Ok(Some(finding))` for a violation. Errors are reserved for checks
+/// that could not run, and abort the [`Guardrails`] node.
+pub trait Guardrail: Send + Sync {
+    /// Name recorded in [`Violation::guardrail`].
+    fn name(&self) -> &str;
+
+    /// Inspect `value`.
+    fn check<'a>(&'a self, value: &'a Value) -> GuardrailFuture<'a>;
+}
+
+/// What to do when a check finds a violation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnViolation {
+    /// Fail with [`AgentFlowError::InvalidOutput`].
+    Block,
+    /// Continue with the check's redacted value. Checks that cannot redact
+    /// (e.g. [`JsonSchema`]) block instead.
+    Redact,
+    /// Continue and set `store["action"]` to this action.
+    Route(String),
+}
+
+impl OnViolation {
+    /// [`OnViolation::Route`] to `action`.
+    pub fn route(action: &str) -> Self {
+        Self::Route(action.to_string())
+    }
+}
+
+/// A recorded violation, as stored in `store["guardrail_violations"]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    /// The check's [`name`](Guardrail::name).
+    pub guardrail: String,
+    /// What was checked: a store key, or `"conversation"`.
+    pub target: String,
+    /// The finding's message.
+    pub message: String,
+    /// The policy that was applied.
+    pub action: OnViolation,
+}
+
+impl Violation {
+    /// All violations recorded in `store`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn load(store: &SharedStore) -> Result<Vec<Self>, AgentFlowError> {
+        Ok(load_json(store, VIOLATIONS_KEY).await?.unwrap_or_default())
+    }
+}
+
+/// The value a [`Guardrails`] node checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Key(String),
+    LastMessage,
+}
+
+/// Node running [`Guardrail`] checks against one value.
+///
+/// Checks run in order, each seeing the value as redacted by the ones
+/// before. A missing value is skipped. See the [module docs](self).
+#[derive(Clone)]
+pub struct Guardrails {
+    target: Target,
+    checks: Vec<(Arc<dyn Guardrail>, OnViolation)>,
+}
+
+impl Guardrails {
+    /// Check `store[key]`.
+    pub fn new(key: &str) -> Self {
+        Self {
+            target: Target::Key(key.to_string()),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Check the text of the last message in `store["conversation"]`, e.g.
+    /// the user's input before an [`llm_node`](crate::llm::llm_node).
+    /// Redaction replaces the message's text parts with one redacted part;
+    /// images and data parts are kept.
+    pub fn last_message() -> Self {
+        Self {
+            target: Target::LastMessage,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Add `check`, applying `on_violation` when it fails.
+    pub fn with_check<G>(mut self, check: G, on_violation: OnViolation) -> Self
+    where
+        G: Guardrail + 'static,
+    {
+        self.checks.push((Arc::new(check), on_violation));
+        self
+    }
+
+    fn target_name(&self) -> &str {
+        match &self.target {
+            Target::Key(key) => key,
+            Target::LastMessage => crate::core::conversation::CONVERSATION_KEY,
+        }
+    }
+
+    async fn read_value(&self, store: &SharedStore) -> Result<Option<Value>, AgentFlowError> {
+        match &self.target {
+            Target::Key(key) => Ok(store.read().await.get(key).cloned()),
+            Target::LastMessage => Ok(Conversation::load(store)
+                .await?
+                .last()
+                .map(|m| Value::String(m.text()))),
+        }
+    }
+
+    async fn write_value(&self, store: &SharedStore, value: Value) -> Result<(), AgentFlowError> {
+        match &self.target {
+            Target::Key(key) => {
+                store.write().await.insert(key.clone(), value);
+            }
+            Target::LastMessage => {
+                let mut conversation = Conversation::load(store).await?;
+                if let Some(last) = conversation.messages.last_mut() {
+                    let text = match value {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    last.content
+                        .retain(|part| !matches!(part, ContentPart::Text { .. }));
+                    last.content.insert(0, ContentPart::Text { text });
+                }
+                conversation.save(store).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run every check and apply its policy.
+    ///
+    /// # Errors
+    ///
+    /// - [`AgentFlowError::InvalidOutput`] if a violation blocks the run.
+    /// - Errors from checks that could not run, or from reading the store.
+    #[instrument(name = "guardrails.run", skip_all, fields(target = %self.target_name(), checks = self.checks.len()))]
+    pub async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let Some(original) = self.read_value(&store).await? else {
+            debug!("Guardrails: nothing to check");
+            return Ok(store);
+        };
+        let mut value = original.clone();
+        let mut violations = Vec::new();
+        let mut blocked = Vec::new();
+        let mut route = None;
+
+        for (check, on_violation) in &self.checks {
+            let Some(finding) = check.check(&value).await? else {
+                continue;
+            };
+            let action = match (on_violation, finding.redacted) {
+                (OnViolation::Redact, Some(redacted)) => {
+                    value = redacted;
+                    OnViolation::Redact
+                }
+                (OnViolation::Redact, None) | (OnViolation::Block, _) => {
+                    blocked.push(format!("{}: {}", check.name(), finding.message));
+                    OnViolation::Block
+                }
+                (OnViolation::Route(action), _) => {
+                    route.get_or_insert_with(|| action.clone());
+                    on_violation.clone()
+                }
+            };
+            warn!(guardrail = check.name(), message = %finding.message, ?action, "Guardrail violation");
+            violations.push(Violation {
+                guardrail: check.name().to_string(),
+                target: self.target_name().to_string(),
+                message: finding.message,
+                action,
+            });
+        }
+
+        if !violations.is_empty() {
+            record(&store, &violations).await?;
+        }
+        if !blocked.is_empty() {
+            return Err(AgentFlowError::InvalidOutput(format!(
+                "Guardrails blocked '{}': {}",
+                self.target_name(),
+                blocked.join("; ")
+            )));
+        }
+        if value != original {
+            self.write_value(&store, value).await?;
+        }
+        if let Some(action) = route {
+            store
+                .write()
+                .await
+                .insert("action".to_string(), Value::String(action));
+        }
+        Ok(store)
+    }
+}
+
+async fn record(store: &SharedStore, violations: &[Violation]) -> Result<(), AgentFlowError> {
+    let mut records = Vec::with_capacity(violations.len());
+    for violation in violations {
+        records.push(serde_json::to_value(violation)?);
+    }
+    let mut guard = store.write().await;
+    match guard.get_mut(VIOLATIONS_KEY) {
+        Some(Value::Array(existing)) => existing.extend(records),
+        _ => {
+            guard.insert(VIOLATIONS_KEY.to_string(), Value::Array(records));
+        }
+    }
+    Ok(())
+}
+
+impl NodeResult<SharedStore, SharedStore> for Guardrails {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
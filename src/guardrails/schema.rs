@@ -0,0 +1,333 @@
+//! Minimal JSON Schema validation for [`JsonSchema`](super::JsonSchema).
+//!
+//! Supports the keywords LLM output schemas use in practice: `type`, `enum`,
+//! `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `minItems`, `maxItems`, `minLength`, `maxLength`, `pattern`, `minimum`,
+//! `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `allOf`, `anyOf` and
+//! `oneOf`. Annotations such as `title`, `description`, `default` and
+//! `format` are accepted but not checked. Any other keyword, including
+//! `$ref`, makes every value fail, since it could not be honoured.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+/// Keywords that constrain the value.
+const ASSERTIONS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "allOf",
+    "anyOf",
+    "oneOf",
+];
+
+/// Keywords that only describe the value.
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "format",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+/// Validate `value` against `schema`, returning one message per problem,
+/// each prefixed with the JSON Pointer of the offending value.
+///
+/// If `schema` uses an unsupported keyword, the value is not checked and
+/// the messages name each such keyword by its pointer in the schema.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    unsupported(schema, "", &mut errors);
+    if errors.is_empty() {
+        check(schema, value, "", &mut errors);
+    }
+    errors
+}
+
+fn unsupported(schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+    for (key, sub) in schema {
+        let sub_path = format!("{}/{}", path, escape(key));
+        match key.as_str() {
+            "properties" => {
+                for (name, sub) in sub.as_object().into_iter().flatten() {
+                    unsupported(sub, &format!("{}/{}", sub_path, escape(name)), errors);
+                }
+            }
+            "additionalProperties" | "items" => unsupported(sub, &sub_path, errors),
+            "allOf" | "anyOf" | "oneOf" => {
+                for (i, sub) in sub.as_array().into_iter().flatten().enumerate() {
+                    unsupported(sub, &format!("{}/{}", sub_path, i), errors);
+                }
+            }
+            key if ASSERTIONS.contains(&key) || ANNOTATIONS.contains(&key) => {}
+            key => errors.push(format!(
+                "schema {}: unsupported keyword '{}'",
+                at(path),
+                key
+            )),
+        }
+    }
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value is allowed", at(path)));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| has_type(value, t)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                at(path),
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of {}",
+                at(path),
+                value,
+                Value::Array(options.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}", at(path), expected));
+        }
+    }
+
+    match value {
+        Value::Object(object) => check_object(schema, object, path, errors),
+        Value::Array(items) => check_array(schema, items, path, errors),
+        Value::String(s) => check_string(schema, s, path, errors),
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64() {
+                check_number(schema, n, path, errors);
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            check(sub, value, path, errors);
+        }
+    }
+    if let Some(Value::Array(any)) = schema.get("anyOf") {
+        if !any
+            .iter()
+            .any(|sub| validate_at(sub, value, path).is_empty())
+        {
+            errors.push(format!("{}: matches none of anyOf", at(path)));
+        }
+    }
+    if let Some(Value::Array(one)) = schema.get("oneOf") {
+        let matches = one
+            .iter()
+            .filter(|sub| validate_at(sub, value, path).is_empty())
+            .count();
+        if matches != 1 {
+            errors.push(format!(
+                "{}: matches {} of oneOf, expected 1",
+                at(path),
+                matches
+            ));
+        }
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, path, &mut errors);
+    errors
+}
+
+fn check_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for key in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(key) {
+                errors.push(format!("{}: missing required property '{}'", at(path), key));
+            }
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (key, child) in object {
+        let child_path = format!("{}/{}", path, escape(key));
+        match properties.and_then(|p| p.get(key)) {
+            Some(sub) => check(sub, child, &child_path, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{}: unexpected property '{}'", at(path), key));
+                }
+                Some(sub @ Value::Object(_)) => check(sub, child, &child_path, errors),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn check_array(schema: &Map<String, Value>, items: &[Value], path: &str, errors: &mut Vec<String>) {
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if (items.len() as u64) < min {
+            errors.push(format!(
+                "{}: expected at least {} items, got {}",
+                at(path),
+                min,
+                items.len()
+            ));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if (items.len() as u64) > max {
+            errors.push(format!(
+                "{}: expected at most {} items, got {}",
+                at(path),
+                max,
+                items.len()
+            ));
+        }
+    }
+    if let Some(sub) = schema.get("items") {
+        for (i, item) in items.iter().enumerate() {
+            check(sub, item, &format!("{}/{}", path, i), errors);
+        }
+    }
+}
+
+fn check_string(schema: &Map<String, Value>, s: &str, path: &str, errors: &mut Vec<String>) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if len < min {
+            errors.push(format!("{}: shorter than {} characters", at(path), min));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if len > max {
+            errors.push(format!("{}: longer than {} characters", at(path), max));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        match Regex::new(pattern) {
+            Ok(re) if !re.is_match(s) => {
+                errors.push(format!(
+                    "{}: does not match pattern '{}'",
+                    at(path),
+                    pattern
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => errors.push(format!(
+                "{}: invalid pattern '{}': {}",
+                at(path),
+                pattern,
+                e
+            )),
+        }
+    }
+}
+
+fn check_number(schema: &Map<String, Value>, n: f64, path: &str, errors: &mut Vec<String>) {
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum") {
+        if n < min {
+            errors.push(format!("{}: {} is less than {}", at(path), n, min));
+        }
+    }
+    if let Some(max) = bound("maximum") {
+        if n > max {
+            errors.push(format!("{}: {} is greater than {}", at(path), n, max));
+        }
+    }
+    if let Some(min) = bound("exclusiveMinimum") {
+        if n <= min {
+            errors.push(format!("{}: {} is not greater than {}", at(path), n, min));
+        }
+    }
+    if let Some(max) = bound("exclusiveMaximum") {
+        if n >= max {
+            errors.push(format!("{}: {} is not less than {}", at(path), n, max));
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Escape `key` as a JSON Pointer segment.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn at(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
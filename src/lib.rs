@@ -9,7 +9,8 @@
 //! ## Design philosophy
 //!
 //! - **Bring your own LLM** — AgentFlow handles orchestration; you supply the
-//!   LLM calls inside nodes (use `rig-core`, `async-openai`, or any HTTP client).
+//!   LLM calls inside nodes (use `rig-core`, `async-openai`, any HTTP client,
+//!   or an [`llm::LlmClient`]).
 //! - **Graph + Shared Store** — every pattern is built on a directed graph of
 //!   [`Node`]s communicating through a [`SharedStore`].
 //! - **Composable** — primitives snap together: a [`Flow`] can contain a
@@ -59,18 +60,29 @@
 //! | `mcp` | MCP stdio server (implies `skills`) |
 //! | `rag` | Qdrant-backed retrieval |
 //! | `repl` | Interactive REPL / TUI via `inquire` |
+//! | `openai` | `OpenAiClient` for OpenAI-compatible chat endpoints |
 //!
 //! ## Crate layout
 //!
-//! - [`core`] — [`Node`], [`Flow`], [`SharedStore`], [`Store`],
+//! - [`core`] — [`Node`], [`Flow`], [`Handoff`], [`SharedStore`], [`Store`],
 //!   [`TypedStore`], [`TypedFlow`], [`Batch`], [`crate::core::error::AgentFlowError`]
 //! - [`patterns`] — [`Agent`], [`Workflow`], [`MultiAgent`], [`Rag`],
-//!   [`MapReduce`], [`StructuredOutput`], [`BatchFlow`], [`RpiWorkflow`]
+//!   [`MapReduce`], [`StructuredOutput`], [`BatchFlow`], [`RpiWorkflow`],
+//!   [`ReactAgent`], [`Reflection`], [`PlanExecute`], [`Router`],
+//!   [`Supervisor`], [`GroupChat`]
+//! - [`guardrails`] — [`Guardrails`](guardrails::Guardrails) node with PII,
+//!   regex, length, banned-term, JSON Schema and custom checks
+//! - [`llm`] — [`LlmClient`](llm::LlmClient) trait, [`MockLlm`](llm::MockLlm),
+//!   [`llm_node`](llm::llm_node)
 //! - [`utils`] — shell tool nodes
+//! - [`macro@FlowAction`], [`macro@typed_node`], [`typed_flow!`] — macros from
+//!   `agentflow-macros` for declaring typed flows
 //! - `skills` *(feature)* — skill file parser, YAML skill definitions
 //! - `mcp` *(feature)* — MCP server
 
 pub mod core;
+pub mod guardrails;
+pub mod llm;
 pub mod patterns;
 pub mod utils;
 
@@ -85,43 +97,77 @@ pub mod mcp;
 /// Convenience re-exports — import everything you need with `use agentflow::prelude::*`.
 pub mod prelude {
     pub use crate::core::batch::{Batch, ParallelBatch};
+    pub use crate::core::conversation::{ChatMessage, Conversation, Role};
     pub use crate::core::error::AgentFlowError;
     pub use crate::core::flow::Flow;
+    pub use crate::core::handoff::Handoff;
     pub use crate::core::node::{
         create_batch_node, create_diff_node, create_node, create_result_node, Node, NodeResult,
         ResultNode, SharedStore, SimpleNode, StateDiff,
     };
     pub use crate::core::parallel::ParallelFlow;
+    pub use crate::core::retry::RetryPolicy;
     pub use crate::core::store::Store;
-    pub use crate::core::typed_flow::{create_typed_node, SimpleTypedNode, TypedFlow, TypedNode};
+    pub use crate::core::typed_flow::{
+        create_typed_node, create_typed_result_node, FlowAction, SimpleTypedNode, TypedFlow,
+        TypedNode, TypedNodeResult, TypedResultNode,
+    };
     pub use crate::core::typed_store::TypedStore;
+    pub use crate::guardrails::{Guardrails, OnViolation};
+    pub use crate::llm::{llm_node, LlmClient, LlmRequest, LlmResponse, MockLlm};
     pub use crate::patterns::agent::Agent;
     pub use crate::patterns::batchflow::BatchFlow;
+    pub use crate::patterns::group_chat::GroupChat;
     pub use crate::patterns::mapreduce::MapReduce;
-    pub use crate::patterns::multi_agent::{MergeStrategy, MultiAgent};
+    pub use crate::patterns::multi_agent::{FailurePolicy, MergeStrategy, MultiAgent};
+    pub use crate::patterns::plan_execute::{Plan, PlanExecute, PlanStep};
     pub use crate::patterns::rag::Rag;
+    pub use crate::patterns::react::ReactAgent;
+    pub use crate::patterns::reflection::Reflection;
+    pub use crate::patterns::router::Router;
     pub use crate::patterns::rpi::RpiWorkflow;
     pub use crate::patterns::structured_output::StructuredOutput;
+    pub use crate::patterns::supervisor::Supervisor;
     pub use crate::patterns::workflow::Workflow;
-    pub use crate::utils::tool::{create_corrective_retry_node, ToolEntry, ToolRegistry};
+    pub use crate::utils::tool::{
+        create_corrective_retry_node, create_corrective_retry_node_with_policy, ToolEntry,
+        ToolRegistry,
+    };
+    pub use agentflow_macros::{typed_flow, typed_node, FlowAction};
 }
 
 // Direct exports to match a flat namespace
 pub use crate::core::batch::{Batch, ParallelBatch};
+pub use crate::core::conversation::{ChatMessage, Conversation, Role};
 pub use crate::core::flow::Flow;
+pub use crate::core::handoff::Handoff;
 pub use crate::core::node::{
     create_batch_node, create_diff_node, create_node, create_result_node, Node, NodeResult,
     ResultNode, SharedStore, SimpleNode, StateDiff,
 };
 pub use crate::core::parallel::ParallelFlow;
+pub use crate::core::retry::RetryPolicy;
 pub use crate::core::store::Store;
-pub use crate::core::typed_flow::{create_typed_node, SimpleTypedNode, TypedFlow, TypedNode};
+pub use crate::core::typed_flow::{
+    create_typed_node, create_typed_result_node, FlowAction, SimpleTypedNode, TypedFlow, TypedNode,
+    TypedNodeResult, TypedResultNode,
+};
 pub use crate::core::typed_store::TypedStore;
+pub use crate::guardrails::{Guardrails, OnViolation};
 pub use crate::patterns::agent::Agent;
 pub use crate::patterns::batchflow::BatchFlow;
+pub use crate::patterns::group_chat::GroupChat;
 pub use crate::patterns::mapreduce::MapReduce;
-pub use crate::patterns::multi_agent::{MergeStrategy, MultiAgent};
+pub use crate::patterns::multi_agent::{FailurePolicy, MergeStrategy, MultiAgent};
+pub use crate::patterns::plan_execute::{Plan, PlanExecute, PlanStep};
 pub use crate::patterns::rag::Rag;
+pub use crate::patterns::react::ReactAgent;
+pub use crate::patterns::reflection::Reflection;
+pub use crate::patterns::router::Router;
 pub use crate::patterns::rpi::RpiWorkflow;
 pub use crate::patterns::structured_output::StructuredOutput;
+pub use crate::patterns::supervisor::Supervisor;
 pub use crate::patterns::workflow::Workflow;
+
+/// Derive and attribute macros for typed flows (from `agentflow-macros`).
+pub use agentflow_macros::{typed_flow, typed_node, FlowAction};
@@ -0,0 +1,170 @@
+use super::{response_chunks, LlmChunk, LlmClient, LlmFuture, LlmRequest, LlmResponse, LlmStream};
+use crate::core::conversation::{ChatMessage, ToolCall};
+use crate::core::error::AgentFlowError;
+use crate::core::sync::lock;
+use crate::core::telemetry::LlmUsage;
+use futures::stream::{self, StreamExt};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Scripted [`LlmClient`] for tests.
+///
+/// Replies are returned in the order they were scripted; once the script is
+/// exhausted every call fails with [`AgentFlowError::NodeFailure`]. Every
+/// request is recorded and can be inspected with [`requests`](Self::requests).
+///
+/// Token usage is estimated as one token per whitespace-separated word unless
+/// a reply sets it with [`with_usage`](Self::with_usage). Streaming yields the
+/// reply text word by word.
+///
+/// Clones share the script and the request log.
+///
+/// ```rust
+/// use agentflow::core::conversation::ToolCall;
+/// use agentflow::llm::{LlmClient, LlmRequest, MockLlm};
+/// use agentflow::prelude::*;
+/// use serde_json::json;
+///
+/// # #[tokio::main] async fn main() {
+/// let llm = MockLlm::new("mock")
+///     .with_tool_call(ToolCall::new("c1", "search", json!({"q": "rust"})))
+///     .with_reply("Rust is a language.");
+///
+/// let request = LlmRequest::new(Conversation::new().with_message(ChatMessage::user("rust?")));
+/// assert_eq!(llm.complete(request.clone()).await.unwrap().tool_calls()[0].name, "search");
+/// assert_eq!(llm.complete(request).await.unwrap().text(), "Rust is a language.");
+/// assert_eq!(llm.requests().len(), 2);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct MockLlm {
+    model: String,
+    script: Arc<Mutex<VecDeque<Scripted>>>,
+    requests: Arc<Mutex<Vec<LlmRequest>>>,
+}
+
+enum Scripted {
+    Reply {
+        message: ChatMessage,
+        usage: Option<(usize, usize)>,
+    },
+    Error(AgentFlowError),
+}
+
+impl MockLlm {
+    /// An empty script reporting `model` in its usage.
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            script: Arc::new(Mutex::new(VecDeque::new())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Queue a message as the next reply.
+    pub fn with_message(self, message: ChatMessage) -> Self {
+        lock(&self.script).push_back(Scripted::Reply {
+            message,
+            usage: None,
+        });
+        self
+    }
+
+    /// Queue a text reply.
+    pub fn with_reply(self, text: &str) -> Self {
+        self.with_message(ChatMessage::assistant(text))
+    }
+
+    /// Queue a reply that calls one tool.
+    pub fn with_tool_call(self, call: ToolCall) -> Self {
+        self.with_message(ChatMessage::assistant("").with_tool_call(call))
+    }
+
+    /// Queue a failure.
+    pub fn with_error(self, error: AgentFlowError) -> Self {
+        lock(&self.script).push_back(Scripted::Error(error));
+        self
+    }
+
+    /// Report `input_tokens` / `output_tokens` for the most recently queued
+    /// reply instead of the word-count estimate.
+    pub fn with_usage(self, input_tokens: usize, output_tokens: usize) -> Self {
+        if let Some(Scripted::Reply { usage, .. }) = lock(&self.script).back_mut() {
+            *usage = Some((input_tokens, output_tokens));
+        }
+        self
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<LlmRequest> {
+        lock(&self.requests).clone()
+    }
+
+    /// Number of scripted replies not yet consumed.
+    pub fn remaining(&self) -> usize {
+        lock(&self.script).len()
+    }
+
+    fn next(&self, request: LlmRequest) -> Result<LlmResponse, AgentFlowError> {
+        let input_tokens = request
+            .conversation
+            .messages
+            .iter()
+            .map(|m| word_count(&m.text()))
+            .sum();
+        lock(&self.requests).push(request);
+        match lock(&self.script).pop_front() {
+            Some(Scripted::Reply { message, usage }) => {
+                let (input, output) =
+                    usage.unwrap_or_else(|| (input_tokens, word_count(&message.text())));
+                let finish_reason = if message.tool_calls.is_empty() {
+                    "stop"
+                } else {
+                    "tool_calls"
+                };
+                Ok(LlmResponse {
+                    message,
+                    usage: LlmUsage::new(self.model.clone(), input, output),
+                    finish_reason: Some(finish_reason.to_string()),
+                })
+            }
+            Some(Scripted::Error(e)) => Err(e),
+            None => Err(AgentFlowError::NodeFailure(format!(
+                "MockLlm '{}' has no scripted reply left",
+                self.model
+            ))),
+        }
+    }
+}
+
+impl LlmClient for MockLlm {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_, LlmResponse> {
+        let result = self.next(request);
+        Box::pin(async move { result })
+    }
+
+    fn stream(&self, request: LlmRequest) -> LlmFuture<'_, LlmStream<'_>> {
+        let result = self.next(request);
+        Box::pin(async move {
+            let mut chunks = Vec::new();
+            for chunk in response_chunks(result?) {
+                match chunk {
+                    LlmChunk::Text(text) => chunks.extend(
+                        text.split_inclusive(' ')
+                            .map(|word| LlmChunk::Text(word.to_string())),
+                    ),
+                    other => chunks.push(other),
+                }
+            }
+            Ok(stream::iter(chunks.into_iter().map(Ok)).boxed())
+        })
+    }
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
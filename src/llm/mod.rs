@@ -0,0 +1,344 @@
+//! Provider-agnostic LLM client interface.
+//!
+//! AgentFlow still leaves the choice of provider to you, but nodes that only
+//! need "send a conversation, get a reply" can depend on the [`LlmClient`]
+//! trait instead of a concrete SDK:
+//!
+//! - [`MockLlm`] — scripted replies for offline tests.
+//! - `OpenAiClient` *(feature `openai`)* — any OpenAI-compatible
+//!   `/chat/completions` endpoint, including local servers.
+//! - [`llm_node`] — a node that sends `store["conversation"]` to a client,
+//!   appends the reply and reports token usage.
+//!
+//! # Example
+//!
+//! ```rust
+//! use agentflow::llm::{llm_node, MockLlm};
+//! use agentflow::prelude::*;
+//! use std::sync::Arc;
+//!
+//! # #[tokio::main] async fn main() {
+//! let llm = MockLlm::new("mock-1").with_reply("Paris.");
+//! let node = llm_node(Arc::new(llm));
+//!
+//! let store = Store::new().into_shared();
+//! Conversation::append(&store, ChatMessage::user("Capital of France?")).await.unwrap();
+//! let store = node.call(store).await.unwrap();
+//! assert_eq!(store.read().await["response"], "Paris.");
+//! # }
+//! ```
+
+mod mock;
+#[cfg(feature = "openai")]
+mod openai;
+
+pub use mock::MockLlm;
+#[cfg(feature = "openai")]
+pub use openai::OpenAiClient;
+
+use crate::core::conversation::{ChatMessage, Conversation, Role, ToolCall};
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, SharedStore};
+use crate::core::telemetry::{record_llm_usage, LlmUsage};
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+/// Boxed future returned by [`LlmClient`] methods.
+pub type LlmFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AgentFlowError>> + Send + 'a>>;
+
+/// Stream of incremental output returned by [`LlmClient::stream`].
+pub type LlmStream<'a> = Pin<Box<dyn Stream<Item = Result<LlmChunk, AgentFlowError>> + Send + 'a>>;
+
+/// A tool the model may call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolSpec {
+    /// Tool name, echoed in [`ToolCall::name`].
+    pub name: String,
+    /// What the tool does, shown to the model.
+    pub description: String,
+    /// JSON Schema of the arguments object.
+    pub parameters: Value,
+}
+
+impl ToolSpec {
+    /// A tool taking arguments described by the JSON Schema `parameters`.
+    pub fn new(name: &str, description: &str, parameters: Value) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+        }
+    }
+}
+
+/// Input of one chat completion.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmRequest {
+    /// The conversation so far.
+    pub conversation: Conversation,
+    /// Tools the model may call.
+    pub tools: Vec<ToolSpec>,
+    /// Sampling temperature; provider default if `None`.
+    pub temperature: Option<f32>,
+    /// Completion token limit; provider default if `None`.
+    pub max_tokens: Option<usize>,
+}
+
+impl LlmRequest {
+    /// A request to continue `conversation`.
+    pub fn new(conversation: Conversation) -> Self {
+        Self {
+            conversation,
+            ..Self::default()
+        }
+    }
+
+    /// Offer `tool` to the model.
+    pub fn with_tool(mut self, tool: ToolSpec) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Set the sampling temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the completion token limit.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+/// Output of one chat completion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmResponse {
+    /// The assistant message, possibly carrying [`ToolCall`]s.
+    pub message: ChatMessage,
+    /// Tokens used by the call.
+    pub usage: LlmUsage,
+    /// Provider's stop reason (`"stop"`, `"tool_calls"`, `"length"`, …).
+    pub finish_reason: Option<String>,
+}
+
+impl LlmResponse {
+    /// Text of the reply.
+    pub fn text(&self) -> String {
+        self.message.text()
+    }
+
+    /// Tool calls requested by the reply.
+    pub fn tool_calls(&self) -> &[ToolCall] {
+        &self.message.tool_calls
+    }
+}
+
+/// One increment of a streamed completion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmChunk {
+    /// More reply text.
+    Text(String),
+    /// A complete tool call.
+    ToolCall(ToolCall),
+    /// Final token usage; sent once, last.
+    Usage(LlmUsage),
+}
+
+/// A chat-completion backend.
+///
+/// Implement [`complete`](Self::complete); [`stream`](Self::stream) defaults
+/// to a single-shot stream built from it. Clients are shared as
+/// `Arc<dyn LlmClient>`.
+pub trait LlmClient: Send + Sync {
+    /// Model name reported in [`LlmUsage::model`].
+    fn model(&self) -> &str;
+
+    /// Run one completion.
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_, LlmResponse>;
+
+    /// Run one completion, yielding output as it arrives.
+    fn stream(&self, request: LlmRequest) -> LlmFuture<'_, LlmStream<'_>> {
+        Box::pin(async move {
+            let response = self.complete(request).await?;
+            Ok(stream::iter(response_chunks(response).into_iter().map(Ok)).boxed())
+        })
+    }
+}
+
+/// Split a finished response into the chunks a stream would have yielded.
+pub(crate) fn response_chunks(response: LlmResponse) -> Vec<LlmChunk> {
+    let mut chunks = Vec::new();
+    let text = response.message.text();
+    if !text.is_empty() {
+        chunks.push(LlmChunk::Text(text));
+    }
+    chunks.extend(
+        response
+            .message
+            .tool_calls
+            .into_iter()
+            .map(LlmChunk::ToolCall),
+    );
+    chunks.push(LlmChunk::Usage(response.usage));
+    chunks
+}
+
+/// Drain a stream into the [`LlmResponse`] it describes.
+///
+/// # Errors
+///
+/// Returns the first error yielded by the stream.
+pub async fn collect_stream(
+    mut stream: LlmStream<'_>,
+    model: &str,
+) -> Result<LlmResponse, AgentFlowError> {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    let mut usage = LlmUsage::new(model, 0, 0);
+    while let Some(chunk) = stream.next().await {
+        match chunk? {
+            LlmChunk::Text(delta) => text.push_str(&delta),
+            LlmChunk::ToolCall(call) => tool_calls.push(call),
+            LlmChunk::Usage(u) => usage = u,
+        }
+    }
+    let finish_reason = if tool_calls.is_empty() {
+        "stop"
+    } else {
+        "tool_calls"
+    };
+    let mut message = ChatMessage::assistant(text);
+    message.tool_calls = tool_calls;
+    Ok(LlmResponse {
+        message,
+        usage,
+        finish_reason: Some(finish_reason.to_string()),
+    })
+}
+
+/// Node that continues `store["conversation"]` with an [`LlmClient`].
+///
+/// Each call loads the [`Conversation`], sends it (windowed, if configured),
+/// then:
+///
+/// - appends the assistant reply, tool calls included, to the conversation;
+/// - writes the reply text to `store["response"]`;
+/// - records the call under [`"llm_usage"`](crate::core::telemetry::LLM_USAGE_KEY),
+///   which a [`Flow`](crate::core::flow::Flow) attributes to this node in
+///   the run's [`FlowContext`](crate::core::telemetry::FlowContext).
+///
+/// Create one with [`llm_node`].
+#[derive(Clone)]
+pub struct LlmNode {
+    client: Arc<dyn LlmClient>,
+    tools: Vec<ToolSpec>,
+    temperature: Option<f32>,
+    max_tokens: Option<usize>,
+    window: Option<usize>,
+    system: Option<String>,
+}
+
+/// Create an [`LlmNode`] backed by `client`.
+pub fn llm_node(client: Arc<dyn LlmClient>) -> LlmNode {
+    LlmNode {
+        client,
+        tools: Vec::new(),
+        temperature: None,
+        max_tokens: None,
+        window: None,
+        system: None,
+    }
+}
+
+impl LlmNode {
+    /// Offer `tool` to the model.
+    pub fn with_tool(mut self, tool: ToolSpec) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Set the sampling temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the completion token limit.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Send only the system prompt and the last `n` messages; see
+    /// [`Conversation::window`].
+    pub fn with_window(mut self, n: usize) -> Self {
+        self.window = Some(n);
+        self
+    }
+
+    /// Prepend `prompt` as a system message when the conversation has none.
+    /// The stored conversation is left untouched.
+    pub fn with_system_prompt(mut self, prompt: &str) -> Self {
+        self.system = Some(prompt.to_string());
+        self
+    }
+
+    fn request(&self, mut conversation: Conversation) -> LlmRequest {
+        if let Some(n) = self.window {
+            conversation.trim(n);
+        }
+        if let Some(prompt) = &self.system {
+            if conversation.messages.first().map(|m| m.role) != Some(Role::System) {
+                conversation
+                    .messages
+                    .insert(0, ChatMessage::system(prompt.clone()));
+            }
+        }
+        LlmRequest {
+            conversation,
+            tools: self.tools.clone(),
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+        }
+    }
+
+    #[instrument(name = "llm_node.call", skip_all, fields(model = %self.client.model()))]
+    async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let mut conversation = Conversation::load(&store).await?;
+        let response = self
+            .client
+            .complete(self.request(conversation.clone()))
+            .await?;
+        debug!(
+            input_tokens = response.usage.input_tokens,
+            output_tokens = response.usage.output_tokens,
+            tool_calls = response.message.tool_calls.len(),
+            "LLM replied"
+        );
+        let text = response.text();
+        conversation.push(response.message);
+        conversation.save(&store).await?;
+        store
+            .write()
+            .await
+            .insert("response".to_string(), Value::String(text));
+        record_llm_usage(&store, response.usage).await;
+        Ok(store)
+    }
+}
+
+impl NodeResult<SharedStore, SharedStore> for LlmNode {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
@@ -0,0 +1,440 @@
+use super::{LlmChunk, LlmClient, LlmFuture, LlmRequest, LlmResponse, LlmStream};
+use crate::core::conversation::{ChatMessage, ContentPart, Role, ToolCall};
+use crate::core::error::AgentFlowError;
+use crate::core::secret::SecretString;
+use crate::core::telemetry::LlmUsage;
+use futures::stream::{self, StreamExt};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Default endpoint used by [`OpenAiClient::from_env`].
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// [`LlmClient`] for OpenAI-compatible `/chat/completions` endpoints.
+///
+/// Works with OpenAI itself and with servers that mimic its API (vLLM,
+/// Ollama, LM Studio, llama.cpp, a test stub, …). Requires the `openai`
+/// feature.
+///
+/// HTTP failures map onto [`AgentFlowError`]: `429` becomes
+/// [`RateLimited`](AgentFlowError::RateLimited) with the `Retry-After` hint,
+/// `408`/`504` and client-side timeouts become
+/// [`Timeout`](AgentFlowError::Timeout), a body that is not a completion
+/// becomes [`InvalidOutput`](AgentFlowError::InvalidOutput), and anything else
+/// [`NodeFailure`](AgentFlowError::NodeFailure).
+///
+/// ```rust,no_run
+/// use agentflow::llm::{llm_node, OpenAiClient};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let local = OpenAiClient::new("http://localhost:11434/v1", "llama3.1")
+///     .with_timeout(Duration::from_secs(60));
+/// let hosted = OpenAiClient::from_env("gpt-4o-mini");
+/// let node = llm_node(Arc::new(hosted));
+/// ```
+#[derive(Clone)]
+pub struct OpenAiClient {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<SecretString>,
+    timeout: Option<Duration>,
+}
+
+impl OpenAiClient {
+    /// A client for `model` at `base_url` (e.g. `http://localhost:8000/v1`),
+    /// without an API key.
+    pub fn new(base_url: &str, model: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: None,
+            timeout: None,
+        }
+    }
+
+    /// A client configured from `OPENAI_API_KEY` and, if set,
+    /// `OPENAI_BASE_URL`.
+    pub fn from_env(model: &str) -> Self {
+        let base_url =
+            std::env::var("OPENAI_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        let client = Self::new(&base_url, model);
+        match std::env::var("OPENAI_API_KEY") {
+            Ok(key) => client.with_api_key(SecretString::new(key)),
+            Err(_) => client,
+        }
+    }
+
+    /// Send `key` as a bearer token.
+    pub fn with_api_key(mut self, key: SecretString) -> Self {
+        self.api_key = Some(key);
+        self
+    }
+
+    /// Fail requests that take longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn body(&self, request: &LlmRequest, stream: bool) -> Value {
+        let mut body = Map::new();
+        body.insert("model".into(), json!(self.model));
+        body.insert(
+            "messages".into(),
+            Value::Array(
+                request
+                    .conversation
+                    .messages
+                    .iter()
+                    .map(message_to_openai)
+                    .collect(),
+            ),
+        );
+        if !request.tools.is_empty() {
+            let tools = request
+                .tools
+                .iter()
+                .map(|t| {
+                    json!({
+                        "type": "function",
+                        "function": {
+                            "name": t.name,
+                            "description": t.description,
+                            "parameters": t.parameters,
+                        }
+                    })
+                })
+                .collect();
+            body.insert("tools".into(), Value::Array(tools));
+        }
+        if let Some(t) = request.temperature {
+            body.insert("temperature".into(), json!(t));
+        }
+        if let Some(n) = request.max_tokens {
+            body.insert("max_tokens".into(), json!(n));
+        }
+        if stream {
+            body.insert("stream".into(), json!(true));
+            body.insert("stream_options".into(), json!({"include_usage": true}));
+        }
+        Value::Object(body)
+    }
+
+    async fn send(&self, body: Value) -> Result<reqwest::Response, AgentFlowError> {
+        let url = format!("{}/chat/completions", self.base_url);
+        debug!(url = %url, model = %self.model, "OpenAiClient: sending request");
+        let mut builder = self
+            .http
+            .post(&url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&body)?);
+        if let Some(key) = &self.api_key {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {}", key.expose_secret()));
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let response = builder.send().await.map_err(transport_error)?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            // Negative, NaN or huge values are ignored rather than panicking.
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+        let text = response.text().await.unwrap_or_default();
+        let message = format!("{} returned HTTP {}: {}", url, status.as_u16(), text);
+        warn!(status = status.as_u16(), "OpenAiClient: request failed");
+        Err(match status.as_u16() {
+            429 => AgentFlowError::RateLimited {
+                message,
+                retry_after,
+            },
+            408 | 504 => AgentFlowError::Timeout(message),
+            _ => AgentFlowError::NodeFailure(message),
+        })
+    }
+}
+
+impl LlmClient for OpenAiClient {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete(&self, request: LlmRequest) -> LlmFuture<'_, LlmResponse> {
+        Box::pin(async move {
+            let response = self.send(self.body(&request, false)).await?;
+            let bytes = response.bytes().await.map_err(transport_error)?;
+            let body: Value = serde_json::from_slice(&bytes)
+                .map_err(|e| AgentFlowError::InvalidOutput(format!("completion body: {e}")))?;
+            parse_completion(&body, &self.model)
+        })
+    }
+
+    fn stream(&self, request: LlmRequest) -> LlmFuture<'_, LlmStream<'_>> {
+        Box::pin(async move {
+            let response = self.send(self.body(&request, true)).await?;
+            let state = SseState {
+                response,
+                buffer: Vec::new(),
+                pending: VecDeque::new(),
+                tool_calls: BTreeMap::new(),
+                usage: LlmUsage::new(self.model.clone(), 0, 0),
+                finished: false,
+            };
+            Ok(stream::unfold(state, SseState::next).boxed())
+        })
+    }
+}
+
+fn transport_error(e: reqwest::Error) -> AgentFlowError {
+    if e.is_timeout() {
+        AgentFlowError::Timeout(e.to_string())
+    } else {
+        AgentFlowError::NodeFailure(e.to_string())
+    }
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// Convert a [`ChatMessage`] to the OpenAI wire format.
+fn message_to_openai(message: &ChatMessage) -> Value {
+    let mut out = Map::new();
+    out.insert("role".into(), json!(role_name(message.role)));
+    let text_only = message
+        .content
+        .iter()
+        .all(|p| matches!(p, ContentPart::Text { .. }));
+    let content = if text_only {
+        let text = message.text();
+        if text.is_empty() && !message.tool_calls.is_empty() {
+            Value::Null
+        } else {
+            Value::String(text)
+        }
+    } else {
+        Value::Array(message.content.iter().map(part_to_openai).collect())
+    };
+    out.insert("content".into(), content);
+    if let Some(name) = &message.name {
+        out.insert("name".into(), json!(name));
+    }
+    if let Some(id) = &message.tool_call_id {
+        out.insert("tool_call_id".into(), json!(id));
+    }
+    if !message.tool_calls.is_empty() {
+        let calls = message
+            .tool_calls
+            .iter()
+            .map(|c| {
+                json!({
+                    "id": c.id,
+                    "type": "function",
+                    "function": {"name": c.name, "arguments": c.arguments.to_string()},
+                })
+            })
+            .collect();
+        out.insert("tool_calls".into(), Value::Array(calls));
+    }
+    Value::Object(out)
+}
+
+fn part_to_openai(part: &ContentPart) -> Value {
+    match part {
+        ContentPart::Text { text } => json!({"type": "text", "text": text}),
+        ContentPart::ImageUrl { url } => json!({"type": "image_url", "image_url": {"url": url}}),
+        ContentPart::Data { mime_type, data } => match mime_type.strip_prefix("audio/") {
+            Some(format) => json!({
+                "type": "input_audio",
+                "input_audio": {"data": data, "format": format},
+            }),
+            None => json!({
+                "type": "file",
+                "file": {"file_data": format!("data:{mime_type};base64,{data}")},
+            }),
+        },
+    }
+}
+
+/// Tool-call arguments arrive as a JSON string; keep the raw string if it
+/// does not parse.
+fn parse_arguments(raw: &str) -> Value {
+    if raw.trim().is_empty() {
+        return json!({});
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn parse_usage(usage: &Value, model: &str) -> LlmUsage {
+    let count = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0) as usize;
+    LlmUsage::new(model, count("prompt_tokens"), count("completion_tokens"))
+}
+
+fn parse_completion(body: &Value, model: &str) -> Result<LlmResponse, AgentFlowError> {
+    let choice = body.get("choices").and_then(|c| c.get(0)).ok_or_else(|| {
+        AgentFlowError::InvalidOutput(format!("completion has no choices: {body}"))
+    })?;
+    let raw = choice.get("message").cloned().unwrap_or(Value::Null);
+    let mut message =
+        ChatMessage::assistant(raw.get("content").and_then(Value::as_str).unwrap_or(""));
+    if let Some(calls) = raw.get("tool_calls").and_then(Value::as_array) {
+        for call in calls {
+            let function = call.get("function").cloned().unwrap_or(Value::Null);
+            message.tool_calls.push(ToolCall::new(
+                call.get("id").and_then(Value::as_str).unwrap_or(""),
+                function.get("name").and_then(Value::as_str).unwrap_or(""),
+                parse_arguments(
+                    function
+                        .get("arguments")
+                        .and_then(Value::as_str)
+                        .unwrap_or(""),
+                ),
+            ));
+        }
+    }
+    Ok(LlmResponse {
+        message,
+        usage: parse_usage(body.get("usage").unwrap_or(&Value::Null), model),
+        finish_reason: choice
+            .get("finish_reason")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// A tool call being assembled from streamed fragments.
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Server-sent-events decoder state for [`OpenAiClient::stream`].
+struct SseState {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+    pending: VecDeque<LlmChunk>,
+    tool_calls: BTreeMap<u64, PartialToolCall>,
+    usage: LlmUsage,
+    finished: bool,
+}
+
+impl SseState {
+    async fn next(mut self) -> Option<(Result<LlmChunk, AgentFlowError>, Self)> {
+        loop {
+            if let Some(chunk) = self.pending.pop_front() {
+                return Some((Ok(chunk), self));
+            }
+            if self.finished {
+                return None;
+            }
+            if let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                if let Err(e) = self.handle_line(line.trim()) {
+                    self.finished = true;
+                    return Some((Err(e), self));
+                }
+                continue;
+            }
+            match self.response.chunk().await {
+                Ok(Some(bytes)) => self.buffer.extend_from_slice(&bytes),
+                Ok(None) => {
+                    let rest = std::mem::take(&mut self.buffer);
+                    let rest = String::from_utf8_lossy(&rest);
+                    if let Err(e) = self.handle_line(rest.trim()) {
+                        self.finished = true;
+                        return Some((Err(e), self));
+                    }
+                    self.finish();
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some((Err(transport_error(e)), self));
+                }
+            }
+        }
+    }
+
+    fn handle_line(&mut self, line: &str) -> Result<(), AgentFlowError> {
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(());
+        };
+        let data = data.trim();
+        if data == "[DONE]" {
+            self.finish();
+            return Ok(());
+        }
+        let event: Value = serde_json::from_str(data)
+            .map_err(|e| AgentFlowError::InvalidOutput(format!("stream event '{data}': {e}")))?;
+        if let Some(error) = event.get("error") {
+            return Err(AgentFlowError::NodeFailure(format!(
+                "stream error: {error}"
+            )));
+        }
+        if let Some(usage) = event.get("usage").filter(|u| !u.is_null()) {
+            self.usage = parse_usage(usage, &self.usage.model);
+        }
+        let Some(delta) = event.pointer("/choices/0/delta") else {
+            return Ok(());
+        };
+        if let Some(text) = delta.get("content").and_then(Value::as_str) {
+            if !text.is_empty() {
+                self.pending.push_back(LlmChunk::Text(text.to_string()));
+            }
+        }
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+            let partial = self.tool_calls.entry(index).or_default();
+            if let Some(id) = call.get("id").and_then(Value::as_str) {
+                partial.id.push_str(id);
+            }
+            if let Some(name) = call.pointer("/function/name").and_then(Value::as_str) {
+                partial.name.push_str(name);
+            }
+            if let Some(args) = call.pointer("/function/arguments").and_then(Value::as_str) {
+                partial.arguments.push_str(args);
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit the assembled tool calls and the usage, then stop.
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        for call in std::mem::take(&mut self.tool_calls).into_values() {
+            self.pending.push_back(LlmChunk::ToolCall(ToolCall::new(
+                &call.id,
+                &call.name,
+                parse_arguments(&call.arguments),
+            )));
+        }
+        self.pending.push_back(LlmChunk::Usage(self.usage.clone()));
+        self.finished = true;
+    }
+}
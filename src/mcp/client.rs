@@ -183,17 +183,22 @@ impl McpClient {
             }
         };
 
+        let name = name.into();
         let result = self
             .service
             .call_tool({
-                let mut params = CallToolRequestParams::new(name.into());
+                let mut params = CallToolRequestParams::new(name.clone());
                 if let Some(args) = arguments {
                     params = params.with_arguments(args);
                 }
                 params
             })
             .await
-            .map_err(|e| AgentFlowError::Custom(format!("MCP tools/call failed: {e}")))?;
+            .map_err(|e| AgentFlowError::ToolFailure {
+                tool: name,
+                exit_code: None,
+                stderr: format!("MCP tools/call failed: {e}"),
+            })?;
 
         Ok(McpCallResult {
             content: result.content.into_iter().map(content_to_json).collect(),
@@ -1,7 +1,10 @@
+use crate::core::conversation::CONVERSATION_KEY;
 use crate::core::error::AgentFlowError;
 use crate::core::node::{Node, NodeResult, SharedStore};
+use crate::core::retry::RetryPolicy;
 use std::future::Future;
 use std::pin::Pin;
+use std::time::Duration;
 use tracing::{debug, info, instrument, warn};
 
 /// Autonomous async decision-making unit with optional retry logic.
@@ -11,7 +14,7 @@ use tracing::{debug, info, instrument, warn};
 /// - **Retry on error key** ([`decide_shared`]) — reruns the inner node up to
 ///   `max_retries` times if the output store contains an `"error"` key.
 /// - **Typed retry** ([`decide_result`]) — works with [`NodeResult`] nodes,
-///   distinguishing transient ([`AgentFlowError::Timeout`]) from fatal errors.
+///   retrying only errors that are [retryable](AgentFlowError::is_retryable).
 ///
 /// # Choosing a method
 ///
@@ -19,8 +22,19 @@ use tracing::{debug, info, instrument, warn};
 /// |---|---|---|---|
 /// | [`decide_shared`] | [`Node`] | `"error"` key in store | any `"error"` key |
 /// | [`decide`] | [`Node`] | `"error"` key | any `"error"` key (plain `HashMap` convenience wrapper) |
-/// | [`run_result`] | [`NodeResult`] (uses `self.node`) | `Err(AgentFlowError)` | `Timeout` only |
-/// | [`decide_result`] | [`NodeResult`] (external `node` ref) | `Err(AgentFlowError)` | `Timeout` only |
+/// | [`run_result`] | [`NodeResult`] (uses `self.node`) | `Err(AgentFlowError)` | [`is_retryable`] errors |
+/// | [`decide_result`] | [`NodeResult`] (external `node` ref) | `Err(AgentFlowError)` | [`is_retryable`] errors |
+///
+/// [`with_retry`](Agent::with_retry) waits a fixed delay between attempts;
+/// [`with_policy`](Agent::with_policy) takes a [`RetryPolicy`] with
+/// exponential or jittered backoff, an elapsed-time cap and custom predicates.
+/// Both wait at least as long as a [`AgentFlowError::RateLimited`] error's
+/// `retry_after` hint.
+///
+/// With [`with_conversation_rollback`](Agent::with_conversation_rollback),
+/// turns a failed attempt appended to the shared
+/// [`Conversation`](crate::core::conversation::Conversation) are discarded
+/// before the next attempt, so retries don't pile up duplicate messages.
 ///
 /// # Example
 ///
@@ -46,6 +60,7 @@ use tracing::{debug, info, instrument, warn};
 /// [`decide`]: Agent::decide
 /// [`run_result`]: Agent::run_result
 /// [`decide_result`]: Agent::decide_result
+/// [`is_retryable`]: AgentFlowError::is_retryable
 #[derive(Clone)]
 pub struct Agent<N> {
     node: N,
@@ -53,6 +68,10 @@ pub struct Agent<N> {
     pub max_retries: usize,
     /// Milliseconds to wait between retry attempts.
     pub wait_millis: u64,
+    /// Retry policy; when set it replaces `max_retries` and `wait_millis`.
+    pub policy: Option<RetryPolicy>,
+    /// Restore `store["conversation"]` after each failed attempt.
+    pub conversation_rollback: bool,
 }
 
 impl<N> Agent<N> {
@@ -62,58 +81,150 @@ impl<N> Agent<N> {
             node,
             max_retries: 1,
             wait_millis: 0,
+            policy: None,
+            conversation_rollback: false,
         }
     }
 
     /// Create an agent with explicit retry settings.
     ///
-    /// - `max_retries` — total number of attempts (1 = no retries).
+    /// - `max_retries` — total number of attempts (1 = no retries). `0` is
+    ///   treated as `1`: the node always runs at least once. (Before retry
+    ///   policies, `0` skipped the node entirely.)
     /// - `wait_millis` — delay between attempts in milliseconds.
     pub fn with_retry(node: N, max_retries: usize, wait_millis: u64) -> Self {
         Self {
             node,
             max_retries,
             wait_millis,
+            policy: None,
+            conversation_rollback: false,
         }
     }
 
+    /// Create an agent that retries according to `policy`.
+    ///
+    /// The policy's predicates override the defaults in the table above:
+    /// [`retry_if`](RetryPolicy::retry_if) decides which errors
+    /// [`run_result`](Self::run_result) / [`decide_result`](Self::decide_result)
+    /// retry, and [`retry_if_output`](RetryPolicy::retry_if_output) decides
+    /// which output stores are retried (by all methods).
+    ///
+    /// ```rust,no_run
+    /// use agentflow::core::retry::RetryPolicy;
+    /// use agentflow::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// # let node = create_node(|s: SharedStore| async move { s });
+    /// let agent = Agent::with_policy(
+    ///     node,
+    ///     RetryPolicy::decorrelated_jitter(6, Duration::from_millis(250), Duration::from_secs(10))
+    ///         .with_max_elapsed(Duration::from_secs(30)),
+    /// );
+    /// ```
+    pub fn with_policy(node: N, policy: RetryPolicy) -> Self {
+        Self {
+            node,
+            max_retries: policy.max_attempts,
+            wait_millis: 0,
+            policy: Some(policy),
+            conversation_rollback: false,
+        }
+    }
+
+    /// Discard conversation turns added by failed attempts.
+    ///
+    /// Before the first attempt the agent snapshots the
+    /// [`Conversation`](crate::core::conversation::Conversation) under
+    /// `store["conversation"]`; whenever an attempt fails, that snapshot is
+    /// restored. Successful attempts keep whatever they appended.
+    pub fn with_conversation_rollback(mut self) -> Self {
+        self.conversation_rollback = true;
+        self
+    }
+
+    /// Snapshot the raw conversation value if rollback is enabled.
+    async fn conversation_snapshot(
+        &self,
+        store: &SharedStore,
+    ) -> Option<Option<serde_json::Value>> {
+        if !self.conversation_rollback {
+            return None;
+        }
+        Some(store.read().await.get(CONVERSATION_KEY).cloned())
+    }
+
+    /// Put a snapshot taken by [`conversation_snapshot`](Self::conversation_snapshot) back.
+    async fn restore_conversation(
+        snapshot: &Option<Option<serde_json::Value>>,
+        store: &SharedStore,
+    ) {
+        match snapshot {
+            None => {}
+            Some(Some(value)) => {
+                store
+                    .write()
+                    .await
+                    .insert(CONVERSATION_KEY.to_string(), value.clone());
+            }
+            Some(None) => {
+                store.write().await.remove(CONVERSATION_KEY);
+            }
+        }
+    }
+
+    /// The policy in effect: `self.policy`, or a fixed-delay policy built from
+    /// `max_retries` and `wait_millis`.
+    fn effective_policy(&self) -> RetryPolicy {
+        self.policy.clone().unwrap_or_else(|| {
+            RetryPolicy::fixed(self.max_retries, Duration::from_millis(self.wait_millis))
+        })
+    }
+
     /// Run the inner node with retry, operating on a [`SharedStore`].
     ///
-    /// Retries when the output store contains an `"error"` key. Returns the
-    /// last store produced (with or without `"error"`) after all attempts are
-    /// exhausted.
+    /// Retries when the output store contains an `"error"` key (or matches the
+    /// policy's output predicate). Returns the last store produced (with or
+    /// without `"error"`) after all attempts are exhausted.
     #[instrument(name = "agent.decide_shared", skip(self, shared_store), fields(max_retries = self.max_retries))]
     pub async fn decide_shared(&self, shared_store: SharedStore) -> SharedStore
     where
         N: Node<SharedStore, SharedStore> + Clone,
     {
-        let mut result_store = None;
-        for attempt in 0..self.max_retries {
+        let policy = self.effective_policy();
+        let mut retry = policy.start();
+        let snapshot = self.conversation_snapshot(&shared_store).await;
+        loop {
+            let attempt = retry.attempts();
             debug!(
                 attempt,
-                max_retries = self.max_retries,
+                max_retries = policy.max_attempts,
                 "Agent::decide_shared attempt"
             );
             let res = self.node.call(shared_store.clone()).await;
-            let has_error = {
+            let failed = {
                 let store = res.read().await;
-                store.contains_key("error")
+                policy.should_retry_output(&store, store.contains_key("error"))
             };
-            if !has_error {
+            if !failed {
                 info!(attempt, "Agent::decide_shared succeeded");
-                result_store = Some(res);
-                break;
+                return res;
             }
-            warn!(
-                attempt,
-                "Agent::decide_shared node returned error key; retrying"
-            );
-            result_store = Some(res);
-            if attempt < self.max_retries - 1 && self.wait_millis > 0 {
-                tokio::time::sleep(tokio::time::Duration::from_millis(self.wait_millis)).await;
+            Self::restore_conversation(&snapshot, &shared_store).await;
+            if !std::sync::Arc::ptr_eq(&res, &shared_store) {
+                Self::restore_conversation(&snapshot, &res).await;
+            }
+            match retry.next_delay(None) {
+                Some(delay) => {
+                    warn!(
+                        attempt,
+                        "Agent::decide_shared node returned error key; retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                None => return res,
             }
         }
-        result_store.unwrap_or(shared_store)
     }
 
     /// Convenience wrapper around [`decide_shared`] that accepts and returns
@@ -142,8 +253,9 @@ impl<N> Agent<N> {
     /// This is the ergonomic alternative to [`decide_result`]: it uses `self.node`
     /// directly so callers don't need to hold a redundant external reference.
     ///
-    /// - [`AgentFlowError::Timeout`] → **transient**: retried up to `max_retries` times.
-    /// - Any other [`AgentFlowError`] variant → **fatal**: returned immediately.
+    /// - [Retryable](AgentFlowError::is_retryable) errors (`Timeout`,
+    ///   `RateLimited`) → **transient**: retried up to `max_retries` times.
+    /// - Any other [`AgentFlowError`] → **fatal**: returned immediately.
     ///
     /// # Errors
     ///
@@ -156,39 +268,15 @@ impl<N> Agent<N> {
     where
         N: NodeResult<SharedStore, SharedStore> + Clone,
     {
-        let mut last_err = AgentFlowError::NodeFailure("No attempts made".to_string());
-        for attempt in 0..self.max_retries {
-            debug!(
-                attempt,
-                max_retries = self.max_retries,
-                "Agent::run_result attempt"
-            );
-            match self.node.call(input.clone()).await {
-                Ok(store) => {
-                    info!(attempt, "Agent::run_result succeeded");
-                    return Ok(store);
-                }
-                Err(AgentFlowError::Timeout(msg)) => {
-                    warn!(attempt, error = %msg, "Agent::run_result timeout; retrying");
-                    last_err = AgentFlowError::Timeout(msg);
-                    if attempt < self.max_retries - 1 && self.wait_millis > 0 {
-                        tokio::time::sleep(tokio::time::Duration::from_millis(self.wait_millis))
-                            .await;
-                    }
-                }
-                Err(other) => {
-                    warn!(attempt, error = %other, "Agent::run_result fatal error; aborting");
-                    return Err(other);
-                }
-            }
-        }
-        Err(last_err)
+        self.retry_result(&self.node, input, "Agent::run_result")
+            .await
     }
 
     /// Run a [`NodeResult`] node with retry, distinguishing transient from fatal errors.
     ///
-    /// - [`AgentFlowError::Timeout`] → **transient**: retried up to `max_retries` times.
-    /// - Any other [`AgentFlowError`] variant → **fatal**: returned immediately,
+    /// - [Retryable](AgentFlowError::is_retryable) errors (`Timeout`,
+    ///   `RateLimited`) → **transient**: retried up to `max_retries` times.
+    /// - Any other [`AgentFlowError`] → **fatal**: returned immediately,
     ///   no further retries.
     ///
     /// # Errors
@@ -204,33 +292,55 @@ impl<N> Agent<N> {
     where
         R: NodeResult<SharedStore, SharedStore> + Clone,
     {
-        let mut last_err = AgentFlowError::NodeFailure("No attempts made".to_string());
-        for attempt in 0..self.max_retries {
+        self.retry_result(node, input, "Agent::decide_result").await
+    }
+
+    /// Shared retry loop for [`run_result`](Self::run_result) and
+    /// [`decide_result`](Self::decide_result).
+    async fn retry_result<R>(
+        &self,
+        node: &R,
+        input: SharedStore,
+        label: &'static str,
+    ) -> Result<SharedStore, AgentFlowError>
+    where
+        R: NodeResult<SharedStore, SharedStore>,
+    {
+        let policy = self.effective_policy();
+        let mut retry = policy.start();
+        let snapshot = self.conversation_snapshot(&input).await;
+        loop {
+            let attempt = retry.attempts();
             debug!(
                 attempt,
-                max_retries = self.max_retries,
-                "Agent::decide_result attempt"
+                max_retries = policy.max_attempts,
+                "{} attempt",
+                label
             );
-            match node.call(input.clone()).await {
+            let (error, rejected) = match node.call(input.clone()).await {
                 Ok(store) => {
-                    info!(attempt, "Agent::decide_result succeeded");
-                    return Ok(store);
-                }
-                Err(AgentFlowError::Timeout(msg)) => {
-                    warn!(attempt, error = %msg, "Agent::decide_result timeout; retrying");
-                    last_err = AgentFlowError::Timeout(msg);
-                    if attempt < self.max_retries - 1 && self.wait_millis > 0 {
-                        tokio::time::sleep(tokio::time::Duration::from_millis(self.wait_millis))
-                            .await;
+                    if !policy.should_retry_output(&*store.read().await, false) {
+                        info!(attempt, "{} succeeded", label);
+                        return Ok(store);
                     }
+                    let e = AgentFlowError::InvalidOutput("Output rejected by retry policy".into());
+                    (e, true)
                 }
-                Err(other) => {
-                    warn!(attempt, error = %other, "Agent::decide_result fatal error; aborting");
-                    return Err(other);
+                Err(e) => (e, false),
+            };
+            Self::restore_conversation(&snapshot, &input).await;
+            if !rejected && !policy.should_retry_error(&error, error.is_retryable()) {
+                warn!(attempt, error = %error.report(), "{} fatal error; aborting", label);
+                return Err(error);
+            }
+            match retry.next_delay(Some(&error)) {
+                Some(delay) => {
+                    warn!(attempt, error = %error.report(), "{} transient error; retrying", label);
+                    tokio::time::sleep(delay).await;
                 }
+                None => return Err(error),
             }
         }
-        Err(last_err)
     }
 }
 
@@ -0,0 +1,371 @@
+use crate::core::conversation::{ChatMessage, Conversation, Role};
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, ResultNode, SharedStore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+/// Store key holding the [`GroupChat`] summary.
+pub const GROUP_CHAT_KEY: &str = "group_chat";
+
+/// Store key where a [selector](TurnOrder::Selector) writes the next speaker.
+pub const NEXT_SPEAKER_KEY: &str = "next_speaker";
+
+/// Store key listing the participant names for the selector.
+pub const PARTICIPANTS_KEY: &str = "participants";
+
+/// Store key where a [termination judge](GroupChat::with_judge) writes `true`
+/// to end the chat.
+pub const TERMINATE_KEY: &str = "terminate";
+
+/// How the next speaker is chosen.
+#[derive(Clone, Default)]
+pub enum TurnOrder {
+    /// Participants speak in registration order, wrapping around.
+    /// This is the default.
+    #[default]
+    RoundRobin,
+    /// The node runs before each turn with `store["participants"]` set and
+    /// writes a name to `store["next_speaker"]`. Missing or unknown names
+    /// fall back to round-robin.
+    Selector(ResultNode),
+    /// The first other participant `@mentioned` in the last message speaks
+    /// next; without a mention, round-robin. `@al` does not mention `al` in
+    /// `@alice`, and the longest matching name wins.
+    Mentions,
+}
+
+/// Why a [`GroupChat`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// A message contained the termination keyword.
+    Keyword,
+    /// The judge wrote `store["terminate"] = true`.
+    Judge,
+    /// `max_turns` turns were taken.
+    MaxTurns,
+}
+
+#[derive(Clone)]
+struct Participant {
+    name: String,
+    persona: Option<String>,
+    node: ResultNode,
+}
+
+/// Agents taking turns on a shared [`Conversation`].
+///
+/// Where [`MultiAgent`](super::multi_agent::MultiAgent) runs agents once,
+/// concurrently, `GroupChat` runs one speaker per turn — for debates,
+/// peer review or writer/critic pairs.
+///
+/// Each turn, the speaker's node sees `store["conversation"]` from its own
+/// point of view: its persona as the system prompt, its own earlier messages
+/// as assistant messages and everyone else's as named user messages
+/// (`"alice: …"`). Any node that appends an assistant message works, e.g.
+/// [`llm_node`](crate::llm::llm_node). The reply's text is then appended to
+/// the shared conversation as an assistant message named after the speaker.
+///
+/// The chat ends when a message contains the
+/// [termination keyword](Self::with_termination_keyword), when the
+/// [judge](Self::with_judge) says so, or after
+/// [`max_turns`](Self::with_max_turns) turns (default 10). The outcome is
+/// written to `store["group_chat"]` as `{turns, speakers, stop_reason}`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use agentflow::llm::{llm_node, LlmClient};
+/// use agentflow::patterns::group_chat::{GroupChat, TurnOrder};
+/// use agentflow::prelude::*;
+/// use std::sync::Arc;
+///
+/// # async fn run(client: Arc<dyn LlmClient>) -> Result<(), AgentFlowError> {
+/// let debate = GroupChat::new()
+///     .with_participant("pro", "Argue for the proposal. Be brief.", llm_node(client.clone()))
+///     .with_participant("con", "Argue against the proposal. Be brief.", llm_node(client.clone()))
+///     .with_participant("moderator", "Summarise and say DONE when settled.", llm_node(client))
+///     .with_turn_order(TurnOrder::RoundRobin)
+///     .with_termination_keyword("DONE")
+///     .with_max_turns(9);
+///
+/// let store = Store::new().into_shared();
+/// Conversation::append(&store, ChatMessage::user("Should we rewrite it in Rust?")).await?;
+/// let store = debate.run(store).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GroupChat {
+    participants: Vec<Participant>,
+    /// How the next speaker is chosen.
+    pub turn_order: TurnOrder,
+    /// Maximum number of turns.
+    pub max_turns: usize,
+    /// End the chat when a message contains this text.
+    pub termination_keyword: Option<String>,
+    judge: Option<ResultNode>,
+}
+
+impl GroupChat {
+    /// An empty round-robin chat limited to 10 turns.
+    pub fn new() -> Self {
+        Self {
+            participants: Vec::new(),
+            turn_order: TurnOrder::RoundRobin,
+            max_turns: 10,
+            termination_keyword: None,
+            judge: None,
+        }
+    }
+
+    /// Add a speaker called `name` whose view starts with the system prompt
+    /// `persona` (none if empty).
+    pub fn with_participant<N>(mut self, name: &str, persona: &str, node: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        self.participants.push(Participant {
+            name: name.to_string(),
+            persona: (!persona.is_empty()).then(|| persona.to_string()),
+            node: Box::new(node),
+        });
+        self
+    }
+
+    /// Set how the next speaker is chosen.
+    pub fn with_turn_order(mut self, turn_order: TurnOrder) -> Self {
+        self.turn_order = turn_order;
+        self
+    }
+
+    /// Stop after `max_turns` turns.
+    pub fn with_max_turns(mut self, max_turns: usize) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// Stop once a message contains `keyword`.
+    pub fn with_termination_keyword(mut self, keyword: &str) -> Self {
+        self.termination_keyword = Some(keyword.to_string());
+        self
+    }
+
+    /// Run `judge` after every turn; it ends the chat by writing
+    /// `store["terminate"] = true`.
+    pub fn with_judge<N>(mut self, judge: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        self.judge = Some(Box::new(judge));
+        self
+    }
+
+    /// Names of the participants, in registration order.
+    pub fn participant_names(&self) -> Vec<String> {
+        self.participants.iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// Take turns until a termination condition is met.
+    ///
+    /// # Errors
+    ///
+    /// - [`AgentFlowError::GraphBuildError`] if there are no participants.
+    /// - Errors from participant, selector or judge nodes, or from reading
+    ///   the conversation.
+    /// - [`AgentFlowError::InvalidOutput`] if a participant did not append an
+    ///   assistant message.
+    #[instrument(name = "group_chat.run", skip_all, fields(participants = self.participants.len(), max_turns = self.max_turns))]
+    pub async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        if self.participants.is_empty() {
+            return Err(AgentFlowError::GraphBuildError(
+                "GroupChat has no participants".into(),
+            ));
+        }
+        let t = Instant::now();
+        let mut store = store;
+        let mut speakers: Vec<String> = Vec::new();
+        let mut previous: Option<usize> = None;
+        let mut stop_reason = StopReason::MaxTurns;
+
+        for turn in 1..=self.max_turns {
+            let (next, s) = self.next_speaker(store, previous).await?;
+            store = s;
+            let speaker = &self.participants[next];
+            debug!(turn, speaker = %speaker.name, "GroupChat: taking turn");
+
+            let mut shared = Conversation::load(&store).await?;
+            self.view(&shared, speaker).save(&store).await?;
+            let handle = store.clone();
+            store = match speaker.node.call(store).await {
+                Ok(store) => store,
+                Err(e) => {
+                    shared.save(&handle).await?;
+                    return Err(e);
+                }
+            };
+            let reply = match Conversation::load(&store).await?.last() {
+                Some(m) if m.role == Role::Assistant => m.text(),
+                _ => {
+                    shared.save(&store).await?;
+                    return Err(AgentFlowError::InvalidOutput(format!(
+                        "GroupChat: participant '{}' did not append an assistant message",
+                        speaker.name
+                    )));
+                }
+            };
+            shared.push(ChatMessage::assistant(reply.clone()).with_name(&speaker.name));
+            shared.save(&store).await?;
+            speakers.push(speaker.name.clone());
+            previous = Some(next);
+
+            if let Some(keyword) = &self.termination_keyword {
+                if reply.contains(keyword.as_str()) {
+                    stop_reason = StopReason::Keyword;
+                    break;
+                }
+            }
+            if let Some(judge) = &self.judge {
+                store.write().await.remove(TERMINATE_KEY);
+                store = judge.call(store).await?;
+                if store.read().await.get(TERMINATE_KEY) == Some(&Value::Bool(true)) {
+                    stop_reason = StopReason::Judge;
+                    break;
+                }
+            }
+        }
+
+        if stop_reason == StopReason::MaxTurns {
+            warn!(max_turns = self.max_turns, "GroupChat: turn limit reached");
+        }
+        let mut guard = store.write().await;
+        guard.remove(NEXT_SPEAKER_KEY);
+        guard.remove(PARTICIPANTS_KEY);
+        guard.insert(
+            GROUP_CHAT_KEY.to_string(),
+            json!({
+                "turns": speakers.len(),
+                "speakers": speakers,
+                "stop_reason": stop_reason,
+            }),
+        );
+        drop(guard);
+        info!(
+            turns = speakers.len(),
+            ?stop_reason,
+            elapsed_ms = t.elapsed().as_millis(),
+            "GroupChat: complete"
+        );
+        Ok(store)
+    }
+
+    async fn next_speaker(
+        &self,
+        store: SharedStore,
+        previous: Option<usize>,
+    ) -> Result<(usize, SharedStore), AgentFlowError> {
+        let round_robin = previous.map_or(0, |i| (i + 1) % self.participants.len());
+        match &self.turn_order {
+            TurnOrder::RoundRobin => Ok((round_robin, store)),
+            TurnOrder::Mentions => {
+                let text = Conversation::load(&store)
+                    .await?
+                    .last()
+                    .map(ChatMessage::text)
+                    .unwrap_or_default();
+                Ok((
+                    self.mentioned(&text, previous).unwrap_or(round_robin),
+                    store,
+                ))
+            }
+            TurnOrder::Selector(selector) => {
+                {
+                    let mut guard = store.write().await;
+                    guard.remove(NEXT_SPEAKER_KEY);
+                    guard.insert(
+                        PARTICIPANTS_KEY.to_string(),
+                        json!(self.participant_names()),
+                    );
+                }
+                let store = selector.call(store).await?;
+                let chosen = store
+                    .read()
+                    .await
+                    .get(NEXT_SPEAKER_KEY)
+                    .and_then(Value::as_str)
+                    .and_then(|name| self.participants.iter().position(|p| p.name == name));
+                if chosen.is_none() {
+                    warn!("GroupChat: selector chose no known participant; using round-robin");
+                }
+                Ok((chosen.unwrap_or(round_robin), store))
+            }
+        }
+    }
+
+    /// Index of the first participant other than `previous` mentioned as
+    /// `@name` in `text`. The name must not run on into a letter, digit or
+    /// `_`; when several names match at the same place the longest wins.
+    fn mentioned(&self, text: &str, previous: Option<usize>) -> Option<usize> {
+        self.participants
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != previous)
+            .filter_map(|(i, p)| {
+                let handle = format!("@{}", p.name);
+                text.match_indices(&handle)
+                    .map(|(at, _)| at)
+                    .find(|at| {
+                        text[at + handle.len()..]
+                            .chars()
+                            .next()
+                            .map_or(true, |c| !c.is_alphanumeric() && c != '_')
+                    })
+                    .map(|at| (at, Reverse(p.name.len()), i))
+            })
+            .min()
+            .map(|(_, _, i)| i)
+    }
+
+    /// The shared conversation as `speaker` sees it.
+    fn view(&self, shared: &Conversation, speaker: &Participant) -> Conversation {
+        let mut view = Conversation::new();
+        if let Some(persona) = &speaker.persona {
+            view.push(ChatMessage::system(persona.clone()));
+        }
+        for message in &shared.messages {
+            match (message.role, message.name.as_deref()) {
+                (Role::Assistant, Some(name)) if name == speaker.name => {
+                    view.push(ChatMessage::assistant(message.text()));
+                }
+                (Role::Assistant, Some(name)) => {
+                    view.push(
+                        ChatMessage::user(format!("{name}: {}", message.text())).with_name(name),
+                    );
+                }
+                (Role::Tool, _) => {}
+                _ => view.push(message.clone()),
+            }
+        }
+        view
+    }
+}
+
+impl Default for GroupChat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeResult<SharedStore, SharedStore> for GroupChat {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::conversation::{Conversation, Role};
 use crate::core::error::AgentFlowError;
 use crate::core::node::{create_result_node, ResultNode, SharedStore};
 
@@ -32,3 +33,34 @@ pub fn create_hitl_node(
         }
     })
 }
+
+/// Creates a HITL node that waits for the human's turn in the shared
+/// [`Conversation`].
+///
+/// If the last message in `store["conversation"]` is from [`Role::User`],
+/// the node sets `action` to `continue_action` and returns `Ok(store)`.
+/// Otherwise (empty conversation, or the assistant spoke last) it returns
+/// `Err(AgentFlowError::Suspended(reason))`; append the human's reply with
+/// [`Conversation::append`] and resume the flow.
+///
+/// # Errors
+///
+/// Returns [`AgentFlowError::TypeMismatch`] if `store["conversation"]` is not a
+/// valid conversation.
+pub fn create_conversation_hitl_node(
+    continue_action: &'static str,
+    reason: &'static str,
+) -> ResultNode {
+    create_result_node(move |store: SharedStore| async move {
+        let conversation = Conversation::load(&store).await?;
+        if conversation.last().map(|m| m.role) == Some(Role::User) {
+            store.write().await.insert(
+                "action".to_string(),
+                serde_json::Value::String(continue_action.to_string()),
+            );
+            Ok(store)
+        } else {
+            Err(AgentFlowError::Suspended(reason.to_string()))
+        }
+    })
+}
@@ -4,14 +4,24 @@
 pub mod agent;
 /// Batch execution pattern.
 pub mod batchflow;
+/// Turn-taking multi-agent conversation pattern.
+pub mod group_chat;
 /// Human-in-the-loop pattern.
 pub mod hitl;
 /// Map-reduce pattern.
 pub mod mapreduce;
 /// Multi-agent concurrent pattern.
 pub mod multi_agent;
+/// Plan-and-execute pattern with a dependency-aware task tree.
+pub mod plan_execute;
 /// Retrieval-augmented generation pattern.
 pub mod rag;
+/// ReAct tool-calling agent pattern.
+pub mod react;
+/// Generate–critique–revise pattern.
+pub mod reflection;
+/// Intent routing pattern.
+pub mod router;
 /// Retry-with-prompt-injection pattern.
 pub mod rpi;
 #[cfg(feature = "skills")]
@@ -19,18 +29,26 @@ pub mod rpi;
 pub mod skill;
 /// Structured output enforcement pattern.
 pub mod structured_output;
+/// Hierarchical coordinator/worker delegation pattern.
+pub mod supervisor;
 /// Linear workflow pattern.
 pub mod workflow;
 
 // Re-export all patterns for convenience
 pub use agent::Agent;
 pub use batchflow::BatchFlow;
-pub use hitl::create_hitl_node;
+pub use group_chat::GroupChat;
+pub use hitl::{create_conversation_hitl_node, create_hitl_node};
 pub use mapreduce::MapReduce;
 pub use multi_agent::MultiAgent;
+pub use plan_execute::{Plan, PlanExecute, PlanStep, StepStatus};
 pub use rag::Rag;
+pub use react::ReactAgent;
+pub use reflection::Reflection;
+pub use router::{KeywordClassifier, Router};
 pub use rpi::RpiWorkflow;
 pub use structured_output::StructuredOutput;
+pub use supervisor::{Delegation, Supervisor};
 pub use workflow::Workflow;
 
 #[cfg(feature = "skills")]
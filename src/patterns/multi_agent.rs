@@ -1,9 +1,22 @@
-use crate::core::node::{Node, SharedStore};
-use futures::future::join_all;
+use crate::core::error::AgentFlowError;
+use crate::core::node::{Node, SharedStore, SimpleNode};
+use crate::core::store::{load_json, save_json};
+use crate::core::telemetry::{forward_llm_usage, LLM_USAGE_KEY};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tracing::{debug, info, instrument};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::{debug, info, instrument, warn};
+
+/// Store key where voting strategies record their [`Consensus`].
+pub const CONSENSUS_KEY: &str = "consensus";
+
+/// Store key where [`MultiAgent`] records its [`AgentReport`].
+pub const AGENT_STATUS_KEY: &str = "agent_status";
 
 /// Controls how results from parallel agents are merged into the final store.
 ///
@@ -23,14 +36,242 @@ pub enum MergeStrategy {
     /// Use this when agents would otherwise overwrite each other's keys.
     Namespaced,
     /// Each agent runs against its own snapshot. The user-supplied closure
-    /// receives all per-agent result stores and returns the merged store.
+    /// receives the per-agent result stores in registration order — `None`
+    /// for agents that failed or timed out — and returns the merged store.
     ///
     /// Wrap your closure in [`Arc::new`] when constructing this variant:
     ///
     /// ```rust,ignore
     /// MergeStrategy::Custom(Arc::new(|stores| { /* ... */ }))
     /// ```
-    Custom(Arc<dyn Fn(Vec<SharedStore>) -> SharedStore + Send + Sync>),
+    Custom(Arc<dyn Fn(Vec<Option<SharedStore>>) -> SharedStore + Send + Sync>),
+    /// Each agent runs against its own snapshot, with `key` removed, and
+    /// writes `key`. The most common value (ties go to the lowest agent
+    /// index) is written to `key` of the merged store only if more than half
+    /// of all agents produced it. Agents that leave `key` unset, fail or time
+    /// out abstain, which counts against a majority.
+    ///
+    /// Values are compared as JSON, so normalise answers before voting.
+    MajorityVote {
+        /// The output key voted on.
+        key: String,
+    },
+    /// Like [`MajorityVote`](Self::MajorityVote), but agent `i` counts
+    /// `weights[i]` (1.0 if missing) instead of one vote, and the winner needs
+    /// more than half of the total weight.
+    WeightedVote {
+        /// The output key voted on.
+        key: String,
+        /// Per-agent weights, in registration order.
+        weights: Vec<f64>,
+    },
+    /// Like [`MajorityVote`](Self::MajorityVote), but the most common value
+    /// is written to `key` if at least `required` agents produced it, whether
+    /// or not that is a majority.
+    Quorum {
+        /// The output key voted on.
+        key: String,
+        /// Minimum number of agreeing agents.
+        required: usize,
+    },
+    /// Each agent runs against its own snapshot, with `key` removed, and
+    /// writes `key`. The judge node then runs on a copy of the input store
+    /// with `store["candidates"]` set to `[{"agent": i, "value": …}, …]` and
+    /// must write the chosen agent index to `store["choice"]`. The chosen
+    /// value is written to `key`.
+    Judge {
+        /// The output key judged.
+        key: String,
+        /// Picks the best candidate.
+        judge: SimpleNode,
+    },
+}
+
+impl MergeStrategy {
+    /// [`MergeStrategy::MajorityVote`] on `key`.
+    pub fn majority_vote(key: &str) -> Self {
+        Self::MajorityVote {
+            key: key.to_string(),
+        }
+    }
+
+    /// [`MergeStrategy::WeightedVote`] on `key`.
+    pub fn weighted_vote(key: &str, weights: Vec<f64>) -> Self {
+        Self::WeightedVote {
+            key: key.to_string(),
+            weights,
+        }
+    }
+
+    /// [`MergeStrategy::Quorum`]: `required` agents must agree on `key`.
+    pub fn quorum(key: &str, required: usize) -> Self {
+        Self::Quorum {
+            key: key.to_string(),
+            required,
+        }
+    }
+
+    /// [`MergeStrategy::Judge`]: `judge` picks the best value of `key`.
+    pub fn judge(key: &str, judge: SimpleNode) -> Self {
+        Self::Judge {
+            key: key.to_string(),
+            judge,
+        }
+    }
+}
+
+/// An agent whose output differs from the consensus value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dissent {
+    /// Agent index, in registration order.
+    pub agent: usize,
+    /// What the agent produced instead.
+    pub value: Value,
+}
+
+/// Outcome of a voting or judge merge, written to `store["consensus"]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Consensus {
+    /// The output key the agents voted on.
+    pub key: String,
+    /// The winning value, if any agent produced one.
+    pub value: Option<Value>,
+    /// Whether the winner was accepted and written to `key`.
+    pub reached: bool,
+    /// Weight of the agents backing the winner over the weight of all agents.
+    pub agreement: f64,
+    /// Indices of the agents that produced the winning value.
+    pub supporters: Vec<usize>,
+    /// Agents that produced a different value.
+    pub dissent: Vec<Dissent>,
+    /// Indices of the agents that left `key` unset.
+    pub abstained: Vec<usize>,
+}
+
+impl Consensus {
+    /// Read the consensus from `store["consensus"]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn load(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        load_json(store, CONSENSUS_KEY).await
+    }
+
+    /// Describe how `outputs` (one per agent) relate to `value`.
+    fn new(
+        key: &str,
+        outputs: &[Option<Value>],
+        value: Option<Value>,
+        weight: impl Fn(usize) -> f64,
+    ) -> Self {
+        let mut supporters = Vec::new();
+        let mut dissent = Vec::new();
+        let mut abstained = Vec::new();
+        for (agent, output) in outputs.iter().enumerate() {
+            match output {
+                None => abstained.push(agent),
+                Some(v) if Some(v) == value.as_ref() => supporters.push(agent),
+                Some(v) => dissent.push(Dissent {
+                    agent,
+                    value: v.clone(),
+                }),
+            }
+        }
+        let total: f64 = (0..outputs.len()).map(&weight).sum();
+        let backing: f64 = supporters.iter().map(|&i| weight(i)).sum();
+        Self {
+            key: key.to_string(),
+            reached: value.is_some(),
+            value,
+            agreement: if total > 0.0 { backing / total } else { 0.0 },
+            supporters,
+            dissent,
+            abstained,
+        }
+    }
+}
+
+/// The value of `outputs` with the highest total weight; ties go to the
+/// value produced first.
+fn plurality(outputs: &[Option<Value>], weight: impl Fn(usize) -> f64) -> Option<Value> {
+    let mut tally: Vec<(&Value, f64)> = Vec::new();
+    for (agent, output) in outputs.iter().enumerate() {
+        let Some(value) = output else { continue };
+        match tally.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, w)) => *w += weight(agent),
+            None => tally.push((value, weight(agent))),
+        }
+    }
+    let mut best: Option<(&Value, f64)> = None;
+    for (value, w) in tally {
+        if best.map_or(true, |(_, b)| w > b) {
+            best = Some((value, w));
+        }
+    }
+    best.map(|(v, _)| v.clone())
+}
+
+/// How many agent failures a [`MultiAgent`] run tolerates.
+///
+/// Panics and [timeouts](MultiAgent::with_agent_timeout) both count as
+/// failures. [`MultiAgent::run`] only records whether the policy was met;
+/// [`MultiAgent::try_run`] turns a violation into an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Every agent must succeed. This is the default.
+    #[default]
+    RequireAll,
+    /// At most this many agents may fail.
+    MaxFailures(usize),
+    /// At least this many agents must succeed.
+    MinSuccesses(usize),
+}
+
+impl FailurePolicy {
+    /// Whether `report` satisfies the policy.
+    pub fn allows(&self, report: &AgentReport) -> bool {
+        let failures = report.failed.len() + report.timed_out.len();
+        match *self {
+            FailurePolicy::RequireAll => failures == 0,
+            FailurePolicy::MaxFailures(n) => failures <= n,
+            FailurePolicy::MinSuccesses(n) => report.succeeded.len() >= n,
+        }
+    }
+}
+
+/// An agent that panicked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentFailure {
+    /// Agent index, in registration order.
+    pub agent: usize,
+    /// The panic message.
+    pub error: String,
+}
+
+/// Per-agent outcome of a [`MultiAgent`] run, written to
+/// `store["agent_status"]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentReport {
+    /// Indices of the agents that finished.
+    pub succeeded: Vec<usize>,
+    /// Agents that panicked.
+    pub failed: Vec<AgentFailure>,
+    /// Indices of the agents cancelled by the per-agent timeout.
+    pub timed_out: Vec<usize>,
+    /// Whether the [`FailurePolicy`] was met.
+    pub policy_met: bool,
+}
+
+impl AgentReport {
+    /// Read the report from `store["agent_status"]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn load(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        load_json(store, AGENT_STATUS_KEY).await
+    }
 }
 
 /// Runs multiple agents concurrently and merges their results.
@@ -47,6 +288,27 @@ pub enum MergeStrategy {
 /// | `SharedStore` | none — shared `Arc` | as written by each agent |
 /// | `Namespaced` | snapshot per agent | `"agent_0.key"`, `"agent_1.key"`, … |
 /// | `Custom(Arc<dyn Fn>)` | snapshot per agent | determined by your closure |
+/// | `MajorityVote` / `WeightedVote` / `Quorum` | snapshot per agent | winning `key`, `"consensus"` |
+/// | `Judge` | snapshot per agent | chosen `key`, `"consensus"` |
+///
+/// # Fault tolerance
+///
+/// Each agent runs as its own Tokio task, so a panicking agent is recorded as
+/// failed instead of taking the ensemble down. With
+/// [`with_agent_timeout`](Self::with_agent_timeout), agents that run too long
+/// are cancelled. Only the stores of agents that finished are merged —
+/// `Custom` receives `None` in place of the others, voting strategies count
+/// them as abstaining. With `SharedStore`, writes a failed agent made
+/// before it stopped remain visible.
+///
+/// Snapshots start without the input's `"llm_usage"`. The LLM usage every
+/// agent records, failed and timed-out agents and the `Judge` included, is
+/// forwarded to the `"llm_usage"` of the merged store, so an enclosing
+/// [`Flow`](crate::core::flow::Flow) and its budget see the whole ensemble.
+///
+/// Every run writes an [`AgentReport`] to `store["agent_status"]`. Whether
+/// it is acceptable is decided by the [`FailurePolicy`]; see
+/// [`try_run`](Self::try_run).
 ///
 /// # Example
 ///
@@ -81,15 +343,32 @@ pub struct MultiAgent {
     pub agents: Vec<Box<dyn Node<SharedStore, SharedStore>>>,
     /// The active merge strategy.
     pub strategy: MergeStrategy,
+    /// Cancel agents that run longer than this.
+    pub agent_timeout: Option<Duration>,
+    /// How many agent failures are acceptable.
+    pub failure_policy: FailurePolicy,
+}
+
+/// When a vote's winner is accepted.
+#[derive(Debug, Clone, Copy)]
+enum Acceptance {
+    /// More than half of the total weight.
+    Majority,
+    /// At least this many supporters.
+    Quorum(usize),
+}
+
+/// Outcome of one agent task.
+enum Outcome {
+    Succeeded(SharedStore),
+    Failed(String),
+    TimedOut,
 }
 
 impl MultiAgent {
     /// Create a `MultiAgent` with the default [`MergeStrategy::SharedStore`].
     pub fn new() -> Self {
-        Self {
-            agents: Vec::new(),
-            strategy: MergeStrategy::SharedStore,
-        }
+        Self::with_strategy(MergeStrategy::SharedStore)
     }
 
     /// Create a `MultiAgent` with an explicit merge strategy.
@@ -97,41 +376,195 @@ impl MultiAgent {
         Self {
             agents: Vec::new(),
             strategy,
+            agent_timeout: None,
+            failure_policy: FailurePolicy::default(),
         }
     }
 
+    /// Cancel any agent still running after `timeout` and record it as
+    /// timed out.
+    pub fn with_agent_timeout(mut self, timeout: Duration) -> Self {
+        self.agent_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how many agent failures are acceptable.
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
     /// Register an agent node. Agents are executed in the order they are added.
     pub fn add_agent(&mut self, agent: Box<dyn Node<SharedStore, SharedStore>>) {
         self.agents.push(agent);
     }
 
     /// Run all agents concurrently and merge the results using the active strategy.
-    #[instrument(name = "multi_agent.run", skip(self, store), fields(agent_count = self.agents.len()))]
+    ///
+    /// Failed and timed-out agents are recorded in `store["agent_status"]`
+    /// but never fail the run.
     pub async fn run(&self, store: SharedStore) -> SharedStore {
+        self.execute(store).await.0
+    }
+
+    /// Like [`run`](Self::run), but fail if the [`FailurePolicy`] is not met.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::NodeFailure`] listing the failed and timed-out
+    /// agents.
+    pub async fn try_run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let (store, report) = self.execute(store).await;
+        if report.policy_met {
+            return Ok(store);
+        }
+        let failed: Vec<usize> = report.failed.iter().map(|f| f.agent).collect();
+        Err(AgentFlowError::NodeFailure(format!(
+            "MultiAgent failure policy {:?} not met: {} of {} agents succeeded (failed: {:?}, timed out: {:?})",
+            self.failure_policy,
+            report.succeeded.len(),
+            self.agents.len(),
+            failed,
+            report.timed_out
+        )))
+    }
+
+    #[instrument(name = "multi_agent.run", skip(self, store), fields(agent_count = self.agents.len()))]
+    async fn execute(&self, store: SharedStore) -> (SharedStore, AgentReport) {
         info!(agent_count = self.agents.len(), "MultiAgent::run starting");
-        match &self.strategy {
+        let (store, mut report) = match &self.strategy {
             MergeStrategy::SharedStore => self.run_shared(store).await,
             MergeStrategy::Namespaced => self.run_namespaced(store).await,
             MergeStrategy::Custom(merge_fn) => self.run_custom(store, merge_fn.clone()).await,
+            MergeStrategy::MajorityVote { key } => {
+                self.run_vote(store, key, &[], Acceptance::Majority).await
+            }
+            MergeStrategy::WeightedVote { key, weights } => {
+                self.run_vote(store, key, weights, Acceptance::Majority)
+                    .await
+            }
+            MergeStrategy::Quorum { key, required } => {
+                self.run_vote(store, key, &[], Acceptance::Quorum(*required))
+                    .await
+            }
+            MergeStrategy::Judge { key, judge } => self.run_judge(store, key, judge).await,
+        };
+        report.policy_met = self.failure_policy.allows(&report);
+        if !report.policy_met {
+            warn!(
+                succeeded = report.succeeded.len(),
+                failed = report.failed.len(),
+                timed_out = report.timed_out.len(),
+                "MultiAgent: failure policy not met"
+            );
+        }
+        if let Err(e) = save_json(&store, AGENT_STATUS_KEY, &report).await {
+            warn!(error = %e, "MultiAgent: could not record agent status");
+        }
+        (store, report)
+    }
+
+    /// Run agent `i` on `stores[i]`, each as its own task, and collect the
+    /// stores of the agents that finished.
+    ///
+    /// The tasks live in a [`JoinSet`], so dropping the run (e.g. when an
+    /// enclosing timeout fires) aborts every agent still running.
+    async fn run_agents(
+        &self,
+        stores: Vec<SharedStore>,
+    ) -> (Vec<Option<SharedStore>>, AgentReport) {
+        let timeout = self.agent_timeout;
+        let mut tasks = JoinSet::new();
+        let mut indices = HashMap::new();
+        for (agent_index, (agent, agent_store)) in self.agents.iter().zip(stores).enumerate() {
+            let agent = agent.clone();
+            let handle = tasks.spawn(async move {
+                match timeout {
+                    // Dropping the agent's future on timeout cancels it.
+                    Some(limit) => tokio::time::timeout(limit, agent.call(agent_store))
+                        .await
+                        .ok(),
+                    None => Some(agent.call(agent_store).await),
+                }
+            });
+            indices.insert(handle.id(), agent_index);
+        }
+
+        let mut outcomes: Vec<Option<Outcome>> = self.agents.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (id, outcome) = match joined {
+                Ok((id, Some(agent_store))) => (id, Outcome::Succeeded(agent_store)),
+                Ok((id, None)) => (id, Outcome::TimedOut),
+                Err(e) if e.is_panic() => (e.id(), Outcome::Failed(panic_message(e.into_panic()))),
+                Err(e) => (e.id(), Outcome::Failed(e.to_string())),
+            };
+            if let Some(&agent_index) = indices.get(&id) {
+                outcomes[agent_index] = Some(outcome);
+            }
+        }
+
+        let mut report = AgentReport::default();
+        let mut results = Vec::new();
+        for (agent, outcome) in outcomes.into_iter().enumerate() {
+            // Every task is joined exactly once, so each slot is filled.
+            match outcome.unwrap_or_else(|| Outcome::Failed("agent task was lost".into())) {
+                Outcome::Succeeded(agent_store) => {
+                    report.succeeded.push(agent);
+                    results.push(Some(agent_store));
+                }
+                Outcome::Failed(error) => {
+                    warn!(agent, %error, "MultiAgent: agent failed");
+                    report.failed.push(AgentFailure { agent, error });
+                    results.push(None);
+                }
+                Outcome::TimedOut => {
+                    warn!(agent, "MultiAgent: agent timed out");
+                    report.timed_out.push(agent);
+                    results.push(None);
+                }
+            }
+        }
+        (results, report)
+    }
+
+    /// Run every agent on its own copy of `snapshot`, without its LLM usage.
+    /// The usage each agent records, whether or not it finished, is then
+    /// forwarded to `store`.
+    async fn run_snapshots(
+        &self,
+        store: &SharedStore,
+        snapshot: &HashMap<String, Value>,
+    ) -> (Vec<Option<SharedStore>>, AgentReport) {
+        let mut snapshot = snapshot.clone();
+        snapshot.remove(LLM_USAGE_KEY);
+        let stores: Vec<SharedStore> = self
+            .agents
+            .iter()
+            .map(|_| Arc::new(tokio::sync::RwLock::new(snapshot.clone())))
+            .collect();
+        let (results, report) = self.run_agents(stores.clone()).await;
+        for agent_store in &stores {
+            forward_llm_usage(agent_store, store).await;
         }
+        (results, report)
     }
 
     /// SharedStore strategy — all agents share one `Arc`.
     #[instrument(name = "multi_agent.run_shared", skip(self, store), fields(agent_count = self.agents.len()))]
-    async fn run_shared(&self, store: SharedStore) -> SharedStore {
+    async fn run_shared(&self, store: SharedStore) -> (SharedStore, AgentReport) {
         debug!(
             agent_count = self.agents.len(),
             "MultiAgent::run_shared spawning agents"
         );
-        let futures = self.agents.iter().map(|agent| agent.call(store.clone()));
-        join_all(futures).await;
+        let stores = self.agents.iter().map(|_| store.clone()).collect();
+        let (_, report) = self.run_agents(stores).await;
         info!("MultiAgent::run_shared complete");
-        store
+        (store, report)
     }
 
     /// Namespaced strategy — snapshot per agent, merge with prefix.
     #[instrument(name = "multi_agent.run_namespaced", skip(self, store), fields(agent_count = self.agents.len()))]
-    async fn run_namespaced(&self, store: SharedStore) -> SharedStore {
+    async fn run_namespaced(&self, store: SharedStore) -> (SharedStore, AgentReport) {
         debug!(
             agent_count = self.agents.len(),
             "MultiAgent::run_namespaced starting"
@@ -139,14 +572,13 @@ impl MultiAgent {
 
         // Snapshot the store once, then fan out to all agents concurrently
         let snapshot = store.read().await.clone();
-        let futures = self.agents.iter().enumerate().map(|(idx, agent)| {
-            let agent_store = std::sync::Arc::new(tokio::sync::RwLock::new(snapshot.clone()));
-            async move { (idx, agent.call(agent_store).await) }
-        });
-        let agent_stores = join_all(futures).await;
+        let (agent_stores, report) = self.run_snapshots(&store, &snapshot).await;
 
         // Merge results back with "agent_N." prefix
-        for (idx, agent_store) in agent_stores {
+        for (idx, agent_store) in agent_stores.into_iter().enumerate() {
+            let Some(agent_store) = agent_store else {
+                continue;
+            };
             let agent_data = agent_store.read().await;
             let mut merged_store = store.write().await;
             for (key, value) in agent_data.iter() {
@@ -156,7 +588,7 @@ impl MultiAgent {
             }
         }
         info!("MultiAgent::run_namespaced complete");
-        store
+        (store, report)
     }
 
     /// Custom strategy — snapshot per agent, user-supplied merge closure.
@@ -164,8 +596,8 @@ impl MultiAgent {
     async fn run_custom(
         &self,
         store: SharedStore,
-        merge_fn: Arc<dyn Fn(Vec<SharedStore>) -> SharedStore + Send + Sync>,
-    ) -> SharedStore {
+        merge_fn: Arc<dyn Fn(Vec<Option<SharedStore>>) -> SharedStore + Send + Sync>,
+    ) -> (SharedStore, AgentReport) {
         debug!(
             agent_count = self.agents.len(),
             "MultiAgent::run_custom starting"
@@ -173,14 +605,135 @@ impl MultiAgent {
 
         // Snapshot the store once, then fan out to all agents concurrently
         let snapshot = store.read().await.clone();
-        let futures = self.agents.iter().map(|agent| {
-            let agent_store = std::sync::Arc::new(tokio::sync::RwLock::new(snapshot.clone()));
-            agent.call(agent_store)
-        });
-        let results = join_all(futures).await;
+        let (results, report) = self.run_snapshots(&store, &snapshot).await;
 
         info!("MultiAgent::run_custom complete, calling merge_fn");
-        merge_fn(results)
+        let merged = merge_fn(results);
+        if !Arc::ptr_eq(&merged, &store) {
+            forward_llm_usage(&store, &merged).await;
+        }
+        (merged, report)
+    }
+
+    /// Run every agent on its own snapshot and collect its value of `key`.
+    ///
+    /// `key` is removed from the snapshots, so an agent that leaves it unset
+    /// abstains instead of voting for the input's value.
+    async fn collect_outputs(
+        &self,
+        store: &SharedStore,
+        key: &str,
+    ) -> (Vec<Option<Value>>, AgentReport) {
+        let mut snapshot = store.read().await.clone();
+        snapshot.remove(key);
+        let (results, report) = self.run_snapshots(store, &snapshot).await;
+        let mut outputs = Vec::new();
+        for agent_store in results {
+            outputs.push(match agent_store {
+                Some(agent_store) => agent_store.read().await.get(key).cloned(),
+                None => None,
+            });
+        }
+        (outputs, report)
+    }
+
+    /// Write the accepted value and the [`Consensus`] record.
+    async fn write_consensus(store: SharedStore, consensus: Consensus) -> SharedStore {
+        if let (true, Some(value)) = (consensus.reached, &consensus.value) {
+            store
+                .write()
+                .await
+                .insert(consensus.key.clone(), value.clone());
+        }
+        if let Err(e) = save_json(&store, CONSENSUS_KEY, &consensus).await {
+            warn!(error = %e, "MultiAgent: could not record consensus");
+        }
+        store
+    }
+
+    /// Vote strategies — snapshot per agent, weighted plurality on `key`.
+    #[instrument(name = "multi_agent.run_vote", skip(self, store, weights), fields(agent_count = self.agents.len()))]
+    async fn run_vote(
+        &self,
+        store: SharedStore,
+        key: &str,
+        weights: &[f64],
+        acceptance: Acceptance,
+    ) -> (SharedStore, AgentReport) {
+        let (outputs, report) = self.collect_outputs(&store, key).await;
+        let weight = |i: usize| weights.get(i).copied().unwrap_or(1.0);
+        let winner = plurality(&outputs, weight);
+        let mut consensus = Consensus::new(key, &outputs, winner, weight);
+        match acceptance {
+            Acceptance::Majority if consensus.agreement <= 0.5 => {
+                warn!(agreement = consensus.agreement, "MultiAgent: no majority");
+                consensus.reached = false;
+            }
+            Acceptance::Quorum(required) if consensus.supporters.len() < required => {
+                warn!(
+                    supporters = consensus.supporters.len(),
+                    required, "MultiAgent: quorum not met"
+                );
+                consensus.reached = false;
+            }
+            _ => {}
+        }
+        info!(
+            agreement = consensus.agreement,
+            reached = consensus.reached,
+            "MultiAgent::run_vote complete"
+        );
+        (Self::write_consensus(store, consensus).await, report)
+    }
+
+    /// Judge strategy — snapshot per agent, a judge node picks the winner.
+    #[instrument(name = "multi_agent.run_judge", skip(self, store, judge), fields(agent_count = self.agents.len()))]
+    async fn run_judge(
+        &self,
+        store: SharedStore,
+        key: &str,
+        judge: &SimpleNode,
+    ) -> (SharedStore, AgentReport) {
+        let (outputs, report) = self.collect_outputs(&store, key).await;
+        let candidates: Vec<Value> = outputs
+            .iter()
+            .enumerate()
+            .filter_map(|(agent, v)| v.as_ref().map(|v| json!({"agent": agent, "value": v})))
+            .collect();
+
+        let mut judge_data: HashMap<String, Value> = store.read().await.clone();
+        judge_data.remove(LLM_USAGE_KEY);
+        judge_data.insert("candidates".to_string(), Value::Array(candidates));
+        let judged = judge
+            .call(Arc::new(tokio::sync::RwLock::new(judge_data)))
+            .await;
+        forward_llm_usage(&judged, &store).await;
+        let choice = judged.read().await.get("choice").and_then(Value::as_u64);
+        let value = match choice.and_then(|i| outputs.get(i as usize)) {
+            Some(Some(value)) => Some(value.clone()),
+            _ => {
+                warn!(?choice, "MultiAgent: judge chose no valid candidate");
+                None
+            }
+        };
+        let consensus = Consensus::new(key, &outputs, value, |_| 1.0);
+        info!(
+            agreement = consensus.agreement,
+            reached = consensus.reached,
+            "MultiAgent::run_judge complete"
+        );
+        (Self::write_consensus(store, consensus).await, report)
+    }
+}
+
+/// Best-effort text of a panic payload.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        format!("panicked: {s}")
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        format!("panicked: {s}")
+    } else {
+        "panicked".to_string()
     }
 }
 
@@ -0,0 +1,494 @@
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, SharedStore};
+use crate::core::store::{load_json, save_json};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+/// Store key under which [`PlanExecute`] checkpoints the [`Plan`].
+pub const PLAN_KEY: &str = "plan";
+
+/// Lifecycle of a [`PlanStep`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    /// Not started yet.
+    #[default]
+    Pending,
+    /// Handed to the executor.
+    Running,
+    /// Finished; `result` is set.
+    Done,
+    /// The executor returned an error; `error` is set.
+    Failed,
+}
+
+/// One task of a [`Plan`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// Unique ID within the plan.
+    pub id: String,
+    /// What to do.
+    pub task: String,
+    /// IDs of steps that must be [`Done`](StepStatus::Done) first.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Current status.
+    #[serde(default)]
+    pub status: StepStatus,
+    /// Executor output, once done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Executor error, if failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PlanStep {
+    /// A pending step.
+    pub fn new(id: &str, task: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            task: task.to_string(),
+            depends_on: Vec::new(),
+            status: StepStatus::Pending,
+            result: None,
+            error: None,
+        }
+    }
+
+    /// Run only after `id` is done.
+    pub fn after(mut self, id: &str) -> Self {
+        self.depends_on.push(id.to_string());
+        self
+    }
+}
+
+/// A goal broken into steps with dependencies.
+///
+/// Serializes to JSON, so it is checkpointed in the store like any other
+/// value and survives [`Store`](crate::core::store::Store) snapshots and
+/// backends.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    /// What the plan is for.
+    pub goal: String,
+    /// Steps, in planner order.
+    pub steps: Vec<PlanStep>,
+    /// Number of times the plan has been replanned.
+    #[serde(default)]
+    pub revision: usize,
+}
+
+impl Plan {
+    /// An empty plan for `goal`.
+    pub fn new(goal: &str) -> Self {
+        Self {
+            goal: goal.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// A plan whose tasks run one after another, with IDs `"1"`, `"2"`, ….
+    pub fn sequential<S: AsRef<str>>(goal: &str, tasks: impl IntoIterator<Item = S>) -> Self {
+        let mut plan = Self::new(goal);
+        for (i, task) in tasks.into_iter().enumerate() {
+            let mut step = PlanStep::new(&(i + 1).to_string(), task.as_ref());
+            if i > 0 {
+                step = step.after(&i.to_string());
+            }
+            plan.steps.push(step);
+        }
+        plan
+    }
+
+    /// Append a step.
+    pub fn with_step(mut self, step: PlanStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// The step with `id`.
+    pub fn step(&self, id: &str) -> Option<&PlanStep> {
+        self.steps.iter().find(|s| s.id == id)
+    }
+
+    fn step_mut(&mut self, id: &str) -> Option<&mut PlanStep> {
+        self.steps.iter_mut().find(|s| s.id == id)
+    }
+
+    /// Pending steps whose dependencies are all done, in plan order.
+    pub fn ready_steps(&self) -> Vec<&PlanStep> {
+        let done: HashSet<&str> = self
+            .steps
+            .iter()
+            .filter(|s| s.status == StepStatus::Done)
+            .map(|s| s.id.as_str())
+            .collect();
+        self.steps
+            .iter()
+            .filter(|s| s.status == StepStatus::Pending)
+            .filter(|s| s.depends_on.iter().all(|d| done.contains(d.as_str())))
+            .collect()
+    }
+
+    /// Whether every step is done.
+    pub fn is_complete(&self) -> bool {
+        self.steps.iter().all(|s| s.status == StepStatus::Done)
+    }
+
+    /// Steps that failed.
+    pub fn failed_steps(&self) -> Vec<&PlanStep> {
+        self.steps
+            .iter()
+            .filter(|s| s.status == StepStatus::Failed)
+            .collect()
+    }
+
+    /// Check that IDs are unique, dependencies exist and there are no cycles.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::GraphBuildError`] describing the first problem found.
+    pub fn validate(&self) -> Result<(), AgentFlowError> {
+        let mut ids = HashSet::new();
+        for step in &self.steps {
+            if !ids.insert(step.id.as_str()) {
+                return Err(AgentFlowError::GraphBuildError(format!(
+                    "Plan has duplicate step id '{}'",
+                    step.id
+                )));
+            }
+        }
+        for step in &self.steps {
+            if let Some(missing) = step.depends_on.iter().find(|d| !ids.contains(d.as_str())) {
+                return Err(AgentFlowError::GraphBuildError(format!(
+                    "Plan step '{}' depends on unknown step '{}'",
+                    step.id, missing
+                )));
+            }
+        }
+        // Kahn's algorithm: anything left unvisited is on a cycle.
+        let mut remaining: HashMap<&str, usize> = self
+            .steps
+            .iter()
+            .map(|s| {
+                let unique: HashSet<&String> = s.depends_on.iter().collect();
+                (s.id.as_str(), unique.len())
+            })
+            .collect();
+        let mut queue: Vec<&str> = remaining
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        while let Some(id) = queue.pop() {
+            remaining.remove(id);
+            for step in &self.steps {
+                if step.depends_on.iter().any(|d| d == id) {
+                    if let Some(n) = remaining.get_mut(step.id.as_str()) {
+                        *n -= 1;
+                        if *n == 0 {
+                            queue.push(step.id.as_str());
+                        }
+                    }
+                }
+            }
+        }
+        if !remaining.is_empty() {
+            let mut cycle: Vec<_> = remaining.keys().copied().collect();
+            cycle.sort_unstable();
+            return Err(AgentFlowError::GraphBuildError(format!(
+                "Plan has a dependency cycle involving steps {:?}",
+                cycle
+            )));
+        }
+        Ok(())
+    }
+
+    /// Read the plan checkpointed under [`PLAN_KEY`], if any.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key does not hold a plan.
+    pub async fn load(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        load_json(store, PLAN_KEY).await
+    }
+
+    /// Checkpoint the plan under [`PLAN_KEY`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plan cannot be serialized.
+    pub async fn save(&self, store: &SharedStore) -> Result<(), AgentFlowError> {
+        save_json(store, PLAN_KEY, self).await
+    }
+}
+
+/// What the executor receives for one step.
+#[derive(Debug, Clone)]
+pub struct StepInput {
+    /// The step to run.
+    pub step: PlanStep,
+    /// Results of the step's dependencies, by step ID.
+    pub dependencies: HashMap<String, Value>,
+    /// The store the plan runs against.
+    pub store: SharedStore,
+}
+
+type PlanFuture = Pin<Box<dyn Future<Output = Result<Plan, AgentFlowError>> + Send>>;
+type StepFuture = Pin<Box<dyn Future<Output = Result<Value, AgentFlowError>> + Send>>;
+
+/// Produces the initial plan from the store (e.g. from `store["goal"]`).
+pub type PlannerFn = Arc<dyn Fn(SharedStore) -> PlanFuture + Send + Sync>;
+
+/// Runs one step and returns its result.
+pub type ExecutorFn = Arc<dyn Fn(StepInput) -> StepFuture + Send + Sync>;
+
+/// Revises a plan that has failed steps.
+pub type ReplannerFn = Arc<dyn Fn(Plan, SharedStore) -> PlanFuture + Send + Sync>;
+
+/// Plan a goal, then execute the steps as their dependencies allow.
+///
+/// 1. If `store["plan"]` holds an unfinished [`Plan`], it is resumed: steps
+///    left `Running` by an interrupted run go back to `Pending`, finished
+///    steps are kept. Otherwise — no plan, or a complete one from an earlier
+///    run — the planner creates a new one. The completed plan stays in
+///    `store["plan"]` after the run, so its results can be read.
+/// 2. Ready steps run concurrently, up to
+///    [`with_concurrency_limit`](Self::with_concurrency_limit). As each step
+///    finishes its status and result are recorded and the plan is
+///    checkpointed to `store["plan"]`, and newly unblocked steps start.
+/// 3. When a step fails, no new steps start. Once running steps finish, the
+///    replanner receives the plan — failed step included — and returns a
+///    revised one (keep `Done` steps to reuse their results; reset or replace
+///    the failed one). Without a replanner, or after
+///    [`with_max_replans`](Self::with_max_replans) revisions, the run stops
+///    with [`AgentFlowError::NodeFailure`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use agentflow::patterns::plan_execute::{Plan, PlanExecute, PlanStep};
+/// use agentflow::prelude::*;
+/// use serde_json::json;
+///
+/// let agent = PlanExecute::new(
+///     |_store| async move {
+///         Ok(Plan::new("report")
+///             .with_step(PlanStep::new("a", "research A"))
+///             .with_step(PlanStep::new("b", "research B"))
+///             .with_step(PlanStep::new("write", "write report").after("a").after("b")))
+///     },
+///     |input| async move {
+///         // Call an LLM with input.step.task and input.dependencies here.
+///         Ok(json!(format!("did {}", input.step.task)))
+///     },
+/// )
+/// .with_replanner(|mut plan, _store| async move {
+///     for step in &mut plan.steps {
+///         if step.status == agentflow::patterns::plan_execute::StepStatus::Failed {
+///             step.task = format!("{} (simplified)", step.task);
+///             step.status = Default::default();
+///         }
+///     }
+///     Ok(plan)
+/// });
+/// ```
+#[derive(Clone)]
+pub struct PlanExecute {
+    planner: PlannerFn,
+    executor: ExecutorFn,
+    replanner: Option<ReplannerFn>,
+    /// Maximum steps running at once.
+    pub concurrency_limit: usize,
+    /// Maximum number of replans before giving up.
+    pub max_replans: usize,
+}
+
+impl PlanExecute {
+    /// Plan with `planner` and run steps with `executor`.
+    pub fn new<P, PFut, E, EFut>(planner: P, executor: E) -> Self
+    where
+        P: Fn(SharedStore) -> PFut + Send + Sync + 'static,
+        PFut: Future<Output = Result<Plan, AgentFlowError>> + Send + 'static,
+        E: Fn(StepInput) -> EFut + Send + Sync + 'static,
+        EFut: Future<Output = Result<Value, AgentFlowError>> + Send + 'static,
+    {
+        Self {
+            planner: Arc::new(move |store| Box::pin(planner(store))),
+            executor: Arc::new(move |input| Box::pin(executor(input))),
+            replanner: None,
+            concurrency_limit: usize::MAX,
+            max_replans: 3,
+        }
+    }
+
+    /// Revise the plan when steps fail.
+    pub fn with_replanner<R, RFut>(mut self, replanner: R) -> Self
+    where
+        R: Fn(Plan, SharedStore) -> RFut + Send + Sync + 'static,
+        RFut: Future<Output = Result<Plan, AgentFlowError>> + Send + 'static,
+    {
+        self.replanner = Some(Arc::new(move |plan, store| {
+            Box::pin(replanner(plan, store))
+        }));
+        self
+    }
+
+    /// Run at most `limit` steps at once (minimum 1).
+    pub fn with_concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = limit.max(1);
+        self
+    }
+
+    /// Give up after `max_replans` revisions.
+    pub fn with_max_replans(mut self, max_replans: usize) -> Self {
+        self.max_replans = max_replans;
+        self
+    }
+
+    /// Plan (or resume an unfinished plan) and execute until every step is
+    /// done.
+    ///
+    /// # Errors
+    ///
+    /// - Errors from the planner or replanner, or an invalid plan
+    ///   ([`Plan::validate`]).
+    /// - [`AgentFlowError::NodeFailure`] if steps still fail when no
+    ///   replanning is left. The failed plan remains in `store["plan"]`.
+    #[instrument(name = "plan_execute.run", skip_all)]
+    pub async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let t = Instant::now();
+        let mut plan = match Plan::load(&store).await? {
+            Some(mut plan) if !plan.is_complete() => {
+                info!(
+                    steps = plan.steps.len(),
+                    "PlanExecute: resuming checkpointed plan"
+                );
+                for step in &mut plan.steps {
+                    if step.status == StepStatus::Running {
+                        step.status = StepStatus::Pending;
+                    }
+                }
+                plan
+            }
+            _ => {
+                let plan = (self.planner)(store.clone()).await?;
+                info!(steps = plan.steps.len(), "PlanExecute: plan created");
+                plan
+            }
+        };
+        plan.validate()?;
+        plan.save(&store).await?;
+
+        let mut in_flight = FuturesUnordered::new();
+        let mut halted = false;
+        loop {
+            if !halted {
+                let ready: Vec<PlanStep> = plan.ready_steps().into_iter().cloned().collect();
+                for step in ready {
+                    if in_flight.len() >= self.concurrency_limit {
+                        break;
+                    }
+                    let dependencies = step
+                        .depends_on
+                        .iter()
+                        .filter_map(|d| {
+                            let result = plan.step(d)?.result.clone()?;
+                            Some((d.clone(), result))
+                        })
+                        .collect();
+                    if let Some(s) = plan.step_mut(&step.id) {
+                        s.status = StepStatus::Running;
+                    }
+                    debug!(step = %step.id, "PlanExecute: starting step");
+                    let id = step.id.clone();
+                    let input = StepInput {
+                        step,
+                        dependencies,
+                        store: store.clone(),
+                    };
+                    let fut = (self.executor)(input);
+                    in_flight.push(async move { (id, fut.await) });
+                }
+                plan.save(&store).await?;
+            }
+
+            let Some((id, result)) = in_flight.next().await else {
+                if plan.is_complete() {
+                    break;
+                }
+                match &self.replanner {
+                    Some(replanner) if plan.revision < self.max_replans => {
+                        let revision = plan.revision + 1;
+                        warn!(revision, "PlanExecute: replanning after failure");
+                        plan = replanner(plan, store.clone()).await?;
+                        plan.revision = revision;
+                        plan.validate()?;
+                        plan.save(&store).await?;
+                        halted = false;
+                        continue;
+                    }
+                    _ => {
+                        let failed: Vec<String> = plan
+                            .failed_steps()
+                            .iter()
+                            .map(|s| format!("'{}': {}", s.id, s.error.as_deref().unwrap_or("?")))
+                            .collect();
+                        let reason = if failed.is_empty() {
+                            "no step is ready to run".to_string()
+                        } else {
+                            format!("failed steps {}", failed.join(", "))
+                        };
+                        return Err(AgentFlowError::NodeFailure(format!(
+                            "Plan '{}' (revision {}) could not complete: {}",
+                            plan.goal, plan.revision, reason
+                        )));
+                    }
+                }
+            };
+
+            if let Some(step) = plan.step_mut(&id) {
+                match result {
+                    Ok(value) => {
+                        debug!(step = %id, "PlanExecute: step done");
+                        step.status = StepStatus::Done;
+                        step.result = Some(value);
+                        step.error = None;
+                    }
+                    Err(e) => {
+                        warn!(step = %id, error = %e.report(), "PlanExecute: step failed");
+                        step.status = StepStatus::Failed;
+                        step.error = Some(e.report());
+                        halted = true;
+                    }
+                }
+            }
+            plan.save(&store).await?;
+        }
+
+        info!(
+            steps = plan.steps.len(),
+            revision = plan.revision,
+            elapsed_ms = t.elapsed().as_millis(),
+            "PlanExecute: plan complete"
+        );
+        Ok(store)
+    }
+}
+
+impl NodeResult<SharedStore, SharedStore> for PlanExecute {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
@@ -1,8 +1,9 @@
+use crate::core::conversation::{ChatMessage, Conversation, Role};
 use crate::core::node::{Node, SharedStore};
 use std::future::Future;
 use std::pin::Pin;
 use std::time::Instant;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 /// Retrieval-Augmented Generation pipeline.
 ///
@@ -17,6 +18,11 @@ use tracing::{debug, info, instrument};
 /// Both nodes operate on the same [`SharedStore`] — the retriever enriches it
 /// in place, then the generator reads from it.
 ///
+/// With [`with_conversation`](Rag::with_conversation) the pipeline also speaks
+/// the shared [`Conversation`]: the latest user message becomes
+/// `store["query"]`, and a string `store["response"]` is appended as an
+/// assistant message.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -56,6 +62,8 @@ pub struct Rag<R, G> {
     pub retriever: R,
     /// The generation node. Reads `store["context"]`, writes `store["response"]`.
     pub generator: G,
+    /// Read the query from, and append the response to, `store["conversation"]`.
+    pub use_conversation: bool,
 }
 
 impl<R, G> Rag<R, G> {
@@ -64,9 +72,18 @@ impl<R, G> Rag<R, G> {
         Self {
             retriever,
             generator,
+            use_conversation: false,
         }
     }
 
+    /// Take the query from the conversation's latest user message and record
+    /// the response as an assistant message. Any `store["response"]` from an
+    /// earlier turn is removed before generation.
+    pub fn with_conversation(mut self) -> Self {
+        self.use_conversation = true;
+        self
+    }
+
     /// Execute the pipeline: retriever → generator.
     #[instrument(name = "rag.ask", skip(self, query))]
     pub async fn ask(&self, query: SharedStore) -> SharedStore
@@ -75,13 +92,50 @@ impl<R, G> Rag<R, G> {
         G: Node<SharedStore, SharedStore>,
     {
         let t = Instant::now();
+        if self.use_conversation {
+            Self::query_from_conversation(&query).await;
+        }
         debug!("Rag: starting retrieval phase");
         let store_after_retrieval = self.retriever.call(query).await;
         debug!("Rag: retrieval done, starting generation");
+        if self.use_conversation {
+            // A response left over from an earlier turn must not be recorded
+            // again if the generator writes none.
+            store_after_retrieval.write().await.remove("response");
+        }
         let result = self.generator.call(store_after_retrieval).await;
+        if self.use_conversation {
+            Self::response_to_conversation(&result).await;
+        }
         info!(elapsed_ms = t.elapsed().as_millis(), "Rag: ask complete");
         result
     }
+
+    /// Copy the latest user message into `store["query"]`.
+    async fn query_from_conversation(store: &SharedStore) {
+        match Conversation::load(store).await {
+            Ok(conversation) => {
+                if let Some(text) = conversation.last_text(Role::User) {
+                    store
+                        .write()
+                        .await
+                        .insert("query".to_string(), serde_json::Value::String(text));
+                }
+            }
+            Err(e) => warn!(error = %e, "Rag: ignoring unreadable conversation"),
+        }
+    }
+
+    /// Append a string `store["response"]` as an assistant message.
+    async fn response_to_conversation(store: &SharedStore) {
+        let response = match store.read().await.get("response") {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            _ => return,
+        };
+        if let Err(e) = Conversation::append(store, ChatMessage::assistant(response)).await {
+            warn!(error = %e, "Rag: could not record response in conversation");
+        }
+    }
 }
 
 impl<R, G> Node<SharedStore, SharedStore> for Rag<R, G>
@@ -0,0 +1,310 @@
+use crate::core::conversation::{ChatMessage, Conversation, Role, ToolCall};
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, ResultNode, SharedStore};
+use crate::core::store::Store;
+use crate::llm::ToolSpec;
+use crate::utils::tool::ToolRegistry;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+/// Store key holding the [`ReactAgent`] trajectory.
+pub const TRAJECTORY_KEY: &str = "trajectory";
+
+/// Store key holding the [`ReactAgent`] final answer.
+pub const ANSWER_KEY: &str = "answer";
+
+type ToolFuture = Pin<Box<dyn Future<Output = Result<Value, AgentFlowError>> + Send>>;
+type ToolFn = Arc<dyn Fn(Value) -> ToolFuture + Send + Sync>;
+
+/// Reason + act loop over structured tool calls.
+///
+/// Each step runs the LLM node, which must append an assistant message to
+/// `store["conversation"]` — [`llm_node`](crate::llm::llm_node) does. Then:
+///
+/// - If the message has no [`ToolCall`]s, its text is the final answer.
+/// - If it calls the [`final answer tool`](Self::with_final_answer_tool), the
+///   call's `"answer"` argument is the final answer.
+/// - Otherwise every call is executed and its result appended as a
+///   [`Role::Tool`] message with the matching `tool_call_id`. Failing or
+///   unknown tools produce an `"Error: …"` observation so the model can
+///   correct itself.
+///
+/// The answer is written to `store["answer"]`. Every step is recorded in
+/// `store["trajectory"]` as it happens, so a failed run still shows what the
+/// agent did. Running out of steps returns
+/// [`AgentFlowError::ExecutionLimitExceeded`].
+///
+/// Tools are native async functions ([`with_tool`](Self::with_tool)) or the
+/// allow-listed commands of a [`ToolRegistry`] ([`with_registry`](Self::with_registry)).
+/// Registry tools run with their registered arguments only; arguments chosen
+/// by the model are ignored. Offer the same tools to the LLM node, e.g. with
+/// [`tool_specs`](Self::tool_specs).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use agentflow::llm::{llm_node, LlmClient, ToolSpec};
+/// use agentflow::patterns::react::ReactAgent;
+/// use agentflow::prelude::*;
+/// use serde_json::json;
+/// use std::sync::Arc;
+///
+/// # async fn run(client: Arc<dyn LlmClient>) -> Result<(), AgentFlowError> {
+/// let search = ToolSpec::new(
+///     "search",
+///     "Search the web",
+///     json!({"type": "object", "properties": {"query": {"type": "string"}}}),
+/// );
+/// let llm = llm_node(client).with_tool(search.clone());
+/// let agent = ReactAgent::new(llm)
+///     .with_tool(search, |args| async move {
+///         Ok(json!(format!("Results for {}", args["query"])))
+///     })
+///     .with_max_steps(6);
+///
+/// let store = Store::new().into_shared();
+/// Conversation::append(&store, ChatMessage::user("Capital of Austria?")).await?;
+/// let store = agent.run(store).await?;
+/// println!("{}", store.read().await["answer"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ReactAgent {
+    llm: ResultNode,
+    tools: HashMap<String, (ToolSpec, ToolFn)>,
+    registry: Option<Arc<ToolRegistry>>,
+    /// Maximum number of LLM calls.
+    pub max_steps: usize,
+    /// Name of the tool that signals the final answer, if any.
+    pub final_answer_tool: Option<String>,
+}
+
+impl ReactAgent {
+    /// An agent driven by `llm`, with no tools and a limit of 10 steps.
+    pub fn new<N>(llm: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        Self {
+            llm: Box::new(llm),
+            tools: HashMap::new(),
+            registry: None,
+            max_steps: 10,
+            final_answer_tool: None,
+        }
+    }
+
+    /// Register a native tool. `func` receives the call's JSON arguments.
+    pub fn with_tool<F, Fut>(mut self, spec: ToolSpec, func: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value, AgentFlowError>> + Send + 'static,
+    {
+        let func: ToolFn = Arc::new(move |args| Box::pin(func(args)));
+        self.tools.insert(spec.name.clone(), (spec, func));
+        self
+    }
+
+    /// Make the commands of `registry` callable by name. Native tools take
+    /// precedence over registry entries with the same name.
+    pub fn with_registry(mut self, registry: Arc<ToolRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Stop with an error after `max_steps` LLM calls without an answer.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Treat a call to `name` as the final answer, taken from its `"answer"`
+    /// argument. [`tool_specs`](Self::tool_specs) includes a spec for it.
+    ///
+    /// The call, and any later calls in the same message (which are not run),
+    /// get a [`Role::Tool`] result so the conversation can be continued.
+    pub fn with_final_answer_tool(mut self, name: &str) -> Self {
+        self.final_answer_tool = Some(name.to_string());
+        self
+    }
+
+    /// Specs of the native tools and the final-answer tool, sorted by name,
+    /// for offering to the LLM.
+    pub fn tool_specs(&self) -> Vec<ToolSpec> {
+        let mut specs: Vec<ToolSpec> = self.tools.values().map(|(s, _)| s.clone()).collect();
+        if let Some(name) = &self.final_answer_tool {
+            specs.push(ToolSpec::new(
+                name,
+                "Give the final answer to the user.",
+                json!({
+                    "type": "object",
+                    "properties": {"answer": {"type": "string"}},
+                    "required": ["answer"],
+                }),
+            ));
+        }
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+
+    /// Run the loop until the model answers or `max_steps` is reached.
+    ///
+    /// # Errors
+    ///
+    /// - Errors from the LLM node, or from reading the conversation.
+    /// - [`AgentFlowError::InvalidOutput`] if the LLM node did not append an
+    ///   assistant message.
+    /// - [`AgentFlowError::ExecutionLimitExceeded`] when steps run out.
+    #[instrument(name = "react.run", skip_all, fields(max_steps = self.max_steps))]
+    pub async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let t = Instant::now();
+        let mut store = store;
+        let mut trajectory = Vec::new();
+        for step in 1..=self.max_steps {
+            debug!(step, "ReactAgent: reasoning");
+            store = self.llm.call(store).await?;
+            let conversation = Conversation::load(&store).await?;
+            let reply = match conversation.last() {
+                Some(m) if m.role == Role::Assistant => m.clone(),
+                _ => {
+                    return Err(AgentFlowError::InvalidOutput(
+                        "ReactAgent: LLM node did not append an assistant message".into(),
+                    ))
+                }
+            };
+            let thought = reply.text();
+
+            if reply.tool_calls.is_empty() {
+                trajectory.push(json!({"step": step, "answer": thought}));
+                return self.finish(store, trajectory, thought, t).await;
+            }
+            if !thought.is_empty() {
+                trajectory.push(json!({"step": step, "thought": thought}));
+            }
+
+            for (i, call) in reply.tool_calls.iter().enumerate() {
+                if self.final_answer_tool.as_deref() == Some(call.name.as_str()) {
+                    let answer = match &call.arguments["answer"] {
+                        Value::String(s) => s.clone(),
+                        Value::Null => call.arguments.to_string(),
+                        other => other.to_string(),
+                    };
+                    // Every tool call needs a result for the conversation to
+                    // stay valid if it is continued.
+                    Conversation::append(&store, ChatMessage::tool(&call.id, "Answer recorded."))
+                        .await?;
+                    for skipped in &reply.tool_calls[i + 1..] {
+                        let note = "Not run: the final answer was already given.";
+                        Conversation::append(&store, ChatMessage::tool(&skipped.id, note)).await?;
+                    }
+                    trajectory.push(json!({"step": step, "answer": answer}));
+                    return self.finish(store, trajectory, answer, t).await;
+                }
+                let (observation, is_error) = match self.execute(call).await {
+                    Ok(Value::String(s)) => (s, false),
+                    Ok(value) => (value.to_string(), false),
+                    Err(e) => {
+                        warn!(step, tool = %call.name, error = %e.report(), "ReactAgent: tool failed");
+                        (format!("Error: {}", e.report()), true)
+                    }
+                };
+                debug!(step, tool = %call.name, "ReactAgent: observation recorded");
+                trajectory.push(json!({
+                    "step": step,
+                    "tool": call.name,
+                    "tool_call_id": call.id,
+                    "arguments": call.arguments,
+                    "observation": observation,
+                    "error": is_error,
+                }));
+                Conversation::append(&store, ChatMessage::tool(&call.id, observation)).await?;
+            }
+            write_trajectory(&store, &trajectory).await;
+        }
+        warn!(max_steps = self.max_steps, "ReactAgent: step limit reached");
+        Err(AgentFlowError::ExecutionLimitExceeded(format!(
+            "ReactAgent reached max_steps ({}) without a final answer",
+            self.max_steps
+        )))
+    }
+
+    async fn finish(
+        &self,
+        store: SharedStore,
+        trajectory: Vec<Value>,
+        answer: String,
+        t: Instant,
+    ) -> Result<SharedStore, AgentFlowError> {
+        write_trajectory(&store, &trajectory).await;
+        store
+            .write()
+            .await
+            .insert(ANSWER_KEY.to_string(), Value::String(answer));
+        info!(
+            entries = trajectory.len(),
+            elapsed_ms = t.elapsed().as_millis(),
+            "ReactAgent: answered"
+        );
+        Ok(store)
+    }
+
+    async fn execute(&self, call: &ToolCall) -> Result<Value, AgentFlowError> {
+        if let Some((_, func)) = self.tools.get(&call.name) {
+            return func(call.arguments.clone()).await;
+        }
+        let registry = self.registry.as_ref().ok_or_else(|| {
+            AgentFlowError::NotFound(format!("Tool '{}' is not available", call.name))
+        })?;
+        let node = registry.create_node(&call.name)?;
+        let scratch = node.call(Store::new().into_shared()).await;
+        let guard = scratch.read().await;
+        if let Some(error) = guard.get(&format!("{}_error", call.name)) {
+            return Err(AgentFlowError::ToolFailure {
+                tool: call.name.clone(),
+                exit_code: None,
+                stderr: error.as_str().unwrap_or_default().to_string(),
+            });
+        }
+        let stdout = guard
+            .get(&format!("{}_stdout", call.name))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        match guard
+            .get(&format!("{}_status", call.name))
+            .and_then(Value::as_i64)
+        {
+            Some(0) => Ok(Value::String(stdout.to_string())),
+            code => Err(AgentFlowError::ToolFailure {
+                tool: call.name.clone(),
+                exit_code: code.map(|c| c as i32),
+                stderr: guard
+                    .get(&format!("{}_stderr", call.name))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            }),
+        }
+    }
+}
+
+async fn write_trajectory(store: &SharedStore, trajectory: &[Value]) {
+    store.write().await.insert(
+        TRAJECTORY_KEY.to_string(),
+        Value::Array(trajectory.to_vec()),
+    );
+}
+
+impl NodeResult<SharedStore, SharedStore> for ReactAgent {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
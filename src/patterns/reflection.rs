@@ -0,0 +1,202 @@
+use crate::core::node::{Node, SharedStore};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+/// Store key holding the [`Reflection`] history of drafts and critiques.
+pub const REFLECTION_HISTORY_KEY: &str = "reflection_history";
+
+/// Decides from the store contents whether the current draft is approved.
+pub type ApprovalFn = Arc<dyn Fn(&HashMap<String, Value>) -> bool + Send + Sync>;
+
+/// Reads a draft's score from the store contents after the critique.
+pub type ScoreFn = Arc<dyn Fn(&HashMap<String, Value>) -> Option<f64> + Send + Sync>;
+
+/// Generate → critique → revise loop.
+///
+/// Store conventions per round:
+///
+/// 1. The **generator** writes `store["draft"]`. From the second round on,
+///    `store["feedback"]` holds the previous critique.
+/// 2. The **critic** reads `store["draft"]` and writes `store["critique"]`,
+///    plus optionally `store["approved"]` (bool) and `store["score"]` (number).
+///    These three keys are cleared before the critic runs.
+/// 3. The approval predicate decides whether to stop. The default approves
+///    when `store["approved"]` is `true`.
+///
+/// Every round is appended to `store["reflection_history"]` as
+/// `{round, draft, critique, score, approved}`. When the loop ends,
+/// `store["approved"]` is `true` or `false`, `store["feedback"]` is removed
+/// and `store["draft"]` holds the final draft — the last one, or with
+/// [`with_best_draft`](Reflection::with_best_draft) the highest-scoring one
+/// if no draft was approved, with its `critique` and `score`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use agentflow::patterns::reflection::Reflection;
+/// use agentflow::prelude::*;
+///
+/// let writer = create_node(|store: SharedStore| async move {
+///     // Call your LLM with store["topic"] and store["feedback"] here.
+///     store.write().await.insert("draft".into(), serde_json::json!("Ownership means…"));
+///     store
+/// });
+/// let editor = create_node(|store: SharedStore| async move {
+///     // Ask an LLM to grade store["draft"] here.
+///     let mut guard = store.write().await;
+///     guard.insert("critique".into(), serde_json::json!("Mention borrowing."));
+///     guard.insert("score".into(), serde_json::json!(6));
+///     drop(guard);
+///     store
+/// });
+///
+/// let reflection = Reflection::new(writer, editor)
+///     .with_max_rounds(4)
+///     .with_approval(|store| store.get("score").and_then(|s| s.as_f64()) >= Some(8.0))
+///     .with_best_draft();
+/// ```
+#[derive(Clone)]
+pub struct Reflection<G, C> {
+    /// Writes `store["draft"]`, reading `store["feedback"]` on revisions.
+    pub generator: G,
+    /// Reads `store["draft"]`, writes `store["critique"]`.
+    pub critic: C,
+    /// Maximum generate/critique rounds.
+    pub max_rounds: usize,
+    /// Return the highest-scoring draft when none is approved.
+    pub best_draft: bool,
+    approval: ApprovalFn,
+    score: ScoreFn,
+}
+
+impl<G, C> Reflection<G, C> {
+    /// Loop `generator` and `critic` for up to 3 rounds.
+    pub fn new(generator: G, critic: C) -> Self {
+        Self {
+            generator,
+            critic,
+            max_rounds: 3,
+            best_draft: false,
+            approval: Arc::new(|store| store.get("approved") == Some(&Value::Bool(true))),
+            score: Arc::new(|store| store.get("score").and_then(Value::as_f64)),
+        }
+    }
+
+    /// Stop after `max_rounds` rounds even if no draft is approved.
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    /// Replace the approval predicate. It sees the store after each critique.
+    pub fn with_approval<F>(mut self, approval: F) -> Self
+    where
+        F: Fn(&HashMap<String, Value>) -> bool + Send + Sync + 'static,
+    {
+        self.approval = Arc::new(approval);
+        self
+    }
+
+    /// Replace how a draft's score is read (default: `store["score"]`).
+    pub fn with_score<F>(mut self, score: F) -> Self
+    where
+        F: Fn(&HashMap<String, Value>) -> Option<f64> + Send + Sync + 'static,
+    {
+        self.score = Arc::new(score);
+        self
+    }
+
+    /// When no draft is approved, leave the highest-scoring draft in
+    /// `store["draft"]` instead of the last one, and its critique and score in
+    /// `store["critique"]` and `store["score"]`.
+    pub fn with_best_draft(mut self) -> Self {
+        self.best_draft = true;
+        self
+    }
+
+    /// Run rounds until a draft is approved or `max_rounds` is reached.
+    #[instrument(name = "reflection.run", skip_all, fields(max_rounds = self.max_rounds))]
+    pub async fn run(&self, store: SharedStore) -> SharedStore
+    where
+        G: Node<SharedStore, SharedStore>,
+        C: Node<SharedStore, SharedStore>,
+    {
+        let t = Instant::now();
+        let mut store = store;
+        let mut history = Vec::new();
+        let mut best: Option<(f64, Value, Value)> = None;
+        let mut approved = false;
+
+        for round in 1..=self.max_rounds {
+            debug!(round, "Reflection: generating draft");
+            store = self.generator.call(store).await;
+            {
+                // A critic that writes nothing must not inherit last round's verdict.
+                let mut guard = store.write().await;
+                for key in ["approved", "critique", "score"] {
+                    guard.remove(key);
+                }
+            }
+            debug!(round, "Reflection: critiquing draft");
+            store = self.critic.call(store).await;
+
+            let mut guard = store.write().await;
+            let draft = guard.get("draft").cloned().unwrap_or(Value::Null);
+            let critique = guard.get("critique").cloned().unwrap_or(Value::Null);
+            let score = (self.score)(&guard);
+            approved = (self.approval)(&guard);
+            history.push(json!({
+                "round": round,
+                "draft": draft,
+                "critique": critique,
+                "score": score,
+                "approved": approved,
+            }));
+            guard.insert(
+                REFLECTION_HISTORY_KEY.to_string(),
+                Value::Array(history.clone()),
+            );
+            if let Some(score) = score {
+                if best.as_ref().map_or(true, |(s, _, _)| score > *s) {
+                    best = Some((score, draft, critique.clone()));
+                }
+            }
+            if approved {
+                info!(round, "Reflection: draft approved");
+                break;
+            }
+            guard.insert("feedback".to_string(), critique);
+        }
+
+        let mut guard = store.write().await;
+        guard.remove("feedback");
+        guard.insert("approved".to_string(), Value::Bool(approved));
+        if !approved {
+            warn!(rounds = history.len(), "Reflection: no draft approved");
+            if let (true, Some((score, draft, critique))) = (self.best_draft, best) {
+                debug!(score, "Reflection: returning best-scoring draft");
+                guard.insert("draft".to_string(), draft);
+                guard.insert("critique".to_string(), critique);
+                guard.insert("score".to_string(), json!(score));
+            }
+        }
+        drop(guard);
+        info!(elapsed_ms = t.elapsed().as_millis(), "Reflection: complete");
+        store
+    }
+}
+
+impl<G, C> Node<SharedStore, SharedStore> for Reflection<G, C>
+where
+    G: Node<SharedStore, SharedStore> + Clone,
+    C: Node<SharedStore, SharedStore> + Clone,
+{
+    fn call(&self, input: SharedStore) -> Pin<Box<dyn Future<Output = SharedStore> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
@@ -0,0 +1,381 @@
+use crate::core::conversation::{Conversation, Role};
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, ResultNode, SharedStore};
+use crate::core::store::{load_json, save_json};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tracing::{debug, info, instrument};
+
+/// Store key where classifiers write their [`Classification`].
+pub const INTENT_KEY: &str = "intent";
+
+/// Store key where [`Router`] records its latest [`RouteEvent`].
+pub const ROUTE_KEY: &str = "route";
+
+/// Route name reported when the fallback node runs.
+pub const FALLBACK_ROUTE: &str = "fallback";
+
+/// A classifier's verdict, stored as `{"label": …, "confidence": …}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Classification {
+    /// Intent label, matched against the [`Router`]'s routes.
+    pub label: String,
+    /// Confidence in `0.0..=1.0`.
+    pub confidence: f64,
+}
+
+impl Classification {
+    /// A verdict of `label` with `confidence`.
+    pub fn new(label: &str, confidence: f64) -> Self {
+        Self {
+            label: label.to_string(),
+            confidence,
+        }
+    }
+
+    /// Read the classification from `store["intent"]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn load(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        load_json(store, INTENT_KEY).await
+    }
+
+    /// Write the classification to `store["intent"]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the classification cannot be serialized.
+    pub async fn save(&self, store: &SharedStore) -> Result<(), AgentFlowError> {
+        save_json(store, INTENT_KEY, self).await
+    }
+}
+
+/// Why a [`Router`] chose its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteReason {
+    /// A known label at or above the confidence threshold.
+    Matched,
+    /// A known label below the confidence threshold.
+    LowConfidence,
+    /// A label with no route.
+    UnknownLabel,
+    /// The classifier wrote no classification.
+    Unclassified,
+}
+
+/// One routing decision, passed to [`Router::on_route`] listeners and
+/// written to `store["route"]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteEvent {
+    /// The label the decision was based on, if any.
+    pub label: Option<String>,
+    /// Its confidence (`0.0` if unclassified).
+    pub confidence: f64,
+    /// The route taken: a label, or [`FALLBACK_ROUTE`].
+    pub target: String,
+    /// Why that route was taken.
+    pub reason: RouteReason,
+    /// Whether the pre-classifier decided without the main classifier.
+    pub pre_classified: bool,
+}
+
+/// Callback registered with [`Router::on_route`].
+pub type RouteListener = Arc<dyn Fn(&RouteEvent) + Send + Sync>;
+
+/// Classify the input, then run the node registered for the intent.
+///
+/// 1. If a [pre-classifier](Self::with_pre_classifier) is set (typically a
+///    cheap [`KeywordClassifier`]) it runs first. A known label at or above
+///    the threshold is used directly; anything else escalates.
+/// 2. The classifier node writes a [`Classification`] to `store["intent"]`.
+/// 3. A known label with confidence ≥ [`min_confidence`](Self::with_min_confidence)
+///    runs its route. Low-confidence, unknown or missing labels run the
+///    [fallback](Self::with_fallback) — e.g. a node asking the user to
+///    clarify — or, without one, fail with [`AgentFlowError::NotFound`].
+///
+/// Every decision is recorded as a [`RouteEvent`]: logged with `tracing`,
+/// written to `store["route"]` and passed to [`on_route`](Self::on_route)
+/// listeners.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use agentflow::patterns::router::{Classification, KeywordClassifier, Router};
+/// use agentflow::prelude::*;
+///
+/// # let billing = create_result_node(|s: SharedStore| async move { Ok(s) });
+/// # let support = create_result_node(|s: SharedStore| async move { Ok(s) });
+/// # let ask_user = create_result_node(|s: SharedStore| async move { Ok(s) });
+/// let llm_classifier = create_result_node(|store: SharedStore| async move {
+///     // Ask an LLM for {"label", "confidence"} here.
+///     Classification::new("billing", 0.9).save(&store).await?;
+///     Ok(store)
+/// });
+///
+/// let router = Router::new(llm_classifier)
+///     .with_pre_classifier(KeywordClassifier::new().with_rule("billing", &["invoice", "refund"]))
+///     .with_route("billing", billing)
+///     .with_route("tech_support", support)
+///     .with_min_confidence(0.7)
+///     .with_fallback(ask_user)
+///     .on_route(|event| println!("{event:?}"));
+/// ```
+#[derive(Clone)]
+pub struct Router {
+    classifier: ResultNode,
+    pre_classifier: Option<ResultNode>,
+    routes: HashMap<String, ResultNode>,
+    fallback: Option<ResultNode>,
+    /// Minimum confidence for taking a labelled route.
+    pub min_confidence: f64,
+    listeners: Vec<RouteListener>,
+}
+
+impl Router {
+    /// A router driven by `classifier`, with no routes and a threshold of 0.5.
+    pub fn new<N>(classifier: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        Self {
+            classifier: Box::new(classifier),
+            pre_classifier: None,
+            routes: HashMap::new(),
+            fallback: None,
+            min_confidence: 0.5,
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Run `node` for intents labelled `label`.
+    pub fn with_route<N>(mut self, label: &str, node: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        self.routes.insert(label.to_string(), Box::new(node));
+        self
+    }
+
+    /// Run `node` for low-confidence, unknown or missing labels.
+    pub fn with_fallback<N>(mut self, node: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        self.fallback = Some(Box::new(node));
+        self
+    }
+
+    /// Try `node` before the classifier; escalate if it is not confident.
+    pub fn with_pre_classifier<N>(mut self, node: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        self.pre_classifier = Some(Box::new(node));
+        self
+    }
+
+    /// Set the confidence threshold.
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Call `listener` with every routing decision.
+    pub fn on_route<F>(mut self, listener: F) -> Self
+    where
+        F: Fn(&RouteEvent) + Send + Sync + 'static,
+    {
+        self.listeners.push(Arc::new(listener));
+        self
+    }
+
+    fn decide(&self, classification: Option<Classification>, pre_classified: bool) -> RouteEvent {
+        let (label, confidence, reason) = match classification {
+            None => (None, 0.0, RouteReason::Unclassified),
+            Some(c) if !self.routes.contains_key(&c.label) => {
+                (Some(c.label), c.confidence, RouteReason::UnknownLabel)
+            }
+            Some(c) if c.confidence < self.min_confidence => {
+                (Some(c.label), c.confidence, RouteReason::LowConfidence)
+            }
+            Some(c) => (Some(c.label), c.confidence, RouteReason::Matched),
+        };
+        let target = match (&label, reason) {
+            (Some(label), RouteReason::Matched) => label.clone(),
+            _ => FALLBACK_ROUTE.to_string(),
+        };
+        RouteEvent {
+            label,
+            confidence,
+            target,
+            reason,
+            pre_classified,
+        }
+    }
+
+    /// Classify and run the chosen route.
+    ///
+    /// # Errors
+    ///
+    /// - Errors from the classifiers or the route node.
+    /// - [`AgentFlowError::NotFound`] if the fallback is needed but not set.
+    #[instrument(name = "router.route", skip_all)]
+    pub async fn route(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let mut store = store;
+        store.write().await.remove(INTENT_KEY);
+
+        let mut event = None;
+        if let Some(pre) = &self.pre_classifier {
+            store = pre.call(store).await?;
+            let candidate = self.decide(Classification::load(&store).await?, true);
+            if candidate.reason == RouteReason::Matched {
+                event = Some(candidate);
+            } else {
+                debug!(reason = ?candidate.reason, "Router: escalating past pre-classifier");
+                store.write().await.remove(INTENT_KEY);
+            }
+        }
+        let event = match event {
+            Some(event) => event,
+            None => {
+                store = self.classifier.call(store).await?;
+                self.decide(Classification::load(&store).await?, false)
+            }
+        };
+
+        info!(
+            label = event.label.as_deref().unwrap_or(""),
+            confidence = event.confidence,
+            target = %event.target,
+            reason = ?event.reason,
+            pre_classified = event.pre_classified,
+            "Router: routing decision"
+        );
+        save_json(&store, ROUTE_KEY, &event).await?;
+        for listener in &self.listeners {
+            listener(&event);
+        }
+
+        let node = if event.reason == RouteReason::Matched {
+            self.routes.get(&event.target)
+        } else {
+            self.fallback.as_ref()
+        };
+        match node {
+            Some(node) => node.call(store).await,
+            None => Err(AgentFlowError::NotFound(format!(
+                "Router has no fallback for {:?} intent '{}'",
+                event.reason,
+                event.label.as_deref().unwrap_or("")
+            ))),
+        }
+    }
+}
+
+impl NodeResult<SharedStore, SharedStore> for Router {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.route(input))
+    }
+}
+
+/// Cheap classifier matching keywords, case-insensitively.
+///
+/// Reads `store["input"]` (or the key set with
+/// [`with_input_key`](Self::with_input_key)), falling back to the latest user
+/// message of the [`Conversation`]. The label with the most keyword hits wins;
+/// its confidence is its share of all hits, so input matching several labels
+/// scores lower. Writes nothing when no keyword matches.
+#[derive(Debug, Clone)]
+pub struct KeywordClassifier {
+    rules: Vec<(String, Vec<String>)>,
+    input_key: String,
+}
+
+impl Default for KeywordClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeywordClassifier {
+    /// A classifier with no rules, reading `store["input"]`.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            input_key: "input".to_string(),
+        }
+    }
+
+    /// Classify input containing any of `keywords` as `label`.
+    pub fn with_rule(mut self, label: &str, keywords: &[&str]) -> Self {
+        self.rules.push((
+            label.to_string(),
+            keywords.iter().map(|k| k.to_lowercase()).collect(),
+        ));
+        self
+    }
+
+    /// Read the input text from `key`.
+    pub fn with_input_key(mut self, key: &str) -> Self {
+        self.input_key = key.to_string();
+        self
+    }
+
+    /// Classify `text`, or `None` if no keyword matches.
+    pub fn classify(&self, text: &str) -> Option<Classification> {
+        let text = text.to_lowercase();
+        let hits: Vec<(&str, usize)> = self
+            .rules
+            .iter()
+            .map(|(label, keywords)| {
+                let n = keywords
+                    .iter()
+                    .filter(|k| text.contains(k.as_str()))
+                    .count();
+                (label.as_str(), n)
+            })
+            .collect();
+        let total: usize = hits.iter().map(|(_, n)| n).sum();
+        // First rule wins ties, so rule order expresses priority.
+        let (label, best) = hits
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, n)| *n)
+            .filter(|(_, n)| *n > 0)?;
+        Some(Classification::new(label, best as f64 / total as f64))
+    }
+
+    async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let text = match store.read().await.get(&self.input_key) {
+            Some(Value::String(text)) => Some(text.clone()),
+            _ => None,
+        };
+        let text = match text {
+            Some(text) => Some(text),
+            None => Conversation::load(&store).await?.last_text(Role::User),
+        };
+        if let Some(classification) = text.and_then(|t| self.classify(&t)) {
+            classification.save(&store).await?;
+        }
+        Ok(store)
+    }
+}
+
+impl NodeResult<SharedStore, SharedStore> for KeywordClassifier {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
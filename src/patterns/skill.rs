@@ -79,10 +79,11 @@ impl NodeResult<SharedStore, SharedStore> for SkillToolNode {
                 }
                 Ok(Err(e)) => {
                     warn!("Skill tool execution failed: {}", e);
-                    Err(AgentFlowError::Custom(format!(
-                        "Failed to run skill tool {}: {}",
-                        tool.name, e
-                    )))
+                    Err(AgentFlowError::ToolFailure {
+                        tool: tool.name.clone(),
+                        exit_code: None,
+                        stderr: format!("failed to start: {}", e),
+                    })
                 }
                 Err(_) => {
                     warn!("Skill tool execution timed out");
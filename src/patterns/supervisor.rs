@@ -0,0 +1,281 @@
+use crate::core::error::AgentFlowError;
+use crate::core::node::{NodeResult, ResultNode, SharedStore};
+use crate::core::store::{load_json, save_json, Store};
+use crate::core::telemetry::forward_llm_usage;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+/// Store key where the coordinator writes its next [`Delegation`].
+pub const DELEGATE_KEY: &str = "delegate";
+
+/// Store key holding the [`Supervisor`] delegation history.
+pub const DELEGATIONS_KEY: &str = "delegations";
+
+/// Key of a worker's sub-store holding its subtask.
+pub const TASK_KEY: &str = "task";
+
+/// Key of a worker's sub-store holding its result.
+pub const RESULT_KEY: &str = "result";
+
+/// A coordinator's decision: give `task` to `worker`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Delegation {
+    /// Name of the worker, as registered with [`Supervisor::with_worker`].
+    pub worker: String,
+    /// The subtask, written to the worker's `store["task"]`.
+    pub task: String,
+    /// Extra entries for the worker's sub-store.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub input: Map<String, Value>,
+}
+
+impl Delegation {
+    /// Give `task` to `worker`.
+    pub fn new(worker: &str, task: &str) -> Self {
+        Self {
+            worker: worker.to_string(),
+            task: task.to_string(),
+            input: Map::new(),
+        }
+    }
+
+    /// Also put `key = value` in the worker's sub-store.
+    pub fn with_input(mut self, key: &str, value: Value) -> Self {
+        self.input.insert(key.to_string(), value);
+        self
+    }
+
+    /// Read the decision from `store["delegate"]`.
+    ///
+    /// # Errors
+    ///
+    /// [`AgentFlowError::TypeMismatch`] if the key holds something else.
+    pub async fn load(store: &SharedStore) -> Result<Option<Self>, AgentFlowError> {
+        load_json(store, DELEGATE_KEY).await
+    }
+
+    /// Write the decision to `store["delegate"]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delegation cannot be serialized.
+    pub async fn save(&self, store: &SharedStore) -> Result<(), AgentFlowError> {
+        save_json(store, DELEGATE_KEY, self).await
+    }
+}
+
+/// Coordinator → worker delegation loop.
+///
+/// Each step:
+///
+/// 1. The **coordinator** reads the store — the goal, plus
+///    `store["delegations"]` with every earlier subtask and its result — and
+///    either writes a [`Delegation`] to `store["delegate"]` or leaves it
+///    unset to finish, typically writing the overall answer to
+///    `store["result"]`.
+/// 2. The chosen **worker** runs against a fresh sub-store holding only
+///    `store["task"]`, the delegation's `input` and the
+///    [shared keys](Self::with_shared_keys). It writes its answer to
+///    `store["result"]`.
+/// 3. `{step, worker, task, result}` — or `error` if the worker failed or
+///    does not exist — is appended to `store["delegations"]`. Failures are
+///    left for the coordinator to handle.
+///
+/// Workers never see each other's scratch keys, and any LLM usage they
+/// record, failed workers included, is forwarded to the parent store. A
+/// `Supervisor` is itself a [`NodeResult`], so a worker can be another
+/// supervisor; its own history is kept under the record's `"delegations"`. Running out of steps returns
+/// [`AgentFlowError::ExecutionLimitExceeded`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use agentflow::patterns::supervisor::{Delegation, Supervisor, DELEGATIONS_KEY};
+/// use agentflow::prelude::*;
+/// use serde_json::json;
+///
+/// let coordinator = create_result_node(|store: SharedStore| async move {
+///     // Ask an LLM which worker should act next, given store["goal"] and
+///     // store["delegations"].
+///     let done = store.read().await.get(DELEGATIONS_KEY).is_some();
+///     if !done {
+///         Delegation::new("researcher", "Find the Rust 2024 edition changes")
+///             .save(&store)
+///             .await?;
+///     }
+///     Ok(store)
+/// });
+/// let researcher = create_result_node(|store: SharedStore| async move {
+///     store.write().await.insert("result".into(), json!("…"));
+///     Ok(store)
+/// });
+///
+/// let supervisor = Supervisor::new(coordinator)
+///     .with_worker("researcher", researcher)
+///     .with_shared_keys(&["goal"])
+///     .with_max_steps(8);
+/// ```
+#[derive(Clone)]
+pub struct Supervisor {
+    coordinator: ResultNode,
+    workers: HashMap<String, ResultNode>,
+    shared_keys: Vec<String>,
+    /// Maximum number of coordinator calls.
+    pub max_steps: usize,
+}
+
+impl Supervisor {
+    /// A supervisor driven by `coordinator`, with no workers and a limit of
+    /// 10 steps.
+    pub fn new<N>(coordinator: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        Self {
+            coordinator: Box::new(coordinator),
+            workers: HashMap::new(),
+            shared_keys: Vec::new(),
+            max_steps: 10,
+        }
+    }
+
+    /// Register `worker` under `name`.
+    pub fn with_worker<N>(mut self, name: &str, worker: N) -> Self
+    where
+        N: NodeResult<SharedStore, SharedStore> + 'static,
+    {
+        self.workers.insert(name.to_string(), Box::new(worker));
+        self
+    }
+
+    /// Copy these keys of the parent store into every worker's sub-store.
+    pub fn with_shared_keys(mut self, keys: &[&str]) -> Self {
+        self.shared_keys = keys.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    /// Stop with an error after `max_steps` coordinator calls without
+    /// finishing.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Names of the registered workers, sorted, for the coordinator prompt.
+    pub fn worker_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.workers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Delegate until the coordinator finishes or `max_steps` is reached.
+    ///
+    /// # Errors
+    ///
+    /// - Errors from the coordinator, or from reading its [`Delegation`].
+    /// - [`AgentFlowError::ExecutionLimitExceeded`] when steps run out.
+    #[instrument(name = "supervisor.run", skip_all, fields(max_steps = self.max_steps))]
+    pub async fn run(&self, store: SharedStore) -> Result<SharedStore, AgentFlowError> {
+        let t = Instant::now();
+        let mut store = store;
+        let mut history = Vec::new();
+        for step in 1..=self.max_steps {
+            store.write().await.remove(DELEGATE_KEY);
+            debug!(step, "Supervisor: consulting coordinator");
+            store = self.coordinator.call(store).await?;
+            let Some(delegation) = Delegation::load(&store).await? else {
+                store.write().await.remove(DELEGATE_KEY);
+                info!(
+                    delegations = history.len(),
+                    elapsed_ms = t.elapsed().as_millis(),
+                    "Supervisor: complete"
+                );
+                return Ok(store);
+            };
+
+            debug!(step, worker = %delegation.worker, "Supervisor: delegating");
+            let mut record = json!({
+                "step": step,
+                "worker": delegation.worker,
+                "task": delegation.task,
+            });
+            match self.delegate(&store, &delegation).await {
+                Ok(sub_store) => {
+                    let mut sub = sub_store.write().await;
+                    record["result"] = sub.remove(RESULT_KEY).unwrap_or(Value::Null);
+                    if let Some(nested) = sub.remove(DELEGATIONS_KEY) {
+                        record[DELEGATIONS_KEY] = nested;
+                    }
+                    drop(sub);
+                    forward_llm_usage(&sub_store, &store).await;
+                }
+                Err((e, sub_store)) => {
+                    warn!(step, worker = %delegation.worker, error = %e.report(), "Supervisor: worker failed");
+                    record["error"] = Value::String(e.report());
+                    // Calls made before the failure were still paid for.
+                    if let Some(sub_store) = sub_store {
+                        forward_llm_usage(&sub_store, &store).await;
+                    }
+                }
+            }
+            history.push(record);
+            store
+                .write()
+                .await
+                .insert(DELEGATIONS_KEY.to_string(), Value::Array(history.clone()));
+        }
+        store.write().await.remove(DELEGATE_KEY);
+        warn!(max_steps = self.max_steps, "Supervisor: step limit reached");
+        Err(AgentFlowError::ExecutionLimitExceeded(format!(
+            "Supervisor reached max_steps ({}) without finishing",
+            self.max_steps
+        )))
+    }
+
+    /// Run the delegated worker on a fresh sub-store. On failure the error
+    /// comes with the sub-store, if the worker was started, so that its LLM
+    /// usage can still be forwarded.
+    async fn delegate(
+        &self,
+        store: &SharedStore,
+        delegation: &Delegation,
+    ) -> Result<SharedStore, (AgentFlowError, Option<SharedStore>)> {
+        let worker = self.workers.get(&delegation.worker).ok_or_else(|| {
+            let e = AgentFlowError::NotFound(format!(
+                "Worker '{}' is not registered",
+                delegation.worker
+            ));
+            (e, None)
+        })?;
+        let sub_store = Store::new().into_shared();
+        {
+            let parent = store.read().await;
+            let mut sub = sub_store.write().await;
+            for key in &self.shared_keys {
+                if let Some(value) = parent.get(key) {
+                    sub.insert(key.clone(), value.clone());
+                }
+            }
+            sub.extend(delegation.input.clone());
+            sub.insert(TASK_KEY.to_string(), Value::String(delegation.task.clone()));
+        }
+        worker
+            .call(sub_store.clone())
+            .await
+            .map_err(|e| (e, Some(sub_store)))
+    }
+}
+
+impl NodeResult<SharedStore, SharedStore> for Supervisor {
+    fn call(
+        &self,
+        input: SharedStore,
+    ) -> Pin<Box<dyn Future<Output = Result<SharedStore, AgentFlowError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
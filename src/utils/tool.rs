@@ -1,5 +1,6 @@
 use crate::core::error::AgentFlowError;
 use crate::core::node::{create_node, SharedStore, SimpleNode};
+use crate::core::retry::RetryPolicy;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::future::Future;
@@ -295,6 +296,26 @@ pub fn create_corrective_retry_node<F, Fut>(
     wait_millis: u64,
     error_key: impl Into<String>,
 ) -> SimpleNode
+where
+    F: Fn(SharedStore) -> Fut + Send + Sync + Clone + 'static,
+    Fut: Future<Output = Result<SharedStore, AgentFlowError>> + Send + 'static,
+{
+    let policy = RetryPolicy::fixed(max_retries, Duration::from_millis(wait_millis));
+    create_corrective_retry_node_with_policy(exec, policy, error_key)
+}
+
+/// [`create_corrective_retry_node`] driven by a [`RetryPolicy`].
+///
+/// Every error is retried unless the policy's
+/// [`retry_if`](RetryPolicy::retry_if) predicate rejects it. If the policy
+/// has an [`retry_if_output`](RetryPolicy::retry_if_output) predicate, a
+/// successful store it matches is treated as an
+/// [`AgentFlowError::InvalidOutput`] failure and retried like an error.
+pub fn create_corrective_retry_node_with_policy<F, Fut>(
+    exec: F,
+    policy: RetryPolicy,
+    error_key: impl Into<String>,
+) -> SimpleNode
 where
     F: Fn(SharedStore) -> Fut + Send + Sync + Clone + 'static,
     Fut: Future<Output = Result<SharedStore, AgentFlowError>> + Send + 'static,
@@ -304,36 +325,52 @@ where
     create_node(move |store: SharedStore| {
         let exec = exec.clone();
         let error_key = error_key.clone();
+        let policy = policy.clone();
 
         async move {
             let current_store = store;
-            let retries = max_retries.max(1);
+            let mut retry = policy.start();
 
-            for attempt in 0..retries {
-                debug!(attempt, max_retries = retries, "corrective_retry attempt");
-                match exec(current_store.clone()).await {
+            loop {
+                let attempt = retry.attempts();
+                debug!(
+                    attempt,
+                    max_retries = policy.max_attempts,
+                    "corrective_retry attempt"
+                );
+                let (error, rejected) = match exec(current_store.clone()).await {
                     Ok(s) => {
-                        debug!(attempt, "corrective_retry succeeded");
-                        // Clear any lingering error key from a previous attempt.
-                        current_store.write().await.remove(&error_key);
-                        return s;
-                    }
-                    Err(e) => {
-                        warn!(attempt, error = %e, "corrective_retry failed; injecting error into store");
-                        current_store
-                            .write()
-                            .await
-                            .insert(error_key.clone(), Value::String(e.to_string()));
-
-                        if attempt < retries - 1 && wait_millis > 0 {
-                            tokio::time::sleep(Duration::from_millis(wait_millis)).await;
+                        let rejected = policy.should_retry_output(&*s.read().await, false);
+                        if !rejected {
+                            debug!(attempt, "corrective_retry succeeded");
+                            // Clear any lingering error key from a previous attempt.
+                            current_store.write().await.remove(&error_key);
+                            return s;
                         }
+                        let e =
+                            AgentFlowError::InvalidOutput("Output rejected by retry policy".into());
+                        (e, true)
                     }
+                    Err(e) => (e, false),
+                };
+
+                warn!(attempt, error = %error, "corrective_retry failed; injecting error into store");
+                current_store
+                    .write()
+                    .await
+                    .insert(error_key.clone(), Value::String(error.to_string()));
+
+                let delay = if rejected || policy.should_retry_error(&error, true) {
+                    retry.next_delay(Some(&error))
+                } else {
+                    None
+                };
+                match delay {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    // All retries exhausted — store already contains the last error.
+                    None => return current_store,
                 }
             }
-
-            // All retries exhausted — store already contains the last error.
-            current_store
         }
     })
 }
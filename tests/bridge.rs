@@ -0,0 +1,246 @@
+use agentflow::core::bridge::{FlowTypedNode, TypedFlowNode};
+use agentflow::core::budget::Budget;
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::telemetry::{record_llm_usage, LlmUsage};
+use agentflow::core::typed_flow::{create_typed_node, create_typed_result_node, TypedFlow};
+use agentflow::core::typed_store::TypedStore;
+use agentflow::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Ticket {
+    title: String,
+    priority: u8,
+    #[serde(default)]
+    assignee: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
+enum Triage {
+    Urgent,
+    Backlog,
+}
+
+fn triage_flow() -> TypedFlow<Ticket, Triage> {
+    let mut flow = TypedFlow::new();
+    flow.add_node(
+        "score",
+        create_typed_node(|mut s: TypedStore<Ticket>| async move {
+            s.inner.title = s.inner.title.to_uppercase();
+            let action = if s.inner.priority > 3 {
+                Triage::Urgent
+            } else {
+                Triage::Backlog
+            };
+            (s, Some(action))
+        }),
+    );
+    flow
+}
+
+fn record(label: &'static str) -> SimpleNode {
+    create_node(move |store: SharedStore| async move {
+        store
+            .write()
+            .await
+            .insert("handled_by".into(), json!(label));
+        store
+    })
+}
+
+#[tokio::test]
+async fn test_typed_flow_node_routes_outer_flow_by_action_name() {
+    let mut flow = Flow::new();
+    flow.add_node(
+        "triage",
+        Box::new(TypedFlowNode::new(triage_flow(), "ticket")),
+    );
+    flow.add_node("page", record("oncall"));
+    flow.add_node("file", record("backlog"));
+    flow.add_edge("triage", "urgent", "page");
+    flow.add_edge("triage", "backlog", "file");
+
+    let store = Store::new();
+    store
+        .set("ticket", json!({ "title": "db down", "priority": 5 }))
+        .await;
+    let result = flow.run_safe(store.into_shared()).await.unwrap();
+
+    let guard = result.read().await;
+    assert_eq!(guard["handled_by"], json!("oncall"));
+    assert_eq!(guard["ticket"]["title"], json!("DB DOWN"));
+    assert!(!guard.contains_key("action"));
+}
+
+#[tokio::test]
+async fn test_typed_flow_node_reports_missing_or_invalid_state() {
+    let node = TypedFlowNode::new(triage_flow(), "ticket");
+    let result = node.call(Store::new().into_shared()).await;
+    assert!(result.read().await.contains_key("error"));
+
+    let seeded = TypedFlowNode::new(triage_flow(), "ticket").with_initial_state(|| Ticket {
+        title: "new".into(),
+        priority: 1,
+        assignee: None,
+    });
+    let result = seeded.call(Store::new().into_shared()).await;
+    let guard = result.read().await;
+    assert_eq!(guard["action"], json!("backlog"));
+    assert_eq!(guard["ticket"]["title"], json!("NEW"));
+}
+
+#[tokio::test]
+async fn test_flow_typed_node_runs_legacy_flow_on_typed_state() {
+    let assign = create_node(|store: SharedStore| async move {
+        let priority = store
+            .read()
+            .await
+            .get("priority")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        let who = if priority > 3 { "alice" } else { "bob" };
+        store.write().await.insert("assignee".into(), json!(who));
+        store
+    });
+    let mut legacy = Flow::new();
+    legacy.add_node("assign", assign);
+
+    let notify = create_typed_node(|mut s: TypedStore<Ticket>| async move {
+        s.inner.title = format!(
+            "{} -> {}",
+            s.inner.title,
+            s.inner.assignee.clone().unwrap_or_default()
+        );
+        (s, None)
+    });
+
+    let mut typed = TypedFlow::<Ticket, Triage>::new();
+    typed.add_result_node(
+        "legacy",
+        Box::new(
+            FlowTypedNode::new(legacy)
+                .with_action(|t: &Ticket| t.assignee.is_some().then_some(Triage::Urgent)),
+        ),
+    );
+    typed.add_node("notify", notify);
+    typed.add_edge("legacy", Triage::Urgent, "notify");
+
+    let result = typed
+        .run_safe(TypedStore::new(Ticket {
+            title: "outage".into(),
+            priority: 9,
+            assignee: None,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(result.inner.title, "outage -> alice");
+}
+
+#[tokio::test]
+async fn test_flow_typed_node_surfaces_type_mismatch() {
+    let clobber = create_node(|store: SharedStore| async move {
+        store.write().await.insert("priority".into(), json!("high"));
+        store
+    });
+    let mut legacy = Flow::new();
+    legacy.add_node("clobber", clobber);
+
+    let mut typed = TypedFlow::<Ticket, Triage>::new();
+    typed.add_result_node("legacy", Box::new(FlowTypedNode::new(legacy)));
+
+    let err = typed
+        .run_safe(TypedStore::new(Ticket::default()))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AgentFlowError::TypeMismatch(_)));
+}
+
+#[tokio::test]
+async fn test_typed_flow_node_forwards_llm_usage_to_flow_budget() {
+    let mut typed = TypedFlow::<Ticket, Triage>::new();
+    typed.add_node(
+        "draft",
+        create_typed_node(|mut s: TypedStore<Ticket>| async move {
+            s.context.record_llm_call(LlmUsage::new("small", 10, 10));
+            (s, Some(Triage::Backlog))
+        }),
+    );
+    typed.add_result_node(
+        "review",
+        create_typed_result_node(|mut s: TypedStore<Ticket>| async move {
+            s.context.record_llm_call(LlmUsage::new("small", 10, 10));
+            Err::<(TypedStore<Ticket>, Option<Triage>), _>(AgentFlowError::Timeout("review".into()))
+        }),
+    );
+    typed.add_edge("draft", Triage::Backlog, "review");
+
+    let build = |budget: Budget| {
+        let mut flow = Flow::new().with_budget(budget);
+        flow.add_node(
+            "triage",
+            Box::new(
+                TypedFlowNode::new(typed.clone(), "ticket").with_initial_state(Ticket::default),
+            ),
+        );
+        flow
+    };
+
+    // The failed typed run's calls still reach the enclosing flow.
+    let (result, context) = build(Budget::new())
+        .run_with_context(Store::new().into_shared())
+        .await;
+    assert_eq!(context.usage_by_node["triage"].calls, 2);
+    assert!(result.read().await.contains_key("error"));
+
+    let err = build(Budget::new().with_max_calls(1))
+        .run_safe(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, AgentFlowError::ExecutionLimitExceeded(msg) if msg.contains("triage: 2 calls")),
+        "{err:?}"
+    );
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Strict {
+    text: String,
+}
+
+#[tokio::test]
+async fn test_flow_typed_node_forwards_llm_usage_to_typed_budget() {
+    let call_llm = create_node(|store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+        record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+        store.write().await.insert("text".into(), json!("done"));
+        store
+    });
+    let mut legacy = Flow::new();
+    legacy.add_node("call", call_llm);
+
+    let build = |budget: Budget| {
+        let mut typed = TypedFlow::<Strict, Triage>::new().with_budget(budget);
+        typed.add_result_node("legacy", Box::new(FlowTypedNode::new(legacy.clone())));
+        typed
+    };
+
+    // `llm_usage` is not read into a `deny_unknown_fields` state.
+    let result = build(Budget::new())
+        .run_safe(TypedStore::new(Strict::default()))
+        .await
+        .unwrap();
+    assert_eq!(result.inner.text, "done");
+    assert_eq!(result.context.usage_by_node["legacy"].calls, 2);
+    assert_eq!(result.context.token_usage, 40);
+
+    let err = build(Budget::new().with_max_calls(1))
+        .run_safe(TypedStore::new(Strict::default()))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, AgentFlowError::ExecutionLimitExceeded(msg) if msg.contains("legacy: 2 calls")),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,197 @@
+use agentflow::core::budget::{Budget, ModelPrice, PriceTable};
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::telemetry::{record_llm_usage, LlmUsage};
+use agentflow::core::typed_flow::{create_typed_node, TypedFlow};
+use agentflow::core::typed_store::TypedStore;
+use agentflow::prelude::*;
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Action {
+    Again,
+}
+
+fn prices() -> PriceTable {
+    PriceTable::from_json_str(
+        r#"{
+            "small": { "input_per_million": 1.0, "output_per_million": 2.0 },
+            "large": { "input_per_million": 10.0, "output_per_million": 30.0 }
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_price_table_costs() {
+    let table = prices().with_model("tiny", ModelPrice::new(0.1, 0.1));
+    // 1000 * 10 / 1M + 500 * 30 / 1M = 0.01 + 0.015
+    let cost = table.cost(&LlmUsage::new("large", 1000, 500)).unwrap();
+    assert!((cost - 0.025).abs() < 1e-12);
+    assert!(table.price("tiny").is_some());
+    assert_eq!(table.cost(&LlmUsage::new("unknown", 1, 1)), None);
+}
+
+#[tokio::test]
+async fn test_typed_flow_budget_stops_with_per_node_breakdown() {
+    let plan = create_typed_node(|mut s: TypedStore<u32>| async move {
+        s.context
+            .record_llm_call(LlmUsage::new("small", 1000, 1000));
+        (s, Some(Action::Again))
+    });
+    let write = create_typed_node(|mut s: TypedStore<u32>| async move {
+        s.inner += 1;
+        s.context
+            .record_llm_call(LlmUsage::new("large", 1000, 1000));
+        (s, Some(Action::Again))
+    });
+
+    let mut flow = TypedFlow::<u32, Action>::new()
+        .with_max_steps(100)
+        .with_budget(Budget::new().with_prices(prices()).with_max_cost(0.1));
+    flow.add_node("plan", plan);
+    flow.add_node("write", write);
+    flow.add_edge("plan", Action::Again, "write");
+    flow.add_edge("write", Action::Again, "write");
+
+    let err = flow.run_safe(TypedStore::new(0)).await.unwrap_err();
+    let AgentFlowError::ExecutionLimitExceeded(msg) = err else {
+        panic!("expected ExecutionLimitExceeded, got {err:?}");
+    };
+    assert!(msg.contains("cost"), "{msg}");
+    assert!(msg.contains("write: 3 calls"), "{msg}");
+    assert!(msg.contains("plan: 1 calls"), "{msg}");
+
+    // `run` halts at the same point and keeps the usage in the context.
+    let result = flow.run(TypedStore::new(0)).await;
+    assert!(result.limit_exceeded);
+    assert_eq!(result.inner, 3);
+    assert_eq!(result.context.usage_by_node["write"].calls, 3);
+    assert_eq!(result.context.usage_by_model["small"].calls, 1);
+    assert!(matches!(
+        result.last_error,
+        Some(AgentFlowError::ExecutionLimitExceeded(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_flow_budget_consumes_llm_usage_key() {
+    let call_llm = create_node(|store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+        record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+        store.write().await.insert("action".into(), json!("again"));
+        store
+    });
+
+    let mut flow = Flow::new()
+        .with_max_steps(50)
+        .with_budget(Budget::new().with_max_calls(5));
+    flow.add_node("call", call_llm);
+    flow.add_edge("call", "again", "call");
+
+    let err = flow.run_safe(Store::new().into_shared()).await.unwrap_err();
+    assert!(matches!(
+        &err,
+        AgentFlowError::ExecutionLimitExceeded(msg) if msg.contains("6 calls > 5") && msg.contains("call: 6 calls")
+    ));
+
+    let result = flow.run(Store::new().into_shared()).await;
+    let guard = result.read().await;
+    assert!(guard["error"].as_str().unwrap().contains("Budget exceeded"));
+    // The run's calls are handed back for an enclosing flow.
+    assert_eq!(guard["llm_usage"].as_array().unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn test_flow_context_is_kept_without_a_budget() {
+    let call_llm = create_node(|store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("small", 10, 5)).await;
+        store
+    });
+    let mut inner = Flow::new();
+    inner.add_node("call", call_llm);
+
+    let (result, context) = inner.run_with_context(Store::new().into_shared()).await;
+    assert_eq!(context.usage_by_node["call"].calls, 1);
+    assert_eq!(context.usage_by_model["small"].total_tokens(), 15);
+    assert_eq!(context.token_usage, 15);
+    assert_eq!(
+        result.read().await["llm_usage"].as_array().unwrap().len(),
+        1
+    );
+
+    // A nested flow's calls count towards the enclosing flow's budget.
+    let mut outer = Flow::new().with_budget(Budget::new().with_max_calls(1));
+    outer.add_node("first", Box::new(inner.clone()));
+    outer.add_node("second", Box::new(inner));
+    outer.add_edge("first", "default", "second");
+    let err = outer
+        .run_safe(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        AgentFlowError::ExecutionLimitExceeded(msg) if msg.contains("second: 1 calls")
+    ));
+}
+
+#[tokio::test]
+async fn test_typed_flow_budget_counts_add_tokens() {
+    let count = create_typed_node(|mut s: TypedStore<u32>| async move {
+        s.inner += 1;
+        s.context.add_tokens(600);
+        (s, Some(Action::Again))
+    });
+    let mut flow = TypedFlow::<u32, Action>::new()
+        .with_max_steps(10)
+        .with_budget(Budget::new().with_max_tokens(1000));
+    flow.add_node("count", count);
+    flow.add_edge("count", Action::Again, "count");
+
+    let result = flow.run(TypedStore::new(0)).await;
+    assert_eq!(result.inner, 2);
+    assert!(matches!(
+        &result.last_error,
+        Some(AgentFlowError::ExecutionLimitExceeded(msg)) if msg.contains("1200 tokens > 1000")
+    ));
+}
+
+#[tokio::test]
+async fn test_flow_settles_usage_of_a_failed_node() {
+    let cheap = create_node(|store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+        store
+    });
+    let runaway = create_result_node(|store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("small", 1000, 1000)).await;
+        Err(AgentFlowError::ExecutionLimitExceeded("step limit".into()))
+    });
+    let build = |budget: Option<Budget>| {
+        let mut flow = Flow::new();
+        if let Some(budget) = budget {
+            flow = flow.with_budget(budget);
+        }
+        flow.add_node("a", cheap.clone());
+        flow.add_result_node("b", runaway.clone());
+        flow.add_edge("a", "default", "b");
+        flow
+    };
+
+    let (result, context) = build(None)
+        .run_with_context(Store::new().into_shared())
+        .await;
+    assert_eq!(context.token_usage, 2020);
+    assert_eq!(context.usage_by_node["b"].total_tokens(), 2000);
+    let guard = result.read().await;
+    assert!(guard["error"].as_str().unwrap().contains("step limit"));
+    assert_eq!(guard["llm_usage"].as_array().unwrap().len(), 2);
+    drop(guard);
+
+    // `run_safe` returns the node's error and leaves every call in the store.
+    let store = Store::new().into_shared();
+    let err = build(Some(Budget::new().with_max_tokens(100)))
+        .run_safe(store.clone())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("step limit"), "{err}");
+    assert_eq!(store.read().await["llm_usage"].as_array().unwrap().len(), 2);
+}
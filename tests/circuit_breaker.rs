@@ -0,0 +1,263 @@
+use agentflow::core::circuit_breaker::{CircuitBreaker, CircuitState};
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::typed_flow::{create_typed_result_node, TypedFlow};
+use agentflow::core::typed_store::TypedStore;
+use agentflow::prelude::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A node whose upstream can be switched on and off, counting real calls.
+fn upstream(healthy: Arc<AtomicBool>, calls: Arc<AtomicUsize>) -> ResultNode {
+    create_result_node(move |store: SharedStore| {
+        let healthy = healthy.load(Ordering::SeqCst);
+        calls.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if healthy {
+                store
+                    .write()
+                    .await
+                    .insert("source".into(), json!("upstream"));
+                Ok(store)
+            } else {
+                Err(AgentFlowError::Timeout("upstream down".into()))
+            }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_breaker_opens_and_fails_fast_across_clones() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seen = events.clone();
+
+    let breaker = CircuitBreaker::new("llm")
+        .with_minimum_calls(3)
+        .with_failure_rate(0.6)
+        .with_cooldown(Duration::from_secs(60))
+        .on_state_change(move |e| seen.lock().unwrap().push((e.from, e.to)));
+    let node_a = breaker.wrap(upstream(healthy.clone(), calls.clone()));
+    // A second flow's node, guarded by a clone of the same breaker.
+    let node_b = breaker
+        .clone()
+        .wrap(upstream(healthy.clone(), calls.clone()));
+
+    for _ in 0..3 {
+        let err = node_a.call(Store::new().into_shared()).await.unwrap_err();
+        assert!(matches!(err, AgentFlowError::Timeout(_)));
+    }
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    let err = node_b.call(Store::new().into_shared()).await.unwrap_err();
+    assert!(matches!(&err, AgentFlowError::CircuitOpen(msg) if msg.contains("'llm'")));
+    assert!(!err.is_retryable());
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        3,
+        "open circuit must not call upstream"
+    );
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![(CircuitState::Closed, CircuitState::Open)]
+    );
+}
+
+#[tokio::test]
+async fn test_half_open_trial_closes_or_reopens() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let breaker = CircuitBreaker::new("search")
+        .with_minimum_calls(1)
+        .with_cooldown(Duration::from_millis(20));
+    let node = breaker.wrap(upstream(healthy.clone(), calls.clone()));
+
+    assert!(node.call(Store::new().into_shared()).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // Trial call fails: straight back to open.
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(node.call(Store::new().into_shared()).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Upstream recovers: trial succeeds and the circuit closes.
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(node.call(Store::new().into_shared()).await.is_ok());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(breaker.current_failure_rate(), 0.0);
+}
+
+#[tokio::test]
+async fn test_ignored_errors_do_not_affect_the_breaker() {
+    let breaker = CircuitBreaker::new("llm")
+        .with_minimum_calls(2)
+        .with_failure_rate(0.5)
+        .with_cooldown(Duration::from_millis(20));
+    let suspend = || async { Err::<(), _>(AgentFlowError::Suspended("approval".into())) };
+    let fail = || async { Err::<(), _>(AgentFlowError::Timeout("upstream down".into())) };
+
+    // Suspensions do not dilute the failure rate while closed.
+    for _ in 0..3 {
+        assert!(breaker.call(suspend).await.is_err());
+    }
+    assert!(breaker.call(fail).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.call(fail).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // A suspended trial neither closes nor reopens the circuit.
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(breaker.call(suspend).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(breaker.call(|| async { Ok(()) }).await.is_ok());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn test_straggler_does_not_count_as_half_open_trial() {
+    let release = Arc::new(tokio::sync::Notify::new());
+    let gate = release.clone();
+    // "slow" waits for `release` then succeeds; "fail" times out at once.
+    let node = CircuitBreaker::new("slow")
+        .with_minimum_calls(1)
+        .with_half_open_calls(2)
+        .with_cooldown(Duration::from_millis(20))
+        .wrap(create_result_node(move |store: SharedStore| {
+            let gate = gate.clone();
+            async move {
+                let mode = store.read().await.get("mode").cloned();
+                match mode {
+                    Some(m) if m == json!("slow") => gate.notified().await,
+                    Some(m) if m == json!("fail") => {
+                        return Err(AgentFlowError::Timeout("upstream down".into()))
+                    }
+                    _ => {}
+                }
+                Ok(store)
+            }
+        }));
+    let with_mode = |mode: &str| {
+        let store = Store::new().into_shared();
+        let mode = json!(mode);
+        async move {
+            store.write().await.insert("mode".into(), mode);
+            store
+        }
+    };
+
+    // The straggler is admitted while closed and finishes during half-open.
+    let straggler = node.call(with_mode("slow").await);
+    let rest = async {
+        assert!(node.call(with_mode("fail").await).await.is_err());
+        assert_eq!(node.breaker().state(), CircuitState::Open);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(node.call(with_mode("ok").await).await.is_ok());
+        assert_eq!(node.breaker().state(), CircuitState::HalfOpen);
+        release.notify_one();
+    };
+    let (straggler, ()) = tokio::join!(straggler, rest);
+    assert!(straggler.is_ok());
+    assert_eq!(node.breaker().state(), CircuitState::HalfOpen);
+
+    // The second real trial closes the circuit.
+    assert!(node.call(with_mode("ok").await).await.is_ok());
+    assert_eq!(node.breaker().state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn test_fallback_runs_only_for_breaker_failures() {
+    let cached = || {
+        create_result_node(|store: SharedStore| async move {
+            store.write().await.insert("source".into(), json!("cache"));
+            Ok(store)
+        })
+    };
+    let failing = |error: AgentFlowError| {
+        create_result_node(move |_store: SharedStore| {
+            let error = error.clone();
+            async move { Err::<SharedStore, _>(error) }
+        })
+    };
+    let breaker = CircuitBreaker::new("picky")
+        .with_failure_predicate(|e| matches!(e, AgentFlowError::Timeout(_)));
+
+    let timeout = breaker
+        .wrap(failing(AgentFlowError::Timeout("slow".into())))
+        .with_fallback(cached());
+    let result = timeout.call(Store::new().into_shared()).await.unwrap();
+    assert_eq!(result.read().await["source"], json!("cache"));
+
+    // Errors the predicate excludes, and HITL pauses, pass through.
+    for error in [
+        AgentFlowError::InvalidOutput("bad json".into()),
+        AgentFlowError::Suspended("await approval".into()),
+    ] {
+        let node = breaker.wrap(failing(error.clone())).with_fallback(cached());
+        assert_eq!(
+            node.call(Store::new().into_shared()).await.unwrap_err(),
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_breaker_combines_with_fallback_nodes_and_error_edges() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let breaker = CircuitBreaker::new("primary").with_minimum_calls(1);
+    let cached = create_result_node(|store: SharedStore| async move {
+        store.write().await.insert("source".into(), json!("cache"));
+        Ok(store)
+    });
+
+    let mut flow = Flow::new();
+    flow.add_result_node(
+        "answer",
+        Box::new(
+            breaker
+                .wrap(upstream(healthy.clone(), calls.clone()))
+                .with_fallback(cached),
+        ),
+    );
+    for _ in 0..2 {
+        let result = flow.run_safe(Store::new().into_shared()).await.unwrap();
+        assert_eq!(result.read().await["source"], json!("cache"));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Typed flows route the open circuit along an error edge.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Action {}
+    let typed_breaker = CircuitBreaker::new("typed").with_minimum_calls(1);
+    let failing = create_typed_result_node(|_s: TypedStore<String>| async move {
+        Err::<(TypedStore<String>, Option<Action>), _>(AgentFlowError::Timeout("down".into()))
+    });
+    let mut typed = TypedFlow::<String, Action>::new();
+    typed.add_result_node("call", Box::new(typed_breaker.wrap(failing)));
+    typed.add_result_node(
+        "degrade",
+        create_typed_result_node(|mut s: TypedStore<String>| async move {
+            s.inner = match s.last_error.take() {
+                Some(AgentFlowError::CircuitOpen(_)) => "skipped".into(),
+                other => format!("{other:?}"),
+            };
+            Ok((s, None))
+        }),
+    );
+    typed.add_error_edge("call", "degrade");
+
+    let first = typed
+        .run_safe(TypedStore::new(String::new()))
+        .await
+        .unwrap();
+    assert!(first.inner.contains("Timeout"));
+    let second = typed
+        .run_safe(TypedStore::new(String::new()))
+        .await
+        .unwrap();
+    assert_eq!(second.inner, "skipped");
+}
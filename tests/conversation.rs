@@ -0,0 +1,161 @@
+use agentflow::core::conversation::{ContentPart, ToolCall, CONVERSATION_KEY};
+use agentflow::core::error::AgentFlowError;
+use agentflow::patterns::hitl::create_conversation_hitl_node;
+use agentflow::prelude::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_messages_round_trip_and_accept_string_content() {
+    let convo = Conversation::new()
+        .with_message(ChatMessage::system("Be brief."))
+        .with_message(ChatMessage::user("What's in this picture?").with_image("https://x/cat.png"))
+        .with_message(ChatMessage::assistant("").with_tool_call(ToolCall::new(
+            "call_1",
+            "classify",
+            json!({"url": "https://x/cat.png"}),
+        )))
+        .with_message(ChatMessage::tool("call_1", "cat"));
+
+    let store = Store::new().into_shared();
+    convo.save(&store).await.unwrap();
+    let value = store.read().await[CONVERSATION_KEY].clone();
+    assert_eq!(value[1]["role"], json!("user"));
+    assert_eq!(value[1]["content"][1]["type"], json!("image_url"));
+    assert_eq!(value[3]["tool_call_id"], json!("call_1"));
+    assert_eq!(Conversation::load(&store).await.unwrap(), convo);
+
+    // OpenAI-style plain-string content is accepted too.
+    let msg: ChatMessage =
+        serde_json::from_value(json!({"role": "user", "content": "hi"})).unwrap();
+    assert_eq!(msg.content, vec![ContentPart::Text { text: "hi".into() }]);
+
+    store
+        .write()
+        .await
+        .insert(CONVERSATION_KEY.into(), json!("not a list"));
+    let err = Conversation::load(&store).await.unwrap_err();
+    assert!(matches!(
+        &err,
+        AgentFlowError::TypeMismatch(msg)
+            if msg.starts_with("Store key 'conversation' does not hold a valid Conversation:")
+    ));
+}
+
+#[test]
+fn test_window_and_trim_keep_system_prompt_and_drop_orphan_tool_results() {
+    let convo: Conversation = [
+        ChatMessage::system("rules"),
+        ChatMessage::user("q1"),
+        ChatMessage::assistant("").with_tool_call(ToolCall::new("c1", "search", json!({}))),
+        ChatMessage::tool("c1", "result"),
+        ChatMessage::assistant("a1"),
+        ChatMessage::user("q2"),
+    ]
+    .into_iter()
+    .collect();
+
+    let window = convo.window(3);
+    let texts: Vec<_> = window.messages.iter().map(ChatMessage::text).collect();
+    // The tool result whose request fell outside the window is dropped.
+    assert_eq!(texts, vec!["rules", "a1", "q2"]);
+
+    let mut trimmed = convo.clone();
+    trimmed.trim_to_chars(10);
+    assert_eq!(trimmed.messages[0].role, Role::System);
+    assert_eq!(trimmed.last_text(Role::User).as_deref(), Some("q2"));
+    assert!(trimmed.len() < convo.len());
+    assert_eq!(convo.window(10), convo);
+}
+
+#[tokio::test]
+async fn test_rag_and_hitl_take_turns_in_the_conversation() {
+    let retriever = create_node(|store: SharedStore| async move {
+        let query = store.read().await["query"].as_str().unwrap().to_string();
+        store
+            .write()
+            .await
+            .insert("context".into(), json!(format!("docs:{query}")));
+        store
+    });
+    let generator = create_node(|store: SharedStore| async move {
+        let ctx = store.read().await["context"].as_str().unwrap().to_string();
+        store
+            .write()
+            .await
+            .insert("response".into(), json!(format!("answer({ctx})")));
+        store
+    });
+    let rag = Rag::new(retriever, generator).with_conversation();
+    let wait_for_user = create_conversation_hitl_node("answer", "waiting for user");
+
+    let store = Store::new().into_shared();
+    let err = wait_for_user.call(store.clone()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::Suspended(_)));
+
+    for question in ["tokio", "axum"] {
+        Conversation::append(&store, ChatMessage::user(question))
+            .await
+            .unwrap();
+        let store = wait_for_user.call(store.clone()).await.unwrap();
+        assert_eq!(store.read().await["action"], json!("answer"));
+        rag.call(store.clone()).await;
+        assert!(wait_for_user.call(store.clone()).await.is_err());
+    }
+
+    let convo = Conversation::load(&store).await.unwrap();
+    assert_eq!(convo.len(), 4);
+    assert_eq!(
+        convo.last_text(Role::Assistant).as_deref(),
+        Some("answer(docs:axum)")
+    );
+
+    // A generator that produces nothing does not replay the last response.
+    let silent = Rag::new(
+        create_node(|store: SharedStore| async move { store }),
+        create_node(|store: SharedStore| async move { store }),
+    )
+    .with_conversation();
+    Conversation::append(&store, ChatMessage::user("tower"))
+        .await
+        .unwrap();
+    let store = silent.call(store).await;
+    let convo = Conversation::load(&store).await.unwrap();
+    assert_eq!(convo.len(), 5);
+    assert!(!store.read().await.contains_key("response"));
+}
+
+#[tokio::test]
+async fn test_agent_rolls_back_turns_from_failed_attempts() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let node = create_node(move |store: SharedStore| {
+        let attempt = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Conversation::append(&store, ChatMessage::assistant(format!("try {attempt}")))
+                .await
+                .unwrap();
+            let mut guard = store.write().await;
+            if attempt < 2 {
+                guard.insert("error".into(), json!("flaky"));
+            } else {
+                guard.remove("error");
+            }
+            drop(guard);
+            store
+        }
+    });
+
+    let store = Store::new().into_shared();
+    Conversation::append(&store, ChatMessage::user("hello"))
+        .await
+        .unwrap();
+    let agent = Agent::with_retry(node, 3, 0).with_conversation_rollback();
+    let result = agent.decide_shared(store).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    let convo = Conversation::load(&result).await.unwrap();
+    let texts: Vec<_> = convo.messages.iter().map(ChatMessage::text).collect();
+    assert_eq!(texts, vec!["hello", "try 2"]);
+}
@@ -0,0 +1,141 @@
+use agentflow::core::error::{AgentFlowError, ErrorContext};
+use agentflow::core::typed_flow::{create_typed_result_node, TypedFlow};
+use agentflow::core::typed_store::TypedStore;
+use agentflow::prelude::*;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+fn counting_node(first: AgentFlowError) -> ResultNode {
+    create_result_node(move |store: SharedStore| {
+        let first = first.clone();
+        async move {
+            let attempts = {
+                let guard = store.read().await;
+                guard.get("attempts").and_then(|v| v.as_i64()).unwrap_or(0)
+            } + 1;
+            store
+                .write()
+                .await
+                .insert("attempts".into(), serde_json::json!(attempts));
+            if attempts < 2 {
+                Err(first)
+            } else {
+                Ok(store)
+            }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_flow_with_id_wraps_node_errors_with_context() {
+    let ok = create_node(|store: SharedStore| async move {
+        store
+            .write()
+            .await
+            .insert("action".into(), serde_json::json!("next"));
+        store
+    });
+    let fail = create_result_node(|_store: SharedStore| async move {
+        Err::<SharedStore, _>(AgentFlowError::InvalidOutput("expected JSON".into()))
+    });
+
+    let mut flow = Flow::new().with_id("ingest-42");
+    flow.add_node("fetch", ok);
+    flow.add_result_node("parse", fail);
+    flow.add_edge("fetch", "next", "parse");
+
+    let err = flow.run_safe(Store::new().into_shared()).await.unwrap_err();
+    assert_eq!(
+        err.context(),
+        Some(
+            &ErrorContext::new()
+                .with_flow_id("ingest-42")
+                .with_node("parse")
+                .with_step(2)
+        )
+    );
+    assert!(matches!(err.root_cause(), AgentFlowError::InvalidOutput(_)));
+    assert_eq!(
+        err.source().map(|s| s.to_string()),
+        Some("Invalid output: expected JSON".to_string())
+    );
+    // Display shows the location only; the source chain carries the rest.
+    assert_eq!(err.to_string(), "[flow 'ingest-42', node 'parse', step 2]");
+    assert_eq!(
+        err.report(),
+        "[flow 'ingest-42', node 'parse', step 2]: Invalid output: expected JSON"
+    );
+
+    // Without an ID or `with_error_context`, errors are returned unchanged.
+    let mut plain = Flow::new();
+    plain.add_result_node(
+        "parse",
+        create_result_node(|_s: SharedStore| async move {
+            Err::<SharedStore, _>(AgentFlowError::Cancelled("shutdown".into()))
+        }),
+    );
+    let err = plain
+        .run_safe(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    assert_eq!(err, AgentFlowError::Cancelled("shutdown".into()));
+}
+
+#[tokio::test]
+async fn test_agent_retries_rate_limits_and_honours_retry_after() {
+    let node = counting_node(AgentFlowError::RateLimited {
+        message: "429 from provider".into(),
+        retry_after: Some(Duration::from_millis(30)),
+    });
+    let agent = Agent::with_retry(node, 3, 0);
+
+    let started = Instant::now();
+    let result = agent.run_result(Store::new().into_shared()).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(30));
+    assert_eq!(result.read().await["attempts"], serde_json::json!(2));
+
+    let fatal = counting_node(AgentFlowError::ToolFailure {
+        tool: "pdftotext".into(),
+        exit_code: Some(2),
+        stderr: "no such file".into(),
+    });
+    let err = Agent::with_retry(fatal, 3, 0)
+        .run_result(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    assert!(!err.is_retryable());
+    assert_eq!(
+        err.to_string(),
+        "Tool 'pdftotext' failed with exit code 2: no such file"
+    );
+}
+
+#[tokio::test]
+async fn test_typed_flow_error_context_reaches_error_edges() {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Action {}
+
+    let mut flow = TypedFlow::<Vec<String>, Action>::new().with_error_context();
+    flow.add_result_node(
+        "call",
+        create_typed_result_node(|_s: TypedStore<Vec<String>>| async move {
+            Err(AgentFlowError::Timeout("llm".into()))
+        }),
+    );
+    flow.add_result_node(
+        "recover",
+        create_typed_result_node(|mut s: TypedStore<Vec<String>>| async move {
+            let err = s.last_error.take().unwrap();
+            s.inner.push(err.report());
+            s.inner.push(err.is_retryable().to_string());
+            Ok((s, None))
+        }),
+    );
+    flow.add_error_edge("call", "recover");
+
+    let result = flow.run_safe(TypedStore::new(Vec::new())).await.unwrap();
+    assert_eq!(
+        result.inner,
+        vec!["[node 'call', step 1]: Timeout: llm", "true"]
+    );
+}
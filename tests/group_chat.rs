@@ -0,0 +1,154 @@
+use agentflow::core::conversation::Role;
+use agentflow::core::error::AgentFlowError;
+use agentflow::llm::{llm_node, MockLlm};
+use agentflow::patterns::group_chat::{GroupChat, TurnOrder, GROUP_CHAT_KEY};
+use agentflow::prelude::*;
+use serde_json::json;
+use std::sync::Arc;
+
+async fn topic(text: &str) -> SharedStore {
+    let store = Store::new().into_shared();
+    Conversation::append(&store, ChatMessage::user(text))
+        .await
+        .unwrap();
+    store
+}
+
+async fn speakers(store: &SharedStore) -> Vec<String> {
+    Conversation::load(store)
+        .await
+        .unwrap()
+        .messages
+        .iter()
+        .filter_map(|m| m.name.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_group_chat_round_robin_with_personas_and_keyword() {
+    let pro = Arc::new(
+        MockLlm::new("m")
+            .with_reply("Yes.")
+            .with_reply("Still yes."),
+    );
+    let con = Arc::new(MockLlm::new("m").with_reply("No. DONE"));
+    let debate = GroupChat::new()
+        .with_participant("pro", "Argue for.", llm_node(pro.clone()))
+        .with_participant("con", "Argue against.", llm_node(con.clone()))
+        .with_termination_keyword("DONE");
+
+    let store = debate.run(topic("Tabs or spaces?").await).await.unwrap();
+    assert_eq!(speakers(&store).await, vec!["pro", "con"]);
+    assert_eq!(
+        store.read().await[GROUP_CHAT_KEY],
+        json!({"turns": 2, "speakers": ["pro", "con"], "stop_reason": "keyword"})
+    );
+
+    // "con" saw its persona, the topic and pro's message as a named user turn.
+    let seen = &con.requests()[0].conversation.messages;
+    assert_eq!(seen[0].role, Role::System);
+    assert_eq!(seen[0].text(), "Argue against.");
+    assert_eq!(seen[1].text(), "Tabs or spaces?");
+    assert_eq!(seen[2].role, Role::User);
+    assert_eq!(seen[2].text(), "pro: Yes.");
+    assert_eq!(pro.remaining(), 1);
+
+    // The shared conversation keeps speakers as named assistant messages.
+    let shared = Conversation::load(&store).await.unwrap();
+    assert_eq!(shared.len(), 3);
+    assert_eq!(shared.messages[2].role, Role::Assistant);
+}
+
+#[tokio::test]
+async fn test_group_chat_mentions_and_max_turns() {
+    let reply = |text: &'static str| {
+        create_result_node(move |store: SharedStore| async move {
+            Conversation::append(&store, ChatMessage::assistant(text)).await?;
+            Ok(store)
+        })
+    };
+    let chat = GroupChat::new()
+        .with_participant("alice", "", reply("@carol what do you think?"))
+        .with_participant("bob", "", reply("Agreed."))
+        .with_participant("carol", "", reply("Over to @alice."))
+        .with_turn_order(TurnOrder::Mentions)
+        .with_max_turns(4);
+
+    let store = chat.run(topic("Plan the release").await).await.unwrap();
+    assert_eq!(
+        speakers(&store).await,
+        vec!["alice", "carol", "alice", "carol"]
+    );
+    assert_eq!(
+        store.read().await[GROUP_CHAT_KEY]["stop_reason"],
+        json!("max_turns")
+    );
+
+    let err = GroupChat::new()
+        .run(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AgentFlowError::GraphBuildError(_)));
+}
+
+#[tokio::test]
+async fn test_group_chat_mentions_match_whole_names() {
+    let reply = |text: &'static str| {
+        create_result_node(move |store: SharedStore| async move {
+            Conversation::append(&store, ChatMessage::assistant(text)).await?;
+            Ok(store)
+        })
+    };
+    let chat = GroupChat::new()
+        .with_participant("bob", "", reply("@alice, thoughts?"))
+        .with_participant("al", "", reply("Hi."))
+        .with_participant("alice", "", reply("@al.b over to you"))
+        .with_participant("al.b", "", reply("@al done"))
+        .with_turn_order(TurnOrder::Mentions)
+        .with_max_turns(4);
+
+    let store = chat.run(topic("Plan the release").await).await.unwrap();
+    assert_eq!(speakers(&store).await, vec!["bob", "alice", "al.b", "al"]);
+}
+
+#[tokio::test]
+async fn test_group_chat_selector_and_judge() {
+    let echo = |name: &'static str| {
+        create_result_node(move |store: SharedStore| async move {
+            Conversation::append(&store, ChatMessage::assistant(format!("{name} here"))).await?;
+            Ok(store)
+        })
+    };
+    // Always picks the last-listed participant.
+    let selector = create_result_node(|store: SharedStore| async move {
+        let last = store.read().await["participants"][2].clone();
+        store.write().await.insert("next_speaker".into(), last);
+        Ok(store)
+    });
+    // Ends the chat once two reviewer messages exist.
+    let judge = create_result_node(|store: SharedStore| async move {
+        let turns = Conversation::load(&store)
+            .await?
+            .messages
+            .iter()
+            .filter(|m| m.name.as_deref() == Some("reviewer"))
+            .count();
+        store
+            .write()
+            .await
+            .insert("terminate".into(), json!(turns >= 2));
+        Ok(store)
+    });
+    let chat = GroupChat::new()
+        .with_participant("author", "", echo("author"))
+        .with_participant("editor", "", echo("editor"))
+        .with_participant("reviewer", "", echo("reviewer"))
+        .with_turn_order(TurnOrder::Selector(selector))
+        .with_judge(judge);
+
+    let store = chat.run(topic("Review the PR").await).await.unwrap();
+    assert_eq!(speakers(&store).await, vec!["reviewer", "reviewer"]);
+    let guard = store.read().await;
+    assert_eq!(guard[GROUP_CHAT_KEY]["stop_reason"], json!("judge"));
+    assert!(!guard.contains_key("next_speaker"));
+}
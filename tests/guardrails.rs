@@ -0,0 +1,361 @@
+use agentflow::core::conversation::ContentPart;
+use agentflow::core::error::AgentFlowError;
+use agentflow::guardrails::{
+    custom, schema, BannedTerms, Finding, Guardrail, JsonSchema, MaxLength, Pii, PiiKind,
+    RegexCheck, Violation,
+};
+use agentflow::prelude::*;
+use serde_json::{json, Value};
+
+async fn store_with(key: &str, value: Value) -> SharedStore {
+    let store = Store::new().into_shared();
+    store.write().await.insert(key.into(), value);
+    store
+}
+
+#[tokio::test]
+async fn test_pii_redacts_emails_phones_and_cards() {
+    let finding = Pii::new()
+        .check(&json!(
+            "Reach jane.doe@example.com or +1 415-555-0132, card 4111 1111 1111 1111."
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        finding.redacted,
+        Some(json!("Reach [EMAIL] or [PHONE], card [CARD]."))
+    );
+    assert_eq!(finding.message, "contains card number, email, phone number");
+
+    // Digit runs failing the Luhn check are not cards.
+    let order = json!("Order 1234 5678 9012 3456 shipped");
+    assert_eq!(
+        Pii::only(&[PiiKind::Card]).check(&order).await.unwrap(),
+        None
+    );
+    assert_eq!(Pii::new().check(&json!("all clear")).await.unwrap(), None);
+
+    // A card number followed by more digits is still found.
+    let cards = Pii::only(&[PiiKind::Card]);
+    for (text, redacted) in [
+        ("Pay with 4111111111111111 12/27", "Pay with [CARD] 12/27"),
+        (
+            "Pay with 4111 1111 1111 1111 12 27",
+            "Pay with [CARD] 12 27",
+        ),
+        ("Ref 2024 5555-5555-5555-4444 ok", "Ref 2024 [CARD] ok"),
+    ] {
+        let finding = cards.check(&json!(text)).await.unwrap().unwrap();
+        assert_eq!(finding.redacted, Some(json!(redacted)));
+    }
+
+    // Long runs of digit groups are scanned in linear time.
+    let table = vec!["1"; 20_000].join(" ");
+    assert_eq!(cards.check(&json!(table)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_pii_redacts_nested_strings() {
+    let value = json!({"name": "Jane", "contacts": ["jane@example.com", 42]});
+    let finding = Pii::only(&[PiiKind::Email])
+        .check(&value)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        finding.redacted,
+        Some(json!({"name": "Jane", "contacts": ["[EMAIL]", 42]}))
+    );
+}
+
+#[tokio::test]
+async fn test_redact_rewrites_value_and_records_violations() {
+    let guard = Guardrails::new("response")
+        .with_check(Pii::new(), OnViolation::Redact)
+        .with_check(MaxLength::new(12), OnViolation::Redact);
+    let store = store_with("response", json!("Mail jane@example.com today")).await;
+
+    let store = guard.call(store).await.unwrap();
+    assert_eq!(store.read().await["response"], json!("Mail [EMAIL]"));
+
+    let violations = Violation::load(&store).await.unwrap();
+    let names: Vec<&str> = violations.iter().map(|v| v.guardrail.as_str()).collect();
+    assert_eq!(names, ["pii", "max_length"]);
+    assert!(violations
+        .iter()
+        .all(|v| v.target == "response" && v.action == OnViolation::Redact));
+}
+
+#[tokio::test]
+async fn test_block_fails_with_invalid_output_and_keeps_value() {
+    let guard = Guardrails::new("response")
+        .with_check(BannedTerms::new(&["Project Falcon"]), OnViolation::Block);
+    let store = store_with("response", json!("About project falcon: no comment")).await;
+
+    let err = guard.call(store.clone()).await.unwrap_err();
+    match err {
+        AgentFlowError::InvalidOutput(msg) => {
+            assert!(msg.contains("banned_terms"), "{msg}");
+            assert!(msg.contains("project falcon"), "{msg}");
+        }
+        other => panic!("expected InvalidOutput, got {other:?}"),
+    }
+    assert_eq!(
+        store.read().await["response"],
+        json!("About project falcon: no comment")
+    );
+    let violations = Violation::load(&store).await.unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].action, OnViolation::Block);
+}
+
+#[tokio::test]
+async fn test_route_sends_flow_to_handler() {
+    let guard = Guardrails::new("request").with_check(
+        BannedTerms::new(&["refund"]),
+        OnViolation::route("escalate"),
+    );
+    let answer = create_node(|store: SharedStore| async move {
+        store
+            .write()
+            .await
+            .insert("handled_by".into(), json!("bot"));
+        store
+    });
+    let escalate = create_node(|store: SharedStore| async move {
+        store
+            .write()
+            .await
+            .insert("handled_by".into(), json!("human"));
+        store
+    });
+
+    let build = || {
+        let mut flow = Flow::new();
+        flow.add_result_node("guard", Box::new(guard.clone()));
+        flow.add_node("answer", answer.clone());
+        flow.add_node("escalate", escalate.clone());
+        flow.add_edge("guard", "default", "answer");
+        flow.add_edge("guard", "escalate", "escalate");
+        flow
+    };
+
+    let store = build()
+        .run_safe(store_with("request", json!("I want a REFUND")).await)
+        .await
+        .unwrap();
+    assert_eq!(store.read().await["handled_by"], json!("human"));
+    // Routing leaves the value untouched.
+    assert_eq!(store.read().await["request"], json!("I want a REFUND"));
+    let violations = Violation::load(&store).await.unwrap();
+    assert_eq!(violations[0].action, OnViolation::route("escalate"));
+
+    let store = build()
+        .run_safe(store_with("request", json!("Where is my order?")).await)
+        .await
+        .unwrap();
+    assert_eq!(store.read().await["handled_by"], json!("bot"));
+    assert!(Violation::load(&store).await.unwrap().is_empty());
+
+    let corrupt = store_with("guardrail_violations", json!({"not": "a list"})).await;
+    match Violation::load(&corrupt).await {
+        Err(AgentFlowError::TypeMismatch(msg)) => {
+            assert!(msg.contains("valid Vec<Violation>"), "{msg}")
+        }
+        other => panic!("expected TypeMismatch, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_redact_without_redaction_blocks() {
+    let guard = Guardrails::new("response").with_check(MaxLength::new(5), OnViolation::Redact);
+    let store = store_with("response", json!({"long": "object"})).await;
+    let err = guard.call(store.clone()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::InvalidOutput(_)));
+    assert_eq!(
+        Violation::load(&store).await.unwrap()[0].action,
+        OnViolation::Block
+    );
+}
+
+#[tokio::test]
+async fn test_regex_check_with_replacement() {
+    let check = RegexCheck::new("ticket_id", r"TCK-\d+")
+        .unwrap()
+        .with_replacement("[TICKET]");
+    let finding = check
+        .check(&json!("See TCK-12 and TCK-345"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(finding.redacted, Some(json!("See [TICKET] and [TICKET]")));
+    assert_eq!(check.name(), "ticket_id");
+
+    assert!(matches!(
+        RegexCheck::new("broken", "(unclosed"),
+        Err(AgentFlowError::Custom(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_banned_terms_match_whole_words_only() {
+    let check = BannedTerms::new(&["ass"]);
+    assert_eq!(check.check(&json!("a classic pass")).await.unwrap(), None);
+    let finding = check.check(&json!("Ass!")).await.unwrap().unwrap();
+    assert_eq!(finding.redacted, Some(json!("[REDACTED]!")));
+
+    // Boundaries only apply on the sides where a term has a word character.
+    let check = BannedTerms::new(&["c++", "@admin"]);
+    let finding = check.check(&json!("I love c++.")).await.unwrap().unwrap();
+    assert_eq!(finding.redacted, Some(json!("I love [REDACTED].")));
+    let finding = check
+        .check(&json!("ping @admin now"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(finding.redacted, Some(json!("ping [REDACTED] now")));
+    assert_eq!(
+        check.check(&json!("abc++ and @admins")).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_json_schema_checks_raw_output() {
+    let check = JsonSchema::new(json!({
+        "type": "object",
+        "required": ["sentiment", "score"],
+        "additionalProperties": false,
+        "properties": {
+            "sentiment": {"enum": ["positive", "negative", "neutral"]},
+            "score": {"type": "number", "minimum": 0, "maximum": 1}
+        }
+    }));
+
+    let ok = json!(r#"{"sentiment": "positive", "score": 0.9}"#);
+    assert_eq!(check.check(&ok).await.unwrap(), None);
+
+    let finding = check
+        .check(&json!({"sentiment": "angry", "score": 3, "extra": true}))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        finding.message.contains("/sentiment"),
+        "{}",
+        finding.message
+    );
+    assert!(finding.message.contains("/score"), "{}", finding.message);
+    assert!(finding.message.contains("'extra'"), "{}", finding.message);
+    assert_eq!(finding.redacted, None);
+
+    let finding = check.check(&json!("not json")).await.unwrap().unwrap();
+    assert!(finding.message.starts_with("not valid JSON"));
+
+    let guard = Guardrails::new("response").with_check(check, OnViolation::Redact);
+    let store = store_with("response", json!(r#"{"sentiment": "meh"}"#)).await;
+    assert!(matches!(
+        guard.call(store).await,
+        Err(AgentFlowError::InvalidOutput(_))
+    ));
+}
+
+#[test]
+fn test_schema_combinators() {
+    let schema = json!({
+        "type": "array",
+        "minItems": 1,
+        "items": {"oneOf": [{"type": "integer"}, {"type": "string", "pattern": "^[a-z]+$"}]}
+    });
+    assert!(schema::validate(&schema, &json!([1, "abc"])).is_empty());
+    let errors = schema::validate(&schema, &json!([1.5, "ABC"]));
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("/0:"));
+    assert!(errors[1].starts_with("/1:"));
+    assert_eq!(schema::validate(&schema, &json!([])).len(), 1);
+}
+
+#[tokio::test]
+async fn test_schema_rejects_unsupported_keywords() {
+    let check = JsonSchema::new(json!({
+        "title": "Order",
+        "type": "object",
+        "properties": {"items": {"type": "array", "items": {"$ref": "#/$defs/item"}}},
+        "$defs": {"item": {"type": "string"}}
+    }));
+    let finding = check
+        .check(&json!({"items": ["a"]}))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        finding.message,
+        "schema /: unsupported keyword '$defs'; \
+         schema /properties/items/items: unsupported keyword '$ref'"
+    );
+
+    // Annotations are accepted without being checked.
+    let schema = json!({"type": "string", "description": "an id", "format": "uuid"});
+    assert!(schema::validate(&schema, &json!("not-a-uuid")).is_empty());
+}
+
+#[tokio::test]
+async fn test_custom_async_validator() {
+    let check = custom("no_shouting", |value| async move {
+        tokio::task::yield_now().await;
+        let text = value.as_str().unwrap_or_default();
+        let shouting = text.len() > 3 && text == text.to_uppercase();
+        Ok(shouting.then(|| Finding::new("shouting").with_redacted(json!(text.to_lowercase()))))
+    });
+    let guard = Guardrails::new("reply").with_check(check, OnViolation::Redact);
+
+    let store = guard
+        .call(store_with("reply", json!("STOP IT")).await)
+        .await
+        .unwrap();
+    assert_eq!(store.read().await["reply"], json!("stop it"));
+
+    let failing = custom("moderation", |_| async {
+        Err(AgentFlowError::Timeout("moderation API".into()))
+    });
+    let guard = Guardrails::new("reply").with_check(failing, OnViolation::Block);
+    let err = guard
+        .call(store_with("reply", json!("hi")).await)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AgentFlowError::Timeout(_)));
+}
+
+#[tokio::test]
+async fn test_last_message_target_redacts_text_and_keeps_images() {
+    let store = Store::new().into_shared();
+    Conversation::append(
+        &store,
+        ChatMessage::user("My card is 4111-1111-1111-1111").with_image("https://img/1.png"),
+    )
+    .await
+    .unwrap();
+
+    let guard = Guardrails::last_message().with_check(Pii::new(), OnViolation::Redact);
+    let store = guard.call(store).await.unwrap();
+
+    let conversation = Conversation::load(&store).await.unwrap();
+    let last = conversation.last().unwrap();
+    assert_eq!(last.text(), "My card is [CARD]");
+    assert!(last
+        .content
+        .iter()
+        .any(|part| matches!(part, ContentPart::ImageUrl { .. })));
+    assert_eq!(
+        Violation::load(&store).await.unwrap()[0].target,
+        "conversation"
+    );
+}
+
+#[tokio::test]
+async fn test_missing_value_is_skipped() {
+    let guard = Guardrails::new("response").with_check(Pii::new(), OnViolation::Block);
+    let store = guard.call(Store::new().into_shared()).await.unwrap();
+    assert!(Violation::load(&store).await.unwrap().is_empty());
+}
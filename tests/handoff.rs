@@ -0,0 +1,131 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::handoff::HANDOFF_CHAIN_KEY;
+use agentflow::prelude::*;
+use serde_json::json;
+
+fn handing_to(to: &'static str) -> ResultNode {
+    create_result_node(move |store: SharedStore| async move {
+        Handoff::new(to, &format!("over to {to}"))
+            .with_context(json!({"ticket": 7}))
+            .save(&store)
+            .await?;
+        Ok(store)
+    })
+}
+
+#[tokio::test]
+async fn test_flow_routes_on_handoff_and_records_chain() {
+    let triage = create_result_node(|store: SharedStore| async move {
+        // The handoff wins over the action.
+        store.write().await.insert("action".into(), json!("faq"));
+        Handoff::new("billing", "invoice question")
+            .with_context(json!({"invoice": "INV-42"}))
+            .save(&store)
+            .await?;
+        Ok(store)
+    });
+    let billing = create_result_node(|store: SharedStore| async move {
+        let handoff = Handoff::received(&store).await?.unwrap();
+        let reply = format!("{} for {}", handoff.reason, handoff.context["invoice"]);
+        store.write().await.insert("reply".into(), json!(reply));
+        Ok(store)
+    });
+    let faq = create_node(|store: SharedStore| async move {
+        store.write().await.insert("faq".into(), json!(true));
+        store
+    });
+
+    let mut flow = Flow::new();
+    flow.add_result_node("triage", triage);
+    flow.add_result_node("billing", billing);
+    flow.add_node("faq", faq);
+    flow.add_edge("triage", "faq", "faq");
+
+    let store = flow.run_safe(Store::new().into_shared()).await.unwrap();
+    let chain = Handoff::chain(&store).await.unwrap();
+    assert_eq!(chain.len(), 1);
+    assert_eq!(chain[0].from.as_deref(), Some("triage"));
+    assert_eq!(chain[0].to, "billing");
+
+    let guard = store.read().await;
+    assert_eq!(guard["reply"], json!("invoice question for \"INV-42\""));
+    assert!(!guard.contains_key("faq"));
+    assert!(!guard.contains_key("handoff"));
+    assert!(!guard.contains_key("action"));
+}
+
+#[tokio::test]
+async fn test_flow_bounds_handoff_loops() {
+    let build = |limit| {
+        let mut flow = Flow::new().with_max_steps(100).with_handoff_limit(limit);
+        flow.add_result_node("a", handing_to("b"));
+        flow.add_result_node("b", handing_to("a"));
+        flow
+    };
+
+    let err = build(2)
+        .run_safe(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    match err {
+        AgentFlowError::ExecutionLimitExceeded(msg) => {
+            assert!(msg.contains("b -> a -> b -> a -> b"), "{msg}")
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // `run` records the error and keeps the chain so far.
+    let store = build(1).run(Store::new().into_shared()).await;
+    let guard = store.read().await;
+    assert!(guard["error"].as_str().unwrap().contains("Handoff loop"));
+    assert_eq!(guard[HANDOFF_CHAIN_KEY].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_flow_rejects_handoff_to_unknown_node() {
+    let mut flow = Flow::new();
+    flow.add_result_node("a", handing_to("nobody"));
+    let err = flow.run_safe(Store::new().into_shared()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn test_received_handoff_is_cleared_after_edge_and_run() {
+    let received = |label: &'static str| {
+        create_result_node(move |store: SharedStore| async move {
+            let from = Handoff::received(&store).await?.map(|h| h.from);
+            let mut guard = store.write().await;
+            guard.insert(label.into(), json!(from));
+            guard.insert("action".into(), json!("next"));
+            drop(guard);
+            Ok(store)
+        })
+    };
+
+    let mut flow = Flow::new();
+    flow.add_result_node("triage", handing_to("billing"));
+    flow.add_result_node("billing", received("billing_from"));
+    flow.add_result_node("followup", received("followup_from"));
+    flow.add_edge("billing", "next", "followup");
+
+    let store = flow.run_safe(Store::new().into_shared()).await.unwrap();
+    {
+        let guard = store.read().await;
+        assert_eq!(guard["billing_from"], json!("triage"));
+        assert_eq!(guard["followup_from"], json!(null));
+        assert_eq!(guard[HANDOFF_CHAIN_KEY].as_array().unwrap().len(), 1);
+    }
+
+    // A reused store keeps the chain but not the received handoff.
+    let mut again = Flow::new();
+    again.add_result_node("start", received("start_from"));
+    let store = again.run_safe(store).await.unwrap();
+    assert_eq!(store.read().await["start_from"], json!(null));
+
+    // Later runs append to the chain until the key is removed.
+    let store = flow.run_safe(store).await.unwrap();
+    assert_eq!(Handoff::chain(&store).await.unwrap().len(), 2);
+    store.write().await.remove(HANDOFF_CHAIN_KEY);
+    let store = flow.run_safe(store).await.unwrap();
+    assert_eq!(Handoff::chain(&store).await.unwrap().len(), 1);
+}
@@ -0,0 +1,98 @@
+use agentflow::core::budget::Budget;
+use agentflow::core::conversation::ToolCall;
+use agentflow::core::error::AgentFlowError;
+use agentflow::llm::{
+    collect_stream, llm_node, LlmChunk, LlmClient, LlmRequest, MockLlm, ToolSpec,
+};
+use agentflow::prelude::*;
+use futures::StreamExt;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_mock_llm_scripts_replies_errors_and_streams() {
+    let llm = MockLlm::new("mock")
+        .with_reply("one two three")
+        .with_usage(10, 3)
+        .with_error(AgentFlowError::Timeout("slow".into()))
+        .with_tool_call(ToolCall::new("c1", "search", json!({"q": "x"})));
+    let request =
+        LlmRequest::new(Conversation::new().with_message(ChatMessage::user("count"))).with_tool(
+            ToolSpec::new("search", "Search the web", json!({"type": "object"})),
+        );
+
+    let chunks: Vec<_> = llm
+        .stream(request.clone())
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(chunks[0], LlmChunk::Text("one ".into()));
+    assert_eq!(chunks.len(), 4);
+
+    let err = llm.complete(request.clone()).await.unwrap_err();
+    assert!(err.is_retryable());
+
+    let stream = llm.stream(request.clone()).await.unwrap();
+    let response = collect_stream(stream, "mock").await.unwrap();
+    assert_eq!(response.tool_calls()[0].arguments, json!({"q": "x"}));
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(response.usage.input_tokens, 1);
+
+    assert_eq!(llm.remaining(), 0);
+    assert!(matches!(
+        llm.complete(request).await,
+        Err(AgentFlowError::NodeFailure(_))
+    ));
+    assert_eq!(llm.requests().len(), 4);
+    assert_eq!(llm.requests()[0].tools[0].name, "search");
+}
+
+#[tokio::test]
+async fn test_llm_node_appends_reply_and_reports_usage_to_budget() {
+    let llm = MockLlm::new("mock")
+        .with_reply("first")
+        .with_usage(40, 10)
+        .with_reply("second")
+        .with_usage(40, 10)
+        .with_reply("third");
+    let node = llm_node(Arc::new(llm.clone()))
+        .with_system_prompt("Be brief.")
+        .with_window(1);
+    let ask_again = create_node(|store: SharedStore| async move {
+        Conversation::append(&store, ChatMessage::user("more"))
+            .await
+            .unwrap();
+        store.write().await.insert("action".into(), json!("again"));
+        store
+    });
+
+    let mut flow = Flow::new()
+        .with_max_steps(20)
+        .with_budget(Budget::new().with_max_tokens(60));
+    flow.add_node("user", ask_again);
+    flow.add_result_node("llm", Box::new(node));
+    flow.add_edge("user", "again", "llm");
+    flow.add_edge("llm", "default", "user");
+
+    let store = Store::new().into_shared();
+    let err = flow.run_safe(store.clone()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::ExecutionLimitExceeded(_)));
+    assert_eq!(store.read().await["response"], json!("second"));
+
+    // The budget stopped the flow after the second call (100 tokens > 60).
+    assert_eq!(llm.remaining(), 1);
+    let requests = llm.requests();
+    let sent: Vec<_> = requests[1]
+        .conversation
+        .messages
+        .iter()
+        .map(ChatMessage::text)
+        .collect();
+    assert_eq!(sent, vec!["Be brief.", "more"]);
+
+    let convo = Conversation::load(&store).await.unwrap();
+    let texts: Vec<_> = convo.messages.iter().map(ChatMessage::text).collect();
+    assert_eq!(texts, vec!["more", "first", "more", "second"]);
+}
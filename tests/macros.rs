@@ -0,0 +1,109 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::typed_store::TypedStore;
+use agentflow::{typed_flow, typed_node, FlowAction};
+
+#[derive(Debug, Clone, Default)]
+struct Pipeline {
+    log: Vec<String>,
+    approved: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, FlowAction)]
+enum Action {
+    Review,
+    NeedsRevision,
+    #[action(rename = "ship")]
+    Approve,
+}
+
+#[typed_node]
+async fn draft(mut store: TypedStore<Pipeline>) -> (TypedStore<Pipeline>, Option<Action>) {
+    store.inner.log.push("draft".into());
+    (store, Some(Action::Review))
+}
+
+#[typed_node]
+async fn review(mut store: TypedStore<Pipeline>) -> (TypedStore<Pipeline>, Option<Action>) {
+    store.inner.log.push("review".into());
+    if store.inner.log.len() < 4 {
+        (store, Some(Action::NeedsRevision))
+    } else {
+        (store, Some(Action::Approve))
+    }
+}
+
+/// Fails until the pipeline has been approved.
+#[typed_node]
+async fn publish(
+    mut store: TypedStore<Pipeline>,
+) -> Result<(TypedStore<Pipeline>, Option<Action>), AgentFlowError> {
+    if !store.inner.approved {
+        return Err(AgentFlowError::NodeFailure("not approved".into()));
+    }
+    store.inner.log.push("publish".into());
+    Ok((store, None))
+}
+
+#[typed_node]
+async fn approve(mut store: TypedStore<Pipeline>) -> (TypedStore<Pipeline>, Option<Action>) {
+    store.inner.approved = true;
+    store.last_error = None;
+    (store, Some(Action::Approve))
+}
+
+#[test]
+fn test_derive_flow_action_names() {
+    assert_eq!(Action::Review.name(), "review");
+    assert_eq!(Action::NeedsRevision.name(), "needs_revision");
+    assert_eq!(Action::Approve.name(), "ship");
+    assert_eq!(Action::from_name("ship"), Some(Action::Approve));
+    assert_eq!(Action::from_name("Approve"), None);
+    assert_eq!(
+        Action::all(),
+        vec![Action::Review, Action::NeedsRevision, Action::Approve]
+    );
+}
+
+#[tokio::test]
+async fn test_typed_flow_macro_builds_runnable_graph() {
+    let flow = typed_flow! {
+        state: Pipeline,
+        action: Action,
+        max_steps: 10,
+        nodes: { draft, review },
+        edges: {
+            draft -> review: Action::Review,
+            review -> draft: Action::NeedsRevision,
+        },
+    };
+    assert!(flow.validate().is_ok());
+
+    let result = flow.run_safe(TypedStore::new(Pipeline::default())).await;
+    assert_eq!(
+        result.unwrap().inner.log,
+        vec!["draft", "review", "draft", "review"]
+    );
+}
+
+#[tokio::test]
+async fn test_typed_flow_macro_registers_result_nodes_and_error_edges() {
+    let flow = typed_flow! {
+        state: Pipeline,
+        action: Action,
+        max_steps: 5,
+        nodes: {
+            publish,
+            fix: approve(),
+        },
+        edges: { fix -> publish: Action::Approve },
+        error_edges: { publish -> fix },
+    };
+
+    let result = flow
+        .run_safe(TypedStore::new(Pipeline::default()))
+        .await
+        .unwrap();
+    assert!(result.inner.approved);
+    assert_eq!(result.inner.log, vec!["publish"]);
+    assert!(result.last_error.is_none());
+}
@@ -1,3 +1,5 @@
+use agentflow::core::telemetry::{record_llm_usage, LlmUsage};
+use agentflow::patterns::multi_agent::{AgentReport, Consensus, Dissent};
 use agentflow::prelude::*;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -64,3 +66,306 @@ async fn test_multi_agent_namespaced() {
         Some("data2")
     );
 }
+
+fn answering(answer: &'static str) -> Box<dyn Node<SharedStore, SharedStore>> {
+    create_node(move |store: SharedStore| async move {
+        store
+            .write()
+            .await
+            .insert("answer".into(), serde_json::json!(answer));
+        store
+    })
+}
+
+fn abstaining() -> Box<dyn Node<SharedStore, SharedStore>> {
+    create_node(|store: SharedStore| async move { store })
+}
+
+async fn vote(
+    strategy: MergeStrategy,
+    agents: Vec<Box<dyn Node<SharedStore, SharedStore>>>,
+) -> (Option<serde_json::Value>, Consensus) {
+    let mut multi = MultiAgent::with_strategy(strategy);
+    for agent in agents {
+        multi.add_agent(agent);
+    }
+    let store = multi.run(Arc::new(RwLock::new(HashMap::new()))).await;
+    let consensus = Consensus::load(&store).await.unwrap().unwrap();
+    let answer = store.read().await.get("answer").cloned();
+    (answer, consensus)
+}
+
+#[tokio::test]
+async fn test_multi_agent_majority_and_weighted_vote() {
+    let agents = || vec![answering("4"), answering("5"), answering("4"), abstaining()];
+
+    // "4" leads, but two of four agents is not a majority.
+    let (answer, consensus) = vote(MergeStrategy::majority_vote("answer"), agents()).await;
+    assert_eq!(answer, None);
+    assert!(!consensus.reached);
+    assert_eq!(consensus.value, Some(serde_json::json!("4")));
+    assert_eq!(consensus.agreement, 0.5);
+    assert_eq!(consensus.supporters, vec![0, 2]);
+    assert_eq!(
+        consensus.dissent,
+        vec![Dissent {
+            agent: 1,
+            value: serde_json::json!("5")
+        }]
+    );
+    assert_eq!(consensus.abstained, vec![3]);
+
+    let mut majority = agents();
+    majority[3] = answering("4");
+    let (answer, consensus) = vote(MergeStrategy::majority_vote("answer"), majority).await;
+    assert_eq!(answer, Some(serde_json::json!("4")));
+    assert!(consensus.reached);
+    assert_eq!(consensus.agreement, 0.75);
+
+    let weights = vec![1.0, 4.0, 1.0, 1.0];
+    let (answer, consensus) = vote(MergeStrategy::weighted_vote("answer", weights), agents()).await;
+    assert_eq!(answer, Some(serde_json::json!("5")));
+    assert!((consensus.agreement - 4.0 / 7.0).abs() < 1e-9);
+    assert_eq!(consensus.supporters, vec![1]);
+}
+
+#[tokio::test]
+async fn test_multi_agent_vote_ignores_stale_input_value() {
+    let mut multi = MultiAgent::with_strategy(MergeStrategy::quorum("answer", 1));
+    multi.add_agent(abstaining());
+    multi.add_agent(abstaining());
+    multi.add_agent(answering("fresh"));
+    let store = Arc::new(RwLock::new(HashMap::from([(
+        "answer".to_string(),
+        serde_json::json!("stale"),
+    )])));
+
+    let store = multi.run(store).await;
+    let consensus = Consensus::load(&store).await.unwrap().unwrap();
+    assert_eq!(consensus.abstained, vec![0, 1]);
+    assert_eq!(consensus.supporters, vec![2]);
+    assert_eq!(
+        store.read().await.get("answer"),
+        Some(&serde_json::json!("fresh"))
+    );
+}
+
+#[tokio::test]
+async fn test_multi_agent_quorum() {
+    let agents = || vec![answering("a"), answering("b"), answering("a")];
+
+    let (answer, consensus) = vote(MergeStrategy::quorum("answer", 2), agents()).await;
+    assert_eq!(answer, Some(serde_json::json!("a")));
+    assert!(consensus.reached);
+
+    let (answer, consensus) = vote(MergeStrategy::quorum("answer", 3), agents()).await;
+    assert_eq!(answer, None);
+    assert!(!consensus.reached);
+    assert_eq!(consensus.value, Some(serde_json::json!("a")));
+}
+
+#[tokio::test]
+async fn test_multi_agent_judge_picks_candidate() {
+    // Picks the longest candidate.
+    let judge = create_node(|store: SharedStore| async move {
+        let choice = {
+            let guard = store.read().await;
+            guard["candidates"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .max_by_key(|c| c["value"].as_str().unwrap().len())
+                .map(|c| c["agent"].clone())
+                .unwrap()
+        };
+        store.write().await.insert("choice".into(), choice);
+        store
+    });
+    let agents = vec![answering("short"), abstaining(), answering("much longer")];
+    let (answer, consensus) = vote(MergeStrategy::judge("answer", judge), agents).await;
+    assert_eq!(answer, Some(serde_json::json!("much longer")));
+    assert_eq!(consensus.supporters, vec![2]);
+    assert!((consensus.agreement - 1.0 / 3.0).abs() < 1e-9);
+
+    let indecisive = create_node(|store: SharedStore| async move { store });
+    let (answer, consensus) = vote(
+        MergeStrategy::judge("answer", indecisive),
+        vec![answering("x")],
+    )
+    .await;
+    assert_eq!(answer, None);
+    assert!(!consensus.reached);
+}
+
+#[tokio::test]
+async fn test_multi_agent_isolates_panics_and_timeouts() {
+    let mut multi = MultiAgent::with_strategy(MergeStrategy::Namespaced)
+        .with_agent_timeout(std::time::Duration::from_millis(50))
+        .with_failure_policy(FailurePolicy::MaxFailures(2));
+    multi.add_agent(answering("fast"));
+    multi.add_agent(create_node(|_store: SharedStore| async move {
+        panic!("provider exploded");
+    }));
+    multi.add_agent(create_node(|store: SharedStore| async move {
+        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        store
+    }));
+
+    let started = std::time::Instant::now();
+    let store = multi
+        .try_run(Arc::new(RwLock::new(HashMap::new())))
+        .await
+        .unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let report = AgentReport::load(&store).await.unwrap().unwrap();
+    assert_eq!(report.succeeded, vec![0]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].agent, 1);
+    assert!(report.failed[0].error.contains("provider exploded"));
+    assert_eq!(report.timed_out, vec![2]);
+    assert!(report.policy_met);
+
+    let state = store.read().await;
+    assert_eq!(
+        state.get("agent_0.answer"),
+        Some(&serde_json::json!("fast"))
+    );
+    assert!(!state.keys().any(|k| k.starts_with("agent_1.")));
+}
+
+#[tokio::test]
+async fn test_multi_agent_failure_policy() {
+    let mut multi = MultiAgent::with_strategy(MergeStrategy::majority_vote("answer"))
+        .with_failure_policy(FailurePolicy::MinSuccesses(3));
+    multi.add_agent(answering("x"));
+    multi.add_agent(answering("x"));
+    multi.add_agent(create_node(|_store: SharedStore| async move {
+        panic!("boom");
+    }));
+
+    // `run` records the violation but still merges the survivors.
+    let store = multi.run(Arc::new(RwLock::new(HashMap::new()))).await;
+    let report = AgentReport::load(&store).await.unwrap().unwrap();
+    assert!(!report.policy_met);
+    assert_eq!(
+        store.read().await.get("answer"),
+        Some(&serde_json::json!("x"))
+    );
+    let consensus = Consensus::load(&store).await.unwrap().unwrap();
+    assert_eq!(consensus.abstained, vec![2]);
+
+    let err = multi
+        .try_run(Arc::new(RwLock::new(HashMap::new())))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        agentflow::core::error::AgentFlowError::NodeFailure(_)
+    ));
+
+    let tolerant = multi.with_failure_policy(FailurePolicy::MinSuccesses(2));
+    assert!(tolerant
+        .try_run(Arc::new(RwLock::new(HashMap::new())))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_multi_agent_custom_merge_sees_failed_slots() {
+    let merge = MergeStrategy::Custom(Arc::new(|stores: Vec<Option<SharedStore>>| {
+        let finished: Vec<bool> = stores.iter().map(Option::is_some).collect();
+        let mut merged = HashMap::new();
+        merged.insert("finished".to_string(), serde_json::json!(finished));
+        Arc::new(RwLock::new(merged))
+    }));
+    let mut multi =
+        MultiAgent::with_strategy(merge).with_failure_policy(FailurePolicy::MaxFailures(1));
+    multi.add_agent(create_node(|_store: SharedStore| async move {
+        panic!("first agent down");
+    }));
+    multi.add_agent(answering("ok"));
+
+    let store = multi.run(Arc::new(RwLock::new(HashMap::new()))).await;
+    assert_eq!(
+        store.read().await.get("finished"),
+        Some(&serde_json::json!([false, true]))
+    );
+}
+
+#[tokio::test]
+async fn test_multi_agent_dropped_run_aborts_agents() {
+    let finished = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let flag = finished.clone();
+    let mut multi = MultiAgent::with_strategy(MergeStrategy::Namespaced);
+    multi.add_agent(create_node(move |store: SharedStore| {
+        let flag = flag.clone();
+        async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            store
+        }
+    }));
+
+    let run = multi.run(Arc::new(RwLock::new(HashMap::new())));
+    assert!(
+        tokio::time::timeout(std::time::Duration::from_millis(10), run)
+            .await
+            .is_err()
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!finished.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+fn answering_with_usage(answer: &'static str) -> Box<dyn Node<SharedStore, SharedStore>> {
+    create_node(move |store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+        store
+            .write()
+            .await
+            .insert("answer".into(), serde_json::json!(answer));
+        store
+    })
+}
+
+#[tokio::test]
+async fn test_multi_agent_forwards_llm_usage_of_every_agent() {
+    let judge = create_node(|store: SharedStore| async move {
+        record_llm_usage(&store, LlmUsage::new("large", 10, 10)).await;
+        store
+            .write()
+            .await
+            .insert("choice".into(), serde_json::json!(0));
+        store
+    });
+    let failing = || {
+        create_node(|store: SharedStore| async move {
+            record_llm_usage(&store, LlmUsage::new("small", 10, 10)).await;
+            panic!("agent down after a call");
+        })
+    };
+    let build = |strategy: MergeStrategy| {
+        let mut multi = MultiAgent::with_strategy(strategy);
+        multi.add_agent(answering_with_usage("4"));
+        multi.add_agent(answering_with_usage("4"));
+        multi.add_agent(failing());
+        let mut flow = Flow::new();
+        flow.add_node("ensemble", Box::new(multi));
+        flow
+    };
+
+    for (strategy, calls) in [
+        (MergeStrategy::majority_vote("answer"), 3),
+        (MergeStrategy::Namespaced, 3),
+        (MergeStrategy::judge("answer", judge), 4),
+    ] {
+        let input = Arc::new(RwLock::new(HashMap::new()));
+        record_llm_usage(&input, LlmUsage::new("small", 1, 1)).await;
+        let (store, context) = build(strategy).run_with_context(input).await;
+        assert_eq!(context.usage_by_node["ensemble"].calls, calls);
+        let guard = store.read().await;
+        // The input's own entry is handed back once, not copied per agent.
+        assert_eq!(guard["llm_usage"].as_array().unwrap().len(), calls + 1);
+        assert!(!guard.keys().any(|k| k.ends_with(".llm_usage")));
+    }
+}
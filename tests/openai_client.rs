@@ -0,0 +1,144 @@
+#![cfg(feature = "openai")]
+
+use agentflow::core::conversation::ToolCall;
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::secret::SecretString;
+use agentflow::llm::{collect_stream, LlmClient, LlmRequest, OpenAiClient, ToolSpec};
+use agentflow::prelude::*;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Seen = Arc<Mutex<Vec<(Option<String>, Value)>>>;
+
+async fn completions(
+    State(seen): State<Seen>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let auth = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    seen.lock().unwrap().push((auth, body.clone()));
+    let last = body["messages"].as_array().unwrap().last().unwrap().clone();
+    if last["content"] == json!("limit") {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [("retry-after", "2")],
+            "slow down",
+        )
+            .into_response();
+    }
+    if body["stream"] == json!(true) {
+        let events = [
+            json!({"choices": [{"delta": {"role": "assistant", "content": "Hel"}}]}),
+            json!({"choices": [{"delta": {"content": "lo"}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "id": "c9", "function": {"name": "lookup", "arguments": "{\"k\":"}}]}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": "1}"}}]}}]}),
+            json!({"choices": [], "usage": {"prompt_tokens": 7, "completion_tokens": 2}}),
+        ];
+        let mut sse: String = events.iter().map(|e| format!("data: {e}\n\n")).collect();
+        sse.push_str("data: [DONE]\n\n");
+        return ([("content-type", "text/event-stream")], sse).into_response();
+    }
+    Json(json!({
+        "choices": [{
+            "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "search", "arguments": "{\"q\":\"rust\"}"}
+                }]
+            },
+            "finish_reason": "tool_calls"
+        }],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 5}
+    }))
+    .into_response()
+}
+
+async fn stub_server() -> (String, Seen) {
+    let seen: Seen = Arc::default();
+    let app = Router::new()
+        .route("/v1/chat/completions", post(completions))
+        .with_state(seen.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}/v1/"), seen)
+}
+
+#[tokio::test]
+async fn test_openai_client_sends_wire_format_and_parses_tool_calls() {
+    let (url, seen) = stub_server().await;
+    let client = OpenAiClient::new(&url, "gpt-test")
+        .with_api_key(SecretString::new("sk-test"))
+        .with_timeout(Duration::from_secs(5));
+
+    let conversation = Conversation::new()
+        .with_message(ChatMessage::user("What is this?").with_image("https://x/cat.png"))
+        .with_message(ChatMessage::assistant("").with_tool_call(ToolCall::new(
+            "c0",
+            "classify",
+            json!({"n": 1}),
+        )))
+        .with_message(ChatMessage::tool("c0", "cat"));
+    let request = LlmRequest::new(conversation)
+        .with_tool(ToolSpec::new("search", "Search", json!({"type": "object"})))
+        .with_temperature(0.0);
+    let response = client.complete(request).await.unwrap();
+
+    assert_eq!(response.tool_calls()[0].name, "search");
+    assert_eq!(response.tool_calls()[0].arguments, json!({"q": "rust"}));
+    assert_eq!(response.usage.model, "gpt-test");
+    assert_eq!(response.usage.total_tokens(), 17);
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+
+    let (auth, body) = seen.lock().unwrap()[0].clone();
+    assert_eq!(auth.as_deref(), Some("Bearer sk-test"));
+    assert_eq!(body["model"], json!("gpt-test"));
+    assert_eq!(body["tools"][0]["function"]["name"], json!("search"));
+    let messages = &body["messages"];
+    assert_eq!(
+        messages[0]["content"][1]["image_url"]["url"],
+        json!("https://x/cat.png")
+    );
+    assert_eq!(messages[1]["content"], Value::Null);
+    assert_eq!(
+        messages[1]["tool_calls"][0]["function"]["arguments"],
+        json!("{\"n\":1}")
+    );
+    assert_eq!(messages[2]["tool_call_id"], json!("c0"));
+}
+
+#[tokio::test]
+async fn test_openai_client_streams_and_maps_rate_limits() {
+    let (url, seen) = stub_server().await;
+    let client = OpenAiClient::new(&url, "gpt-test");
+
+    let request = LlmRequest::new(Conversation::new().with_message(ChatMessage::user("hi")));
+    let stream = client.stream(request).await.unwrap();
+    let response = collect_stream(stream, client.model()).await.unwrap();
+    assert_eq!(response.text(), "Hello");
+    assert_eq!(response.tool_calls()[0].id, "c9");
+    assert_eq!(response.tool_calls()[0].arguments, json!({"k": 1}));
+    assert_eq!(response.usage.input_tokens, 7);
+    assert_eq!(
+        seen.lock().unwrap()[0].1["stream_options"]["include_usage"],
+        json!(true)
+    );
+    assert_eq!(seen.lock().unwrap()[0].0, None);
+
+    let request = LlmRequest::new(Conversation::new().with_message(ChatMessage::user("limit")));
+    let err = client.complete(request).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::RateLimited { .. }));
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(2)));
+}
@@ -0,0 +1,159 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::patterns::plan_execute::{Plan, PlanExecute, PlanStep, StepStatus};
+use agentflow::prelude::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn diamond() -> Plan {
+    Plan::new("report")
+        .with_step(PlanStep::new("a", "research A"))
+        .with_step(PlanStep::new("b", "research B"))
+        .with_step(PlanStep::new("c", "write").after("a").after("b"))
+}
+
+#[tokio::test]
+async fn test_plan_execute_runs_independent_steps_in_parallel() {
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (r, p) = (running.clone(), peak.clone());
+    let agent = PlanExecute::new(
+        |_store| async move { Ok(diamond()) },
+        move |input| {
+            let (running, peak) = (r.clone(), p.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                let mut deps: Vec<_> = input.dependencies.values().cloned().collect();
+                deps.sort_by_key(|v| v.to_string());
+                Ok(json!({"task": input.step.task, "inputs": deps}))
+            }
+        },
+    );
+
+    let store = agent.run(Store::new().into_shared()).await.unwrap();
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+    let plan = Plan::load(&store).await.unwrap().unwrap();
+    assert!(plan.is_complete());
+    let c = plan.step("c").unwrap();
+    assert_eq!(c.status, StepStatus::Done);
+    assert_eq!(
+        c.result.as_ref().unwrap()["inputs"][0]["task"],
+        json!("research A")
+    );
+}
+
+#[tokio::test]
+async fn test_plan_execute_replans_failed_steps() {
+    let executor = |input: agentflow::patterns::plan_execute::StepInput| async move {
+        if input.step.task.contains("flaky") {
+            Err(AgentFlowError::Timeout("upstream".into()))
+        } else {
+            Ok(json!(input.step.task))
+        }
+    };
+    let plan = || async {
+        Ok(Plan::sequential(
+            "goal",
+            ["fetch", "flaky parse", "summarise"],
+        ))
+    };
+
+    // Without a replanner the failure is reported and checkpointed.
+    let agent = PlanExecute::new(move |_| plan(), executor);
+    let store = Store::new().into_shared();
+    let err = agent.run(store.clone()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::NodeFailure(_)));
+    let failed = Plan::load(&store).await.unwrap().unwrap();
+    assert_eq!(failed.step("1").unwrap().status, StepStatus::Done);
+    assert_eq!(failed.step("2").unwrap().status, StepStatus::Failed);
+    assert!(failed
+        .step("2")
+        .unwrap()
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("upstream"));
+    assert_eq!(failed.step("3").unwrap().status, StepStatus::Pending);
+
+    let replans = Arc::new(AtomicUsize::new(0));
+    let counter = replans.clone();
+    let agent = agent.with_replanner(move |mut plan: Plan, _store| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            for step in &mut plan.steps {
+                if step.status == StepStatus::Failed {
+                    step.task = "careful parse".into();
+                    step.status = StepStatus::Pending;
+                }
+            }
+            Ok(plan)
+        }
+    });
+    // Resuming the failed checkpoint goes straight to the replanner.
+    let store = agent.run(store).await.unwrap();
+    let plan = Plan::load(&store).await.unwrap().unwrap();
+    assert!(plan.is_complete());
+    assert_eq!(plan.revision, 1);
+    assert_eq!(replans.load(Ordering::SeqCst), 1);
+    assert_eq!(plan.step("2").unwrap().result, Some(json!("careful parse")));
+}
+
+#[tokio::test]
+async fn test_plan_execute_resumes_checkpoint_and_validates() {
+    let mut checkpoint = diamond();
+    checkpoint.steps[0].status = StepStatus::Done;
+    checkpoint.steps[0].result = Some(json!("A"));
+    checkpoint.steps[1].status = StepStatus::Running;
+    let store = Store::new().into_shared();
+    checkpoint.save(&store).await.unwrap();
+
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = calls.clone();
+    let agent = PlanExecute::new(
+        |_store| async move { panic!("planner must not run when resuming") },
+        move |input| {
+            seen.lock().unwrap().push(input.step.id.clone());
+            async move { Ok(json!(input.dependencies.len())) }
+        },
+    )
+    .with_concurrency_limit(1);
+    let store = agent.run(store).await.unwrap();
+    assert_eq!(*calls.lock().unwrap(), vec!["b", "c"]);
+    let plan = Plan::load(&store).await.unwrap().unwrap();
+    assert_eq!(plan.step("c").unwrap().result, Some(json!(2)));
+
+    let cyclic = Plan::new("loop")
+        .with_step(PlanStep::new("x", "x").after("y"))
+        .with_step(PlanStep::new("y", "y").after("x"));
+    assert!(matches!(
+        cyclic.validate(),
+        Err(AgentFlowError::GraphBuildError(_))
+    ));
+    let dangling = Plan::new("bad").with_step(PlanStep::new("x", "x").after("nope"));
+    assert!(dangling.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_plan_execute_replans_after_a_completed_plan() {
+    let goals = Arc::new(AtomicUsize::new(0));
+    let counter = goals.clone();
+    let agent = PlanExecute::new(
+        move |_store| {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { Ok(Plan::new(&format!("goal {n}")).with_step(PlanStep::new("a", "do"))) }
+        },
+        |input| async move { Ok(json!(input.step.task)) },
+    );
+
+    let store = agent.run(Store::new().into_shared()).await.unwrap();
+    let store = agent.run(store).await.unwrap();
+    assert_eq!(goals.load(Ordering::SeqCst), 2);
+    let plan = Plan::load(&store).await.unwrap().unwrap();
+    assert_eq!(plan.goal, "goal 2");
+    assert!(plan.is_complete());
+}
@@ -0,0 +1,131 @@
+use agentflow::core::conversation::ToolCall;
+use agentflow::core::error::AgentFlowError;
+use agentflow::llm::{llm_node, MockLlm, ToolSpec};
+use agentflow::patterns::react::ReactAgent;
+use agentflow::prelude::*;
+use serde_json::json;
+use std::sync::Arc;
+
+fn calculator() -> ToolSpec {
+    ToolSpec::new(
+        "add",
+        "Add two numbers",
+        json!({"type": "object", "properties": {"a": {"type": "number"}, "b": {"type": "number"}}}),
+    )
+}
+
+async fn ask(question: &str) -> SharedStore {
+    let store = Store::new().into_shared();
+    Conversation::append(&store, ChatMessage::user(question))
+        .await
+        .unwrap();
+    store
+}
+
+#[tokio::test]
+async fn test_react_agent_runs_tools_and_answers() {
+    let llm = MockLlm::new("mock")
+        .with_message(
+            ChatMessage::assistant("I need to add.")
+                .with_tool_call(ToolCall::new("c1", "add", json!({"a": 2, "b": 3})))
+                .with_tool_call(ToolCall::new("c2", "weather", json!({}))),
+        )
+        .with_reply("2 + 3 = 5");
+    let agent = ReactAgent::new(llm_node(Arc::new(llm.clone())).with_tool(calculator())).with_tool(
+        calculator(),
+        |args| async move {
+            Ok(json!(
+                args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap()
+            ))
+        },
+    );
+
+    let store = agent.run(ask("What is 2 + 3?").await).await.unwrap();
+    let guard = store.read().await;
+    assert_eq!(guard["answer"], json!("2 + 3 = 5"));
+
+    let trajectory = guard["trajectory"].as_array().unwrap();
+    assert_eq!(trajectory.len(), 4);
+    assert_eq!(trajectory[0]["thought"], json!("I need to add."));
+    assert_eq!(trajectory[1]["observation"], json!("5"));
+    assert_eq!(trajectory[2]["error"], json!(true));
+    assert_eq!(trajectory[3]["answer"], json!("2 + 3 = 5"));
+    drop(guard);
+
+    // The second LLM call saw both observations, matched by tool-call ID.
+    let sent = &llm.requests()[1].conversation;
+    assert_eq!(sent.messages[2].tool_call_id.as_deref(), Some("c1"));
+    assert_eq!(sent.messages[2].text(), "5");
+    assert!(sent.messages[3].text().starts_with("Error: Not found"));
+    assert_eq!(llm.requests()[0].tools[0].name, "add");
+}
+
+#[tokio::test]
+async fn test_react_agent_final_answer_tool_and_step_limit() {
+    let llm = MockLlm::new("mock").with_message(
+        ChatMessage::assistant("")
+            .with_tool_call(ToolCall::new(
+                "c1",
+                "final_answer",
+                json!({"answer": "Vienna"}),
+            ))
+            .with_tool_call(ToolCall::new("c2", "echo", json!({}))),
+    );
+    let agent = ReactAgent::new(llm_node(Arc::new(llm))).with_final_answer_tool("final_answer");
+    assert_eq!(agent.tool_specs()[0].name, "final_answer");
+    let store = agent.run(ask("Capital of Austria?").await).await.unwrap();
+    assert_eq!(store.read().await["answer"], json!("Vienna"));
+    // Both calls, the skipped one included, have a result.
+    let convo = Conversation::load(&store).await.unwrap();
+    let answered: Vec<_> = convo.messages[2..]
+        .iter()
+        .map(|m| (m.role, m.tool_call_id.as_deref()))
+        .collect();
+    assert_eq!(
+        answered,
+        vec![(Role::Tool, Some("c1")), (Role::Tool, Some("c2"))]
+    );
+
+    let looping = (0..5).fold(MockLlm::new("mock"), |llm, i| {
+        llm.with_tool_call(ToolCall::new(&format!("c{i}"), "echo", json!({"i": i})))
+    });
+    let agent = ReactAgent::new(llm_node(Arc::new(looping)))
+        .with_tool(
+            ToolSpec::new("echo", "Echo", json!({"type": "object"})),
+            |args| async move { Ok(args) },
+        )
+        .with_max_steps(3);
+    let store = ask("loop").await;
+    let err = agent.run(store.clone()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::ExecutionLimitExceeded(_)));
+    // The partial trajectory is still visible.
+    assert_eq!(
+        store.read().await["trajectory"].as_array().unwrap().len(),
+        3
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_react_agent_calls_registry_tools() {
+    let mut registry = ToolRegistry::new();
+    registry.register("greet", "echo", vec!["hello".into()], None);
+    registry.register("fail", "false", vec![], None);
+    let llm = MockLlm::new("mock")
+        .with_message(
+            ChatMessage::assistant("")
+                .with_tool_call(ToolCall::new("c1", "greet", json!({})))
+                .with_tool_call(ToolCall::new("c2", "fail", json!({}))),
+        )
+        .with_reply("done");
+    let agent = ReactAgent::new(llm_node(Arc::new(llm))).with_registry(registry.into_arc());
+
+    let store = agent.run(ask("say hi").await).await.unwrap();
+    let trajectory = store.read().await["trajectory"].clone();
+    assert_eq!(trajectory[0]["observation"], json!("hello\n"));
+    assert_eq!(trajectory[1]["error"], json!(true));
+    assert!(trajectory[1]["observation"]
+        .as_str()
+        .unwrap()
+        .contains("exit code 1"));
+}
@@ -0,0 +1,99 @@
+use agentflow::prelude::*;
+use serde_json::json;
+
+/// Writes "draft N", where N counts the generator calls.
+fn writer() -> SimpleNode {
+    create_node(|store: SharedStore| async move {
+        let mut guard = store.write().await;
+        let n = guard
+            .get("reflection_history")
+            .and_then(|h| h.as_array())
+            .map_or(0, Vec::len)
+            + 1;
+        let feedback = guard.get("feedback").cloned();
+        guard.insert("draft".into(), json!(format!("draft {n}")));
+        guard.insert("seen_feedback".into(), feedback.unwrap_or(json!(null)));
+        drop(guard);
+        store
+    })
+}
+
+/// Scores drafts from a fixed list and approves at 9 or more.
+fn editor(scores: &'static [f64]) -> SimpleNode {
+    create_node(move |store: SharedStore| async move {
+        let mut guard = store.write().await;
+        let n = guard["draft"].as_str().unwrap()[6..]
+            .parse::<usize>()
+            .unwrap();
+        let score = scores[n - 1];
+        guard.insert("critique".into(), json!(format!("critique {n}")));
+        guard.insert("score".into(), json!(score));
+        guard.insert("approved".into(), json!(score >= 9.0));
+        drop(guard);
+        store
+    })
+}
+
+#[tokio::test]
+async fn test_reflection_stops_when_critic_approves() {
+    let reflection = Reflection::new(writer(), editor(&[4.0, 9.5, 10.0])).with_max_rounds(5);
+    let store = reflection.run(Store::new().into_shared()).await;
+    let guard = store.read().await;
+
+    assert_eq!(guard["draft"], json!("draft 2"));
+    assert_eq!(guard["approved"], json!(true));
+    assert_eq!(guard["seen_feedback"], json!("critique 1"));
+    assert!(!guard.contains_key("feedback"));
+    let history = guard["reflection_history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["critique"], json!("critique 1"));
+    assert_eq!(history[1]["approved"], json!(true));
+}
+
+#[tokio::test]
+async fn test_reflection_returns_best_draft_inside_a_flow() {
+    let reflection = Reflection::new(writer(), editor(&[5.0, 8.0, 6.0]))
+        .with_max_rounds(3)
+        .with_approval(|store| store["score"].as_f64() == Some(10.0))
+        .with_best_draft();
+
+    let mut flow = Flow::new();
+    flow.add_node("reflect", Box::new(reflection.clone()));
+    let store = flow.run(Store::new().into_shared()).await;
+    let guard = store.read().await;
+    assert_eq!(guard["draft"], json!("draft 2"));
+    assert_eq!(guard["critique"], json!("critique 2"));
+    assert_eq!(guard["score"], json!(8.0));
+    assert_eq!(guard["approved"], json!(false));
+    assert_eq!(guard["reflection_history"].as_array().unwrap().len(), 3);
+    drop(guard);
+
+    let mut last = reflection.with_approval(|_| false);
+    last.best_draft = false;
+    let store = last.run(Store::new().into_shared()).await;
+    assert_eq!(store.read().await["draft"], json!("draft 3"));
+}
+
+#[tokio::test]
+async fn test_reflection_clears_previous_verdict_before_critique() {
+    // Grades the first draft only; later rounds write nothing.
+    let silent_after_first = create_node(|store: SharedStore| async move {
+        let mut guard = store.write().await;
+        if guard["draft"] == json!("draft 1") {
+            guard.insert("critique".into(), json!("critique 1"));
+            guard.insert("score".into(), json!(7.0));
+            guard.insert("approved".into(), json!(false));
+        }
+        drop(guard);
+        store
+    });
+    let store = Reflection::new(writer(), silent_after_first)
+        .with_max_rounds(2)
+        .run(Store::new().into_shared())
+        .await;
+    let guard = store.read().await;
+    let history = guard["reflection_history"].as_array().unwrap();
+    assert_eq!(history[1]["critique"], json!(null));
+    assert_eq!(history[1]["score"], json!(null));
+    assert!(!guard.contains_key("score"));
+}
@@ -0,0 +1,126 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::resource_pool::{
+    PrioritySemaphore, RateLimit, RateLimiter, ResourcePool, Throttled,
+};
+use agentflow::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_semaphore_serves_higher_priority_first_then_fifo() {
+    let sem = PrioritySemaphore::new("llm", 1);
+    let held = sem.acquire(0).await;
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let mut handles = Vec::new();
+    for (label, priority) in [("low-1", 0), ("low-2", 0), ("high", 5)] {
+        let sem = sem.clone();
+        let order = order.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = sem.acquire(priority).await;
+            order.lock().unwrap().push(label);
+        }));
+        // Make arrival order deterministic.
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(sem.metrics().waiting, 3);
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    drop(held);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec!["high", "low-1", "low-2"]);
+
+    let metrics = sem.metrics();
+    assert_eq!(metrics.acquisitions, 4);
+    assert_eq!(metrics.waiting, 0);
+    assert!(metrics.max_wait >= Duration::from_millis(20));
+    assert_eq!(sem.available(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_paces_callers_and_rejects_oversized_requests() {
+    let limiter = RateLimiter::new("requests", RateLimit::per_second(50.0).with_burst(1.0));
+    let started = Instant::now();
+    for _ in 0..4 {
+        limiter.acquire(1.0, 0).await.unwrap();
+    }
+    // One immediately, then three refills at 20 ms each.
+    assert!(started.elapsed() >= Duration::from_millis(55));
+    assert_eq!(limiter.metrics().acquisitions, 4);
+    assert!(limiter.metrics().total_wait >= Duration::from_millis(55));
+
+    let tokens = RateLimiter::new("tokens", RateLimit::per_minute(1000.0));
+    let err = tokens.acquire(5000.0, 0).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::ExecutionLimitExceeded(_)));
+    assert!(tokens.acquire(600.0, 0).await.unwrap() < Duration::from_millis(5));
+}
+
+#[tokio::test]
+async fn test_rate_limiter_rejects_invalid_amounts_and_rates() {
+    let limiter = RateLimiter::new("requests", RateLimit::per_second(10.0));
+    for amount in [f64::NAN, f64::INFINITY, -1.0] {
+        let err = limiter.acquire(amount, 0).await.unwrap_err();
+        assert!(matches!(err, AgentFlowError::ExecutionLimitExceeded(_)));
+    }
+    assert_eq!(limiter.metrics().acquisitions, 0);
+
+    let infinite = RateLimiter::new("infinite", RateLimit::per_second(f64::INFINITY));
+    assert!(infinite.acquire(1.0, 0).await.is_err());
+
+    // A wait too long for a `Duration` waits instead of panicking.
+    let slow = RateLimiter::new("slow", RateLimit::per_second(1e-20));
+    slow.acquire(1.0, 0).await.unwrap();
+    let pending = tokio::time::timeout(Duration::from_millis(20), slow.acquire(1.0, 0)).await;
+    assert!(pending.is_err());
+}
+
+#[tokio::test]
+async fn test_throttled_nodes_share_global_pool_across_flows() {
+    let pool = ResourcePool::global();
+    let slots = pool.register_semaphore("test.shared_slots", 2);
+    // Registering again returns the same resource, ignoring the new size.
+    assert_eq!(
+        pool.register_semaphore("test.shared_slots", 10).permits(),
+        2
+    );
+    assert!(pool.semaphore("test.missing").is_err());
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let work = {
+        let (running, peak) = (running.clone(), peak.clone());
+        create_result_node(move |store: SharedStore| {
+            let (running, peak) = (running.clone(), peak.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(15)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(store)
+            }
+        })
+    };
+
+    let mut runs = Vec::new();
+    for _ in 0..6 {
+        let node = Throttled::new(work.clone()).with_semaphore(
+            ResourcePool::global()
+                .semaphore("test.shared_slots")
+                .unwrap(),
+        );
+        let mut flow = Flow::new();
+        flow.add_result_node("work", Box::new(node));
+        runs.push(tokio::spawn(async move {
+            flow.run_safe(Store::new().into_shared()).await
+        }));
+    }
+    for run in runs {
+        run.await.unwrap().unwrap();
+    }
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    assert_eq!(pool.metrics()["test.shared_slots"].acquisitions, 6);
+    assert_eq!(slots.available(), 2);
+}
@@ -0,0 +1,158 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::node::create_retry_node_with_policy;
+use agentflow::core::retry::{Backoff, RetryPolicy};
+use agentflow::prelude::*;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn test_exponential_schedule_caps_and_honours_retry_after() {
+    let policy = RetryPolicy::exponential(5, ms(100), ms(350));
+    let mut retry = policy.start();
+    let delays: Vec<_> = std::iter::from_fn(|| retry.next_delay(None)).collect();
+    assert_eq!(delays, vec![ms(100), ms(200), ms(350), ms(350)]);
+    assert_eq!(retry.attempts(), 5);
+
+    let limited = AgentFlowError::RateLimited {
+        message: "429".into(),
+        retry_after: Some(ms(900)),
+    };
+    assert_eq!(policy.start().next_delay(Some(&limited)), Some(ms(900)));
+    assert_eq!(
+        policy
+            .clone()
+            .with_retry_after(false)
+            .start()
+            .next_delay(Some(&limited)),
+        Some(ms(100))
+    );
+
+    // A delay that would end past `max_elapsed` is not started.
+    let capped = policy.with_max_elapsed(ms(150));
+    let mut retry = capped.start();
+    assert_eq!(retry.next_delay(None), Some(ms(100)));
+    assert_eq!(retry.next_delay(None), None);
+}
+
+#[test]
+fn test_exponential_schedule_saturates_at_unbounded_max() {
+    let policy = RetryPolicy::exponential(80, Duration::from_secs(1), Duration::MAX);
+    let mut retry = policy.start();
+    let last = std::iter::from_fn(|| retry.next_delay(None))
+        .last()
+        .unwrap();
+    assert_eq!(last, Duration::MAX);
+}
+
+#[test]
+fn test_decorrelated_jitter_stays_within_bounds() {
+    let policy = RetryPolicy::decorrelated_jitter(50, ms(10), ms(200));
+    assert_eq!(
+        policy.backoff,
+        Backoff::DecorrelatedJitter {
+            base: ms(10),
+            max: ms(200)
+        }
+    );
+    let mut retry = policy.start();
+    let mut previous = ms(10);
+    while let Some(delay) = retry.next_delay(None) {
+        assert!(delay >= ms(10) && delay <= ms(200), "{delay:?}");
+        assert!(delay <= previous * 3, "{delay:?} > 3 × {previous:?}");
+        previous = delay;
+    }
+    assert_eq!(retry.attempts(), 50);
+}
+
+#[tokio::test]
+async fn test_agent_policy_predicates_over_errors_and_output() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    // Fails once with a normally fatal error, then returns an unusable reply
+    // once, then a valid one.
+    let node = create_result_node(move |store: SharedStore| {
+        let n = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            match n {
+                0 => Err(AgentFlowError::InvalidOutput("truncated".into())),
+                1 => Ok(store),
+                _ => {
+                    store.write().await.insert("reply".into(), json!("ok"));
+                    Ok(store)
+                }
+            }
+        }
+    });
+
+    let policy = RetryPolicy::new(4)
+        .retry_if(|e| matches!(e, AgentFlowError::InvalidOutput(_)))
+        .retry_if_output(|out| !out.contains_key("reply"));
+    let result = Agent::with_policy(node.clone(), policy)
+        .run_result(Store::new().into_shared())
+        .await
+        .unwrap();
+    assert_eq!(result.read().await["reply"], json!("ok"));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // Without the error predicate, InvalidOutput is fatal.
+    calls.store(0, Ordering::SeqCst);
+    let err = Agent::with_policy(node, RetryPolicy::new(4))
+        .run_result(Store::new().into_shared())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AgentFlowError::InvalidOutput(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_retry_nodes_accept_policies() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let node = create_retry_node_with_policy(
+        |_store: SharedStore| async { Value::Null },
+        move |_store: &SharedStore, _prep: &Value| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Err::<Value, _>(AgentFlowError::NotFound("model".into())) }
+        },
+        |store: SharedStore, _prep: &Value, exec: &Value| {
+            let exec = exec.clone();
+            async move {
+                store.write().await.insert("exec".into(), exec);
+                store
+            }
+        },
+        RetryPolicy::fixed(5, ms(1)).retry_if(|e| e.is_retryable()),
+        None,
+    );
+    let result = node.call(Store::new().into_shared()).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert!(result.read().await["exec"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("NotFound"));
+
+    let corrective = create_corrective_retry_node_with_policy(
+        |store: SharedStore| async move {
+            let hint = store.read().await.get("last_error").cloned();
+            match hint {
+                Some(_) => {
+                    store.write().await.insert("fixed".into(), json!(true));
+                    Ok(store)
+                }
+                None => Err(AgentFlowError::InvalidOutput("missing field".into())),
+            }
+        },
+        RetryPolicy::exponential(3, ms(1), ms(5)),
+        "last_error",
+    );
+    let result = corrective.call(Store::new().into_shared()).await;
+    let guard = result.read().await;
+    assert_eq!(guard["fixed"], json!(true));
+    assert!(!guard.contains_key("last_error"));
+}
@@ -0,0 +1,117 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::patterns::router::{
+    Classification, KeywordClassifier, RouteEvent, RouteReason, Router,
+};
+use agentflow::prelude::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+fn handler(name: &'static str) -> ResultNode {
+    create_result_node(move |store: SharedStore| async move {
+        store.write().await.insert("handled_by".into(), json!(name));
+        Ok(store)
+    })
+}
+
+/// Classifier that reports a fixed verdict and counts its calls.
+fn fixed(label: &'static str, confidence: f64, calls: Arc<AtomicUsize>) -> ResultNode {
+    create_result_node(move |store: SharedStore| {
+        calls.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Classification::new(label, confidence).save(&store).await?;
+            Ok(store)
+        }
+    })
+}
+
+async fn input(text: &str) -> SharedStore {
+    let store = Store::new().into_shared();
+    store.write().await.insert("input".into(), json!(text));
+    store
+}
+
+#[tokio::test]
+async fn test_router_applies_threshold_and_fallback() {
+    let events: Arc<Mutex<Vec<RouteEvent>>> = Arc::default();
+    let log = events.clone();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let build = |label, confidence| {
+        let log = log.clone();
+        Router::new(fixed(label, confidence, calls.clone()))
+            .with_route("billing", handler("billing"))
+            .with_route("tech", handler("tech"))
+            .with_min_confidence(0.7)
+            .with_fallback(handler("clarify"))
+            .on_route(move |e| log.lock().unwrap().push(e.clone()))
+    };
+
+    let store = build("billing", 0.9).route(input("x").await).await.unwrap();
+    assert_eq!(store.read().await["handled_by"], json!("billing"));
+    assert_eq!(store.read().await["route"]["reason"], json!("matched"));
+
+    let store = build("billing", 0.4).route(input("x").await).await.unwrap();
+    assert_eq!(store.read().await["handled_by"], json!("clarify"));
+
+    let store = build("weather", 0.99)
+        .route(input("x").await)
+        .await
+        .unwrap();
+    assert_eq!(store.read().await["handled_by"], json!("clarify"));
+
+    let reasons: Vec<_> = events.lock().unwrap().iter().map(|e| e.reason).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            RouteReason::Matched,
+            RouteReason::LowConfidence,
+            RouteReason::UnknownLabel
+        ]
+    );
+    assert_eq!(events.lock().unwrap()[1].target, "fallback");
+
+    let no_fallback = Router::new(create_result_node(|s: SharedStore| async move { Ok(s) }))
+        .with_route("billing", handler("billing"));
+    let err = no_fallback.route(input("x").await).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::NotFound(_)));
+}
+
+#[tokio::test]
+async fn test_router_tries_keywords_before_escalating() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let keywords = KeywordClassifier::new()
+        .with_rule("billing", &["invoice", "refund"])
+        .with_rule("tech", &["crash", "error"]);
+    let router = Router::new(fixed("tech", 0.8, calls.clone()))
+        .with_pre_classifier(keywords.clone())
+        .with_route("billing", handler("billing"))
+        .with_route("tech", handler("tech"))
+        .with_min_confidence(0.6);
+
+    let store = router
+        .route(input("Where is my REFUND?").await)
+        .await
+        .unwrap();
+    assert_eq!(store.read().await["handled_by"], json!("billing"));
+    assert_eq!(store.read().await["route"]["pre_classified"], json!(true));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    // Ambiguous (0.5 < 0.6) and unmatched input escalate to the classifier.
+    for text in ["refund after the crash", "hello"] {
+        let store = router.route(input(text).await).await.unwrap();
+        assert_eq!(store.read().await["handled_by"], json!("tech"));
+        assert_eq!(store.read().await["route"]["pre_classified"], json!(false));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // The keyword classifier also reads the conversation.
+    let store = Store::new().into_shared();
+    Conversation::append(&store, ChatMessage::user("app error on start"))
+        .await
+        .unwrap();
+    let store = keywords.call(store).await.unwrap();
+    assert_eq!(
+        Classification::load(&store).await.unwrap(),
+        Some(Classification::new("tech", 1.0))
+    );
+}
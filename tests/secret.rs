@@ -0,0 +1,104 @@
+use agentflow::core::secret::{
+    mask_leaked_secrets, redact_map, redaction_hook, SecretString, REDACTED,
+};
+use agentflow::core::store::Store;
+use agentflow::prelude::*;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_secret_is_only_readable_explicitly() {
+    let store = Store::new();
+    store.set_secret("api_key", "sk-live-123").await;
+
+    assert_eq!(store.get_string("api_key").await, None);
+    let secret = store.require_secret("api_key").await.unwrap();
+    assert_eq!(secret.expose_secret(), "sk-live-123");
+
+    assert_eq!(format!("{secret:?}"), REDACTED);
+    assert_eq!(serde_json::to_string(&secret).unwrap(), "\"[REDACTED]\"");
+    assert!(!format!("{store:?}").contains("sk-live-123"));
+}
+
+#[tokio::test]
+async fn test_snapshot_never_contains_secret() {
+    let store = Store::new();
+    store.set_secret("token", "tok-abc").await;
+    store.set_string("log", "calling with tok-abc").await;
+
+    let path = std::env::temp_dir().join(format!("agentflow_{}_secret.json", std::process::id()));
+    store.save_to(&path).await.unwrap();
+    let raw = std::fs::read_to_string(&path).unwrap();
+
+    assert!(!raw.contains("tok-abc"));
+    assert!(raw.contains(REDACTED));
+
+    // Reloaded secrets are gone, and say so.
+    let reloaded = Store::load_from(&path).await.unwrap();
+    let _ = std::fs::remove_file(&path);
+    match reloaded.require_secret("token").await {
+        Err(AgentFlowError::TypeMismatch(msg)) => assert!(msg.contains("redacted"), "{msg}"),
+        other => panic!("expected TypeMismatch, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_masking_keeps_nested_secrets() {
+    let store = Store::new().into_shared();
+    store.write().await.insert(
+        "config".into(),
+        json!({
+            "auth": SecretString::new("sk-nested").into_value(),
+            "headers": [SecretString::new("sk-nested").into_value()],
+            "note": "sent sk-nested"
+        }),
+    );
+
+    assert_eq!(mask_leaked_secrets(&store).await, 1);
+    let guard = store.read().await;
+    let config = &guard["config"];
+    assert_eq!(
+        SecretString::from_value(&config["auth"])
+            .unwrap()
+            .expose_secret(),
+        "sk-nested"
+    );
+    assert!(SecretString::from_value(&config["headers"][0]).is_some());
+    assert_eq!(config["note"], json!("sent [REDACTED]"));
+}
+
+#[tokio::test]
+async fn test_redaction_hook_masks_leaks_in_flow() {
+    let leaky = create_node(|store: SharedStore| async move {
+        let key = {
+            let guard = store.read().await;
+            guard
+                .get("api_key")
+                .and_then(SecretString::from_value)
+                .map(|s| s.expose_secret().to_string())
+                .unwrap_or_default()
+        };
+        store
+            .write()
+            .await
+            .insert("response".into(), json!(format!("your key is {key}")));
+        store
+    });
+
+    let mut flow = Flow::new().with_post_node_hook(redaction_hook());
+    flow.add_node("leaky", leaky);
+
+    let store = Store::new();
+    store.set_secret("api_key", "sk-999").await;
+    let result = flow.run(store.into_shared()).await;
+
+    let guard = result.read().await;
+    assert_eq!(guard["response"], json!("your key is [REDACTED]"));
+    // The secret itself is still available to nodes that ask for it.
+    assert_eq!(
+        SecretString::from_value(&guard["api_key"])
+            .unwrap()
+            .expose_secret(),
+        "sk-999"
+    );
+    assert_eq!(redact_map(&guard)["api_key"], json!(REDACTED));
+}
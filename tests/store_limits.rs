@@ -0,0 +1,152 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::limits::{EvictionPolicy, EvictionReason, StoreEvent, StoreLimits};
+use agentflow::core::store::Store;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_per_key_cap_rejects_large_values() {
+    let store = Store::new().with_limits(StoreLimits::new().with_max_key_bytes(32));
+
+    store.set_string("small", "ok").await;
+    let result = store.try_set("big", json!("x".repeat(100))).await;
+
+    assert!(matches!(
+        result,
+        Err(AgentFlowError::ExecutionLimitExceeded(_))
+    ));
+    assert!(store.contains_key("small").await);
+    assert!(!store.contains_key("big").await);
+}
+
+#[tokio::test]
+async fn test_lru_evicts_least_recently_used() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    // Each entry below is 1 (key) + 12 (quoted 10-char string) = 13 bytes.
+    let store = Store::new().with_limits(
+        StoreLimits::new()
+            .with_max_total_bytes(40)
+            .with_eviction(EvictionPolicy::Lru)
+            .on_event(move |e| sink.lock().unwrap().push(e.clone())),
+    );
+
+    store.set_string("a", "aaaaaaaaaa").await;
+    store.set_string("b", "bbbbbbbbbb").await;
+    store.set_string("c", "cccccccccc").await;
+    // Touch "a" so "b" becomes the least recently used.
+    assert!(store.get_string("a").await.is_some());
+    store.set_string("d", "dddddddddd").await;
+
+    let mut keys = store.keys().await;
+    keys.sort();
+    assert_eq!(keys, vec!["a", "c", "d"]);
+    assert_eq!(
+        events.lock().unwrap().as_slice(),
+        &[StoreEvent::Evicted {
+            key: "b".into(),
+            size_bytes: 13,
+            reason: EvictionReason::Capacity,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_oldest_first_and_protected_keys() {
+    let store = Store::new().with_limits(
+        StoreLimits::new()
+            .with_max_total_bytes(30)
+            .with_eviction(EvictionPolicy::OldestFirst)
+            .protect("a"),
+    );
+
+    store.set_string("a", "aaaaaaaaaa").await;
+    store.set_string("b", "bbbbbbbbbb").await;
+    assert!(store.get_string("b").await.is_some());
+    store.set_string("c", "cccccccccc").await;
+
+    // "a" is oldest but protected, so "b" goes.
+    assert!(store.contains_key("a").await);
+    assert!(!store.contains_key("b").await);
+
+    // Only a protected key could make room for this one: rejected.
+    let result = store.try_set("big", json!("y".repeat(20))).await;
+    assert!(matches!(
+        result,
+        Err(AgentFlowError::ExecutionLimitExceeded(_))
+    ));
+    assert!(store.contains_key("a").await);
+}
+
+#[tokio::test]
+async fn test_value_larger_than_the_store_is_rejected() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let store = Store::new().with_limits(
+        StoreLimits::new()
+            .with_max_total_bytes(30)
+            .on_event(move |e| sink.lock().unwrap().push(e.clone())),
+    );
+    store.set_string("a", "aaaaaaaaaa").await;
+
+    // 3 (key) + 42 (quoted 40-char string) bytes can never fit.
+    let err = store
+        .try_set("big", json!("z".repeat(40)))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("45 bytes"), "{err}");
+    assert!(store.contains_key("a").await);
+    assert_eq!(
+        events.lock().unwrap().as_slice(),
+        &[StoreEvent::Rejected {
+            key: "big".into(),
+            size_bytes: 45,
+            limit: 30,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_ttl_expires_keys() {
+    let store = Store::new().with_limits(StoreLimits::new());
+
+    store
+        .set_with_ttl("session", json!("temp"), Duration::from_millis(20))
+        .await
+        .unwrap();
+    store.set_string("durable", "stay").await;
+    assert!(store.contains_key("session").await);
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    assert_eq!(store.get("session").await, None);
+    assert_eq!(store.keys().await, vec!["durable".to_string()]);
+}
+
+#[tokio::test]
+async fn test_custom_policy_refusal_is_reported_separately() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let store = Store::new().with_limits(
+        StoreLimits::new()
+            .with_max_total_bytes(30)
+            .with_eviction(EvictionPolicy::Custom(Arc::new(|_| None)))
+            .on_event(move |e| sink.lock().unwrap().push(e.clone())),
+    );
+
+    store.set_string("a", "aaaaaaaaaa").await;
+    store.set_string("b", "bbbbbbbbbb").await;
+    let result = store.try_set("c", json!("cccccccccc")).await;
+
+    match result {
+        Err(AgentFlowError::ExecutionLimitExceeded(msg)) => {
+            assert!(msg.contains("refused"), "{msg}");
+        }
+        other => panic!("expected ExecutionLimitExceeded, got {other:?}"),
+    }
+    assert_eq!(
+        events.lock().unwrap().as_slice(),
+        &[StoreEvent::EvictionRefused { key: "c".into() }]
+    );
+}
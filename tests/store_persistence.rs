@@ -0,0 +1,125 @@
+use agentflow::core::persistence::{FileLogBackend, StoreFormat};
+use agentflow::core::store::Store;
+use serde_json::json;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("agentflow_{}_{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn test_save_and_load_roundtrip_json_and_binary() {
+    let store = Store::new();
+    store.set_string("name", "Alice").await;
+    store.set_i64("age", 30).await;
+    store.set("tags", json!(["a", "b"])).await;
+
+    for file in ["snapshot.json", "snapshot.bin"] {
+        let path = temp_path(file);
+        store.save_to(&path).await.unwrap();
+
+        let loaded = Store::load_from(&path).await.unwrap();
+        assert_eq!(loaded.get_string("name").await.as_deref(), Some("Alice"));
+        assert_eq!(loaded.get_i64("age").await, Some(30));
+        assert_eq!(loaded.get("tags").await, Some(json!(["a", "b"])));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    assert_eq!(StoreFormat::from_path("x.msgpack"), StoreFormat::Binary);
+    assert_eq!(StoreFormat::from_path("x.json"), StoreFormat::Json);
+}
+
+#[tokio::test]
+async fn test_file_log_backend_survives_restart() {
+    let path = temp_path("session.log");
+
+    {
+        let store = Store::with_backend(FileLogBackend::new(&path))
+            .await
+            .unwrap();
+        store.set_string("user", "Bob").await;
+        store.set_i64("turns", 1).await;
+        store.set_i64("turns", 2).await;
+        store.set_bool("scratch", true).await;
+        store.remove("scratch").await;
+    }
+
+    let restored = Store::with_backend(FileLogBackend::new(&path))
+        .await
+        .unwrap();
+    assert_eq!(restored.get_string("user").await.as_deref(), Some("Bob"));
+    assert_eq!(restored.get_i64("turns").await, Some(2));
+    assert!(!restored.contains_key("scratch").await);
+
+    // Compaction rewrites the log to one line per live key.
+    restored.compact().await.unwrap();
+    let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert_eq!(lines, 2);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_file_log_backend_skips_torn_final_line() {
+    let path = temp_path("torn.log");
+    std::fs::write(
+        &path,
+        "{\"op\":\"set\",\"key\":\"ok\",\"value\":1}\n{\"op\":\"set\",\"key\":\"bro",
+    )
+    .unwrap();
+
+    let store = Store::with_backend(FileLogBackend::new(&path))
+        .await
+        .unwrap();
+    assert_eq!(store.get_i64("ok").await, Some(1));
+    assert_eq!(store.len().await, 1);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_file_log_backend_appends_cleanly_after_torn_line() {
+    let path = temp_path("torn_append.log");
+    std::fs::write(
+        &path,
+        "{\"op\":\"set\",\"key\":\"ok\",\"value\":1}\n{\"op\":\"set\",\"key\":\"bro",
+    )
+    .unwrap();
+
+    let store = Store::with_backend(FileLogBackend::new(&path))
+        .await
+        .unwrap();
+    store.set_i64("after", 2).await;
+    drop(store);
+
+    let store = Store::with_backend(FileLogBackend::new(&path))
+        .await
+        .unwrap();
+    assert_eq!(store.get_i64("ok").await, Some(1));
+    assert_eq!(store.get_i64("after").await, Some(2));
+    assert_eq!(store.len().await, 2);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_file_log_backend_terminates_unterminated_final_line() {
+    let path = temp_path("unterminated.log");
+    std::fs::write(&path, "{\"op\":\"set\",\"key\":\"ok\",\"value\":1}").unwrap();
+
+    let store = Store::with_backend(FileLogBackend::new(&path))
+        .await
+        .unwrap();
+    store.set_i64("after", 2).await;
+    drop(store);
+
+    let store = Store::with_backend(FileLogBackend::new(&path))
+        .await
+        .unwrap();
+    assert_eq!(store.get_i64("ok").await, Some(1));
+    assert_eq!(store.get_i64("after").await, Some(2));
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,115 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::telemetry::{record_llm_usage, LlmUsage, LLM_USAGE_KEY};
+use agentflow::patterns::supervisor::{Delegation, Supervisor, DELEGATIONS_KEY};
+use agentflow::prelude::*;
+use serde_json::{json, Value};
+
+/// Coordinator that hands out `plan` in order, then finishes with the last
+/// worker's result.
+fn scripted(plan: Vec<Delegation>) -> ResultNode {
+    create_result_node(move |store: SharedStore| {
+        let plan = plan.clone();
+        async move {
+            let history = match store.read().await.get(DELEGATIONS_KEY) {
+                Some(Value::Array(items)) => items.clone(),
+                _ => Vec::new(),
+            };
+            match plan.get(history.len()) {
+                Some(next) => next.save(&store).await?,
+                None => {
+                    let last = history.last().map(|r| r["result"].clone());
+                    store
+                        .write()
+                        .await
+                        .insert("result".into(), last.unwrap_or(Value::Null));
+                }
+            }
+            Ok(store)
+        }
+    })
+}
+
+fn echo(prefix: &'static str) -> ResultNode {
+    create_result_node(move |store: SharedStore| async move {
+        let mut guard = store.write().await;
+        let task = guard["task"].as_str().unwrap_or_default().to_string();
+        let goal = guard.get("goal").cloned().unwrap_or(Value::Null);
+        guard.insert("scratch".into(), json!(true));
+        guard.insert(
+            "result".into(),
+            json!({"out": format!("{prefix}: {task}"), "goal": goal}),
+        );
+        drop(guard);
+        record_llm_usage(&store, LlmUsage::new("mock", 3, 2)).await;
+        Ok(store)
+    })
+}
+
+#[tokio::test]
+async fn test_supervisor_delegates_and_records_history() {
+    let supervisor = Supervisor::new(scripted(vec![
+        Delegation::new("researcher", "find facts"),
+        Delegation::new("ghost", "haunt"),
+        Delegation::new("flaky", "try"),
+        Delegation::new("writer", "summarize").with_input("tone", json!("terse")),
+    ]))
+    .with_worker("researcher", echo("research"))
+    .with_worker(
+        "flaky",
+        create_result_node(|store: SharedStore| async move {
+            record_llm_usage(&store, LlmUsage::new("mock", 7, 0)).await;
+            Err::<SharedStore, _>(AgentFlowError::InvalidOutput("no JSON".into()))
+        }),
+    )
+    .with_worker("writer", echo("write"))
+    .with_shared_keys(&["goal"]);
+    assert_eq!(
+        supervisor.worker_names(),
+        vec!["flaky", "researcher", "writer"]
+    );
+
+    let store = Store::new().into_shared();
+    store.write().await.insert("goal".into(), json!("report"));
+    store.write().await.insert("secret".into(), json!("hidden"));
+    let store = supervisor.run(store).await.unwrap();
+
+    let guard = store.read().await;
+    let history = guard[DELEGATIONS_KEY].as_array().unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[0]["result"]["out"], json!("research: find facts"));
+    assert_eq!(history[0]["result"]["goal"], json!("report"));
+    assert!(history[1]["error"].as_str().unwrap().contains("ghost"));
+    assert!(history[2]["error"].as_str().unwrap().contains("no JSON"));
+    assert_eq!(history[3]["worker"], json!("writer"));
+    // Scratch keys stay in the worker's sub-store; usage is forwarded, even
+    // from the worker that failed.
+    assert!(!guard.contains_key("scratch"));
+    assert!(!guard.contains_key("delegate"));
+    let usage = guard[LLM_USAGE_KEY].as_array().unwrap();
+    assert_eq!(usage.len(), 3);
+    assert_eq!(usage[1]["input_tokens"], json!(7));
+}
+
+#[tokio::test]
+async fn test_supervisor_nests_and_enforces_step_limit() {
+    let team = Supervisor::new(scripted(vec![Delegation::new("coder", "write code")]))
+        .with_worker("coder", echo("code"));
+    let lead = Supervisor::new(scripted(vec![Delegation::new("team", "build it")]))
+        .with_worker("team", team);
+    let store = lead.run(Store::new().into_shared()).await.unwrap();
+    let guard = store.read().await;
+    let record = &guard[DELEGATIONS_KEY][0];
+    assert_eq!(record["result"]["out"], json!("code: write code"));
+    assert_eq!(record[DELEGATIONS_KEY][0]["worker"], json!("coder"));
+    drop(guard);
+
+    let endless = create_result_node(|store: SharedStore| async move {
+        Delegation::new("coder", "again").save(&store).await?;
+        Ok(store)
+    });
+    let looping = Supervisor::new(endless)
+        .with_worker("coder", echo("code"))
+        .with_max_steps(3);
+    let err = looping.run(Store::new().into_shared()).await.unwrap_err();
+    assert!(matches!(err, AgentFlowError::ExecutionLimitExceeded(_)));
+}
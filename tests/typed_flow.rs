@@ -72,3 +72,27 @@ async fn test_typed_flow_run_safe_cycle() {
         _ => panic!("Expected limit exceeded error"),
     }
 }
+
+#[tokio::test]
+async fn test_typed_flow_run_safe_rejects_dangling_edge() {
+    let mut flow = TypedFlow::<MyState, Action>::new();
+
+    let a = create_typed_node(|mut store: TypedStore<MyState>| async move {
+        store.inner.step_a = true;
+        (store, Some(Action::Next))
+    });
+
+    flow.add_node("A", a);
+    flow.add_edge("A", Action::Next, "B"); // "B" was never registered
+
+    let store = TypedStore::new(MyState {
+        step_a: false,
+        step_b: false,
+        count: 0,
+    });
+
+    match flow.run_safe(store).await {
+        Err(AgentFlowError::GraphBuildError(msg)) => assert!(msg.contains("'B'")),
+        _ => panic!("Expected GraphBuildError"),
+    }
+}
@@ -0,0 +1,107 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::typed_flow::{create_typed_node, TypedFlow};
+use agentflow::core::typed_parallel::TypedParallel;
+use agentflow::core::typed_store::TypedStore;
+
+#[derive(Debug, Clone, Default)]
+struct Research {
+    topic: String,
+    findings: Vec<String>,
+    summary: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Action {
+    Summarize,
+    Loop,
+}
+
+fn merge_findings(mut base: Research, branches: Vec<Research>) -> Research {
+    for branch in branches {
+        base.findings.extend(branch.findings);
+    }
+    base
+}
+
+#[tokio::test]
+async fn test_typed_parallel_merges_branches_and_telemetry() {
+    let web = create_typed_node(|mut s: TypedStore<Research>| async move {
+        s.context.add_tokens(10);
+        s.inner.findings.push(format!("web:{}", s.inner.topic));
+        (s, None)
+    });
+    let docs = create_typed_node(|mut s: TypedStore<Research>| async move {
+        s.context.add_tokens(5);
+        s.inner.findings.push(format!("docs:{}", s.inner.topic));
+        (s, None)
+    });
+
+    let fan_out = TypedParallel::<Research, Action>::new(merge_findings)
+        .add_node_branch("web", web)
+        .add_node_branch("docs", docs);
+    assert_eq!(fan_out.len(), 2);
+
+    let mut store = TypedStore::new(Research {
+        topic: "rust".into(),
+        ..Default::default()
+    });
+    store.context.add_tokens(1);
+    let result = fan_out.run(store).await;
+
+    assert_eq!(result.inner.findings, vec!["web:rust", "docs:rust"]);
+    assert_eq!(result.context.token_usage, 16);
+    assert!(result.context.node_durations.contains_key("web"));
+    assert!(result.context.node_durations.contains_key("docs"));
+    assert!(!result.limit_exceeded);
+}
+
+#[tokio::test]
+async fn test_typed_parallel_as_node_in_typed_flow() {
+    let a = create_typed_node(|mut s: TypedStore<Research>| async move {
+        s.inner.findings.push("a".into());
+        (s, None)
+    });
+    let b = create_typed_node(|mut s: TypedStore<Research>| async move {
+        s.inner.findings.push("b".into());
+        (s, None)
+    });
+    let fan_out = TypedParallel::new(merge_findings)
+        .add_node_branch("a", a)
+        .add_node_branch("b", b)
+        .with_action(Action::Summarize);
+
+    let summarize = create_typed_node(|mut s: TypedStore<Research>| async move {
+        s.inner.summary = Some(s.inner.findings.join("+"));
+        (s, None)
+    });
+
+    let mut flow = TypedFlow::<Research, Action>::new();
+    flow.add_node("gather", Box::new(fan_out));
+    flow.add_node("summarize", summarize);
+    flow.add_edge("gather", Action::Summarize, "summarize");
+
+    let result = flow.run(TypedStore::new(Research::default())).await;
+    assert_eq!(result.inner.summary.as_deref(), Some("a+b"));
+    assert!(result.context.node_durations.contains_key("a"));
+    assert!(result.context.node_durations.contains_key("gather"));
+}
+
+#[tokio::test]
+async fn test_typed_parallel_run_safe_propagates_branch_errors() {
+    let looping =
+        create_typed_node(|s: TypedStore<Research>| async move { (s, Some(Action::Loop)) });
+    let mut bounded = TypedFlow::<Research, Action>::new().with_max_steps(3);
+    bounded.add_node("loop", looping);
+    bounded.add_edge("loop", Action::Loop, "loop");
+
+    let fan_out = TypedParallel::new(merge_findings).add_branch(bounded);
+
+    let store = fan_out.run(TypedStore::new(Research::default())).await;
+    assert!(store.limit_exceeded);
+
+    let result = fan_out.run_safe(TypedStore::new(Research::default())).await;
+    assert!(matches!(
+        result,
+        Err(AgentFlowError::ExecutionLimitExceeded(_))
+    ));
+}
@@ -0,0 +1,168 @@
+use agentflow::core::error::AgentFlowError;
+use agentflow::core::telemetry::LlmUsage;
+use agentflow::core::typed_flow::{create_typed_node, create_typed_result_node, TypedFlow};
+use agentflow::core::typed_store::TypedStore;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+struct Job {
+    attempts: usize,
+    output: Option<String>,
+    handled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Action {
+    Done,
+}
+
+#[tokio::test]
+async fn test_result_node_retries_from_snapshot() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let flaky = create_typed_result_node(move |mut s: TypedStore<Job>| {
+        let counter = counter.clone();
+        async move {
+            // Mutations from failed attempts must not leak into the next one.
+            s.inner.attempts += 1;
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err(AgentFlowError::Timeout("upstream slow".into()));
+            }
+            s.inner.output = Some("ok".into());
+            Ok((s, None))
+        }
+    });
+
+    let mut flow = TypedFlow::<Job, Action>::new();
+    flow.add_result_node_with_retry("fetch", flaky, 3, 0);
+
+    let result = flow
+        .run_safe(TypedStore::new(Job::default()))
+        .await
+        .unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(result.inner.attempts, 1);
+    assert_eq!(result.inner.output.as_deref(), Some("ok"));
+    assert!(result.last_error.is_none());
+}
+
+#[tokio::test]
+async fn test_result_node_error_edge_routes_to_handler() {
+    let failing = create_typed_result_node(|_s: TypedStore<Job>| async move {
+        Err::<(TypedStore<Job>, Option<Action>), _>(AgentFlowError::NodeFailure("boom".into()))
+    });
+    let handler = create_typed_node(|mut s: TypedStore<Job>| async move {
+        s.inner.handled = matches!(s.last_error, Some(AgentFlowError::NodeFailure(_)));
+        s.last_error = None;
+        (s, Some(Action::Done))
+    });
+    let finish = create_typed_node(|mut s: TypedStore<Job>| async move {
+        s.inner.output = Some("recovered".into());
+        (s, None)
+    });
+
+    let mut flow = TypedFlow::<Job, Action>::new();
+    flow.add_result_node("work", failing);
+    flow.add_node("handler", handler);
+    flow.add_node("finish", finish);
+    flow.add_error_edge("work", "handler");
+    flow.add_edge("handler", Action::Done, "finish");
+
+    let result = flow
+        .run_safe(TypedStore::new(Job::default()))
+        .await
+        .unwrap();
+    assert!(result.inner.handled);
+    assert_eq!(result.inner.output.as_deref(), Some("recovered"));
+    assert!(result.last_error.is_none());
+}
+
+#[tokio::test]
+async fn test_result_node_error_without_edge_stops_flow() {
+    let build = || {
+        let before = create_typed_node(|mut s: TypedStore<Job>| async move {
+            s.inner.attempts = 7;
+            (s, Some(Action::Done))
+        });
+        let failing = create_typed_result_node(|_s: TypedStore<Job>| async move {
+            Err::<(TypedStore<Job>, Option<Action>), _>(AgentFlowError::Custom("bad".into()))
+        });
+        let mut flow = TypedFlow::<Job, Action>::new();
+        flow.add_node("before", before);
+        flow.add_result_node("work", failing);
+        flow.add_edge("before", Action::Done, "work");
+        flow
+    };
+
+    let result = build().run(TypedStore::new(Job::default())).await;
+    assert_eq!(result.inner.attempts, 7);
+    assert_eq!(
+        result.last_error,
+        Some(AgentFlowError::Custom("bad".into()))
+    );
+
+    let err = build()
+        .run_safe(TypedStore::new(Job::default()))
+        .await
+        .unwrap_err();
+    assert_eq!(err, AgentFlowError::Custom("bad".into()));
+
+    let mut dangling = build();
+    dangling.add_error_edge("work", "missing");
+    assert!(matches!(
+        dangling.validate(),
+        Err(AgentFlowError::GraphBuildError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_result_node_retries_only_retryable_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let invalid = create_typed_result_node(move |_s: TypedStore<Job>| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Err::<(TypedStore<Job>, Option<Action>), _>(AgentFlowError::InvalidOutput(
+                "not JSON".into(),
+            ))
+        }
+    });
+
+    let mut flow = TypedFlow::<Job, Action>::new();
+    flow.add_result_node_with_retry("parse", invalid, 3, 0);
+
+    let result = flow.run(TypedStore::new(Job::default())).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(matches!(
+        result.last_error,
+        Some(AgentFlowError::InvalidOutput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_failed_attempts_usage_is_kept() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let flaky = create_typed_result_node(move |mut s: TypedStore<Job>| {
+        let counter = counter.clone();
+        async move {
+            s.context.record_llm_call(LlmUsage::new("model", 100, 50));
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err(AgentFlowError::Timeout("upstream slow".into()));
+            }
+            Ok((s, None))
+        }
+    });
+
+    let mut flow = TypedFlow::<Job, Action>::new();
+    flow.add_result_node_with_retry("draft", flaky, 3, 0);
+
+    let result = flow
+        .run_safe(TypedStore::new(Job::default()))
+        .await
+        .unwrap();
+    assert_eq!(result.context.total_calls(), 3);
+    assert_eq!(result.context.usage_by_node["draft"].calls, 3);
+    assert_eq!(result.context.token_usage, 450);
+}